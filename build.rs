@@ -0,0 +1,78 @@
+//! Generates `shader_with_config!` from `src/config.rs`, so the values the shaders share with the
+//! engine are only written down once.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+  println!("cargo:rerun-if-changed=src/config.rs");
+  let config = fs::read_to_string("src/config.rs").unwrap();
+
+  // Shaders compare these with unsigned integers, apart from the SSAO kernel size
+  let cluster_grid = config_value(&config, "CLUSTER_GRID");
+  let defines = [
+    ("CLUSTER_GRID_X", format!("{}u", cluster_grid[0])),
+    ("CLUSTER_GRID_Y", format!("{}u", cluster_grid[1])),
+    ("CLUSTER_GRID_Z", format!("{}u", cluster_grid[2])),
+    (
+      "MAX_LIGHTS_PER_CLUSTER",
+      format!("{}u", config_value(&config, "MAX_LIGHTS_PER_CLUSTER")[0]),
+    ),
+    (
+      "SHADOW_CASCADE_COUNT",
+      format!("{}u", config_value(&config, "SHADOW_CASCADE_COUNT")[0]),
+    ),
+    (
+      "SSAO_KERNEL_SIZE",
+      config_value(&config, "SSAO_KERNEL_SIZE")[0].to_string(),
+    ),
+  ];
+  let defines: Vec<String> = defines
+    .iter()
+    .map(|(name, value)| format!("(\"{}\", \"{}\")", name, value))
+    .collect();
+
+  let shader_macro = format!(
+    "/// Compiles a shader like `vulkano_shaders::shader!`, defining the values from `config.rs`
+/// which the shaders share with the engine. Shaders don't define these themselves, so they
+/// fail to compile without them.
+macro_rules! shader_with_config {{
+  ($($option:ident: $value:tt),* $(,)?) => {{
+    vulkano_shaders::shader! {{
+      $($option: $value,)*
+      define: [{}],
+    }}
+  }};
+}}
+",
+    defines.join(", ")
+  );
+
+  let out_dir = env::var("OUT_DIR").unwrap();
+  fs::write(Path::new(&out_dir).join("shader_config.rs"), shader_macro).unwrap();
+}
+
+/// Returns the numbers making up the value of the constant `name` in `config`, which is either a
+/// single number or an array of them.
+fn config_value(config: &str, name: &str) -> Vec<u32> {
+  let declaration = format!("pub const {}:", name);
+  let value = config
+    .lines()
+    .find(|line| line.starts_with(&declaration))
+    .and_then(|line| line.split_once('='))
+    .unwrap_or_else(|| panic!("{} is not defined in src/config.rs", name))
+    .1;
+
+  value
+    .trim()
+    .trim_end_matches(';')
+    .trim_matches(|c| c == '[' || c == ']')
+    .split(',')
+    .map(|number| {
+      number
+        .trim()
+        .replace('_', "")
+        .parse()
+        .unwrap_or_else(|_| panic!("{} in src/config.rs must be written as plain numbers", name))
+    })
+    .collect()
+}
@@ -2,12 +2,14 @@ use std::env;
 
 use log::info;
 
-use mdr_engine::resources::texture::{MdrSamplerMode, MdrTextureCreateInfo};
+use mdr_engine::resources::texture::{
+  MdrFilter, MdrMipmapMode, MdrSamplerMode, MdrTextureCreateInfo,
+};
 use mdr_engine::resources::{MdrColorType, MdrMaterialCreateInfo, MdrRgb};
 use mdr_engine::{
   logger,
-  scene::{MdrLight, MdrRenderObject},
-  MdrEngine, MdrEngineOptions,
+  scene::{MdrAntialiasing, MdrFlyController, MdrLight, MdrRenderObject},
+  MdrEngine, MdrEngineOptions, MdrRenderPath,
 };
 
 // Some functions and constants extraneous to the example
@@ -26,6 +28,8 @@ fn main() {
 
   let opts = MdrEngineOptions {
     debug: DEBUG_ENABLED,
+    msaa_samples: 4,
+    render_path: MdrRenderPath::Forward,
   };
   let (mut engine, event_loop) = MdrEngine::new(opts);
 
@@ -56,6 +60,9 @@ fn main() {
         source: asset("textures/metal_plates/base_color.png").as_str(),
         color_type: MdrColorType::SRGBA,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "metal_plates_base_color",
     )
@@ -67,6 +74,9 @@ fn main() {
         source: asset("textures/metal_plates/roughness.png").as_str(),
         color_type: MdrColorType::NonColorData,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "metal_plates_roughness",
     )
@@ -78,10 +88,41 @@ fn main() {
         source: asset("textures/metal_plates/normal.png").as_str(),
         color_type: MdrColorType::NonColorData,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "metal_plates_normal",
     )
     .unwrap();
+  let metal_plates_metalness = engine
+    .manage_resources()
+    .load_texture(
+      MdrTextureCreateInfo {
+        source: asset("textures/metal_plates/metalness.png").as_str(),
+        color_type: MdrColorType::NonColorData,
+        sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
+      },
+      "metal_plates_metalness",
+    )
+    .unwrap();
+  let metal_plates_occlusion = engine
+    .manage_resources()
+    .load_texture(
+      MdrTextureCreateInfo {
+        source: asset("textures/metal_plates/occlusion.png").as_str(),
+        color_type: MdrColorType::NonColorData,
+        sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
+      },
+      "metal_plates_occlusion",
+    )
+    .unwrap();
   // Blue tiles
   let blue_tiles_base_color = engine
     .manage_resources()
@@ -90,6 +131,9 @@ fn main() {
         source: asset("textures/blue_tiles/base_color.png").as_str(),
         color_type: MdrColorType::SRGBA,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "blue_tiles_base_color",
     )
@@ -101,6 +145,9 @@ fn main() {
         source: asset("textures/blue_tiles/roughness.png").as_str(),
         color_type: MdrColorType::NonColorData,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "blue_tiles_roughness",
     )
@@ -112,10 +159,27 @@ fn main() {
         source: asset("textures/blue_tiles/normal.png").as_str(),
         color_type: MdrColorType::NonColorData,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "blue_tiles_normal",
     )
     .unwrap();
+  let blue_tiles_occlusion = engine
+    .manage_resources()
+    .load_texture(
+      MdrTextureCreateInfo {
+        source: asset("textures/blue_tiles/occlusion.png").as_str(),
+        color_type: MdrColorType::NonColorData,
+        sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
+      },
+      "blue_tiles_occlusion",
+    )
+    .unwrap();
   // Wood planks
   let wood_planks_base_color = engine
     .manage_resources()
@@ -124,6 +188,9 @@ fn main() {
         source: asset("textures/wood_planks/base_color.png").as_str(),
         color_type: MdrColorType::SRGBA,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "wood_planks_base_color",
     )
@@ -135,6 +202,9 @@ fn main() {
         source: asset("textures/wood_planks/roughness.png").as_str(),
         color_type: MdrColorType::NonColorData,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "wood_planks_roughness",
     )
@@ -146,10 +216,27 @@ fn main() {
         source: asset("textures/wood_planks/normal.png").as_str(),
         color_type: MdrColorType::NonColorData,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "wood_planks_normal",
     )
     .unwrap();
+  let wood_planks_occlusion = engine
+    .manage_resources()
+    .load_texture(
+      MdrTextureCreateInfo {
+        source: asset("textures/wood_planks/occlusion.png").as_str(),
+        color_type: MdrColorType::NonColorData,
+        sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
+      },
+      "wood_planks_occlusion",
+    )
+    .unwrap();
   // Red fabric
   let white_bricks_base_color = engine
     .manage_resources()
@@ -158,6 +245,9 @@ fn main() {
         source: asset("textures/white_bricks/base_color.png").as_str(),
         color_type: MdrColorType::SRGBA,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "white_bricks_base_color",
     )
@@ -169,6 +259,9 @@ fn main() {
         source: asset("textures/white_bricks/roughness.png").as_str(),
         color_type: MdrColorType::NonColorData,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "white_bricks_roughness",
     )
@@ -180,10 +273,27 @@ fn main() {
         source: asset("textures/white_bricks/normal.png").as_str(),
         color_type: MdrColorType::NonColorData,
         sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
       },
       "white_bricks_normal",
     )
     .unwrap();
+  let white_bricks_occlusion = engine
+    .manage_resources()
+    .load_texture(
+      MdrTextureCreateInfo {
+        source: asset("textures/white_bricks/occlusion.png").as_str(),
+        color_type: MdrColorType::NonColorData,
+        sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::Linear,
+        anisotropy: Some(16),
+      },
+      "white_bricks_occlusion",
+    )
+    .unwrap();
 
   // Create object materials
   let monkey_mat = engine
@@ -193,8 +303,13 @@ fn main() {
         diffuse: white_bricks_base_color,
         roughness: white_bricks_roughness,
         normal: white_bricks_normal,
-        specular_color: MdrRgb::white(),
-        shininess: 2.0,
+        metallic: None,
+        occlusion: Some(white_bricks_occlusion),
+        emissive: None,
+
+        metallic_factor: 0.0,
+        roughness_factor: 1.0,
+        emissive_factor: MdrRgb::black(),
       },
       "monkey_mat",
     )
@@ -206,8 +321,13 @@ fn main() {
         diffuse: blue_tiles_base_color,
         roughness: blue_tiles_roughness,
         normal: blue_tiles_normal,
-        specular_color: MdrRgb::white(),
-        shininess: 20.0,
+        metallic: None,
+        occlusion: Some(blue_tiles_occlusion),
+        emissive: None,
+
+        metallic_factor: 0.0,
+        roughness_factor: 1.0,
+        emissive_factor: MdrRgb::black(),
       },
       "sphere_mat",
     )
@@ -219,8 +339,13 @@ fn main() {
         diffuse: metal_plates_base_color,
         roughness: metal_plates_roughness,
         normal: metal_plates_normal,
-        specular_color: MdrRgb::white(),
-        shininess: 20.0,
+        metallic: Some(metal_plates_metalness),
+        occlusion: Some(metal_plates_occlusion),
+        emissive: None,
+
+        metallic_factor: 1.0,
+        roughness_factor: 1.0,
+        emissive_factor: MdrRgb::black(),
       },
       "plane_mat",
     )
@@ -232,8 +357,13 @@ fn main() {
         diffuse: wood_planks_base_color,
         roughness: wood_planks_roughness,
         normal: wood_planks_normal,
-        specular_color: MdrRgb::white(),
-        shininess: 20.0,
+        metallic: None,
+        occlusion: Some(wood_planks_occlusion),
+        emissive: None,
+
+        metallic_factor: 0.0,
+        roughness_factor: 1.0,
+        emissive_factor: MdrRgb::black(),
       },
       "cube_mat",
     )
@@ -256,50 +386,37 @@ fn main() {
   ground_plane.transform.translation.set(0.0, 1.0, 0.0);
   engine.scene.add_object(ground_plane);
 
-  // Add white light
+  // Add white light above the objects, shadowing the ground plane
   let mut white_light = MdrLight::white(LIGHT_BRIGHTNESS);
-  white_light.translation.set(1.0, 3.0, 3.0);
+  white_light.translation.set(1.0, -3.0, 3.0);
+  white_light.casts_shadows = true;
   engine.scene.lights.add_light(white_light);
 
+  // Light the scene with a sky, and draw it behind the objects
+  let sky = engine
+    .manage_resources()
+    .load_environment(asset("environments/sky.hdr").as_str(), "sky")
+    .unwrap();
+  engine.scene.environment = Some(sky);
+  engine.scene.skybox = Some(
+    engine
+      .manage_resources()
+      .retrieve_environment("sky")
+      .unwrap(),
+  );
+
+  // Shade the creases between objects, let the bright sky glow, and smooth jagged edges
+  engine.scene.post_processing.ambient_occlusion.enabled = true;
+  engine.scene.post_processing.bloom.enabled = true;
+  engine.scene.post_processing.antialiasing = MdrAntialiasing::Fxaa;
+
   // Set update function
-  engine.set_update_function(Box::new(|scene, input_state, dt| {
-    // Camera WASD movement
-    if input_state.w {
-      let move_magnitude = dt * CAMERA_MOV_SPEED;
-      let move_vector = scene.camera.get_forward_vector() * move_magnitude;
-      scene
-        .camera
-        .transform
-        .translation
-        .translate_by(move_vector.x, 0.0, move_vector.z);
-    }
-    if input_state.d {
-      let move_magnitude = dt * -CAMERA_MOV_SPEED;
-      let move_vector = scene.camera.get_sideways_vector() * move_magnitude;
-      scene
-        .camera
-        .transform
-        .translation
-        .translate_by(move_vector.x, 0.0, move_vector.z);
-    }
-    if input_state.a {
-      let move_magnitude = dt * CAMERA_MOV_SPEED;
-      let move_vector = scene.camera.get_sideways_vector() * move_magnitude;
-      scene
-        .camera
-        .transform
-        .translation
-        .translate_by(move_vector.x, 0.0, move_vector.z);
-    }
-    if input_state.s {
-      let move_magnitude = dt * -CAMERA_MOV_SPEED;
-      let move_vector = scene.camera.get_forward_vector() * move_magnitude;
-      scene
-        .camera
-        .transform
-        .translation
-        .translate_by(move_vector.x, 0.0, move_vector.z);
-    }
+  let mut camera_controller = MdrFlyController::new(&engine.scene.camera);
+  camera_controller.movement_speed = CAMERA_MOV_SPEED;
+  camera_controller.rotation_speed = CAMERA_ROT_SPEED;
+  engine.set_update_function(Box::new(move |scene, input_state, dt| {
+    // Camera movement with WASD, looking around with the mouse when right-button pressed
+    camera_controller.update(&mut scene.camera, input_state, dt);
 
     // Light movement with arrow keys
     if scene.lights.get_count() > 0 {
@@ -318,23 +435,13 @@ fn main() {
         light.translation.x += dt * -LIGHT_MOV_SPEED;
       }
     }
-
-    // Camera rotation with mouse when right-button pressed
-    if input_state.mouse_right {
-      let delta_x = input_state.mouse_delta[0];
-      let delta_y = input_state.mouse_delta[1];
-
-      scene.camera.transform.rotation.z += delta_x * CAMERA_ROT_SPEED;
-      scene.camera.transform.rotation.x += delta_y * -CAMERA_ROT_SPEED;
-    }
   }));
 
   // Start event loop
   info!("Starting event loop");
-  event_loop.run(
-    move |event, _, control_flow| match engine.handle_event(event) {
-      Some(flow) => *control_flow = flow,
-      None => (),
-    },
-  );
+  event_loop.run(move |event, _, control_flow| {
+    if let Some(flow) = engine.handle_event(event) {
+      *control_flow = flow;
+    }
+  });
 }
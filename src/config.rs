@@ -1,2 +1,50 @@
-/// Maximum number of point lights allowed in a scene
-pub const MAX_POINT_LIGHTS: usize = 10;
+/// Number of light clusters the view frustum is divided into along the x, y, and z axes. Also
+/// defined in shaders as `CLUSTER_GRID_*` by `shader_with_config!`, which build.rs generates
+/// from this file, so the values shared with shaders must be written as plain numbers.
+pub const CLUSTER_GRID: [u32; 3] = [16, 9, 24];
+/// Maximum number of lights which can affect a single cluster, further lights are ignored.
+/// Also defined in shaders by `shader_with_config!`.
+pub const MAX_LIGHTS_PER_CLUSTER: u32 = 128;
+/// Brightness, after falling off with the square of the distance, below which a light is
+/// treated as no longer reaching a surface. Sets the default `range` of new lights.
+pub const LIGHT_CUTOFF_BRIGHTNESS: f32 = 0.01;
+
+/// Width and height in texels of each shadow map.
+pub const SHADOW_MAP_SIZE: u32 = 1024;
+/// Number of shadow maps available each frame. Shadow-casting point lights use six, one for each
+/// face of a cube around the light, directional lights use one for each cascade, and spot lights
+/// use one.
+pub const MAX_SHADOW_MAPS: u32 = 16;
+/// Number of cascades directional light shadows are split into between the camera's near and
+/// far planes. Also defined in shaders by `shader_with_config!`, and can be at most 4.
+pub const SHADOW_CASCADE_COUNT: usize = 4;
+/// How the cascades are spaced, from 0 for evenly spaced splits to 1 for logarithmically spaced
+/// splits which give nearby cascades more resolution.
+pub const SHADOW_CASCADE_SPLIT_LAMBDA: f32 = 0.8;
+
+/// Width and height in texels of each face of an environment's irradiance map, which only holds
+/// the low-frequency diffuse lighting.
+pub const IRRADIANCE_MAP_SIZE: u32 = 32;
+/// Width and height in texels of each face of the first mip level of an environment's
+/// prefiltered specular map.
+pub const PREFILTERED_MAP_SIZE: u32 = 128;
+/// Number of mip levels in each prefiltered specular map, spread evenly from a roughness of 0 at
+/// the first level to 1 at the last.
+pub const PREFILTERED_MAP_MIP_LEVELS: u32 = 5;
+/// Width and height in texels of the lookup table of the split-sum specular BRDF.
+pub const BRDF_LUT_SIZE: u32 = 256;
+/// Largest width and height in texels of each face of the cubemap an environment is converted
+/// into.
+pub const MAX_ENVIRONMENT_CUBE_SIZE: u32 = 1024;
+
+/// Anisotropy of the samplers used by textures which are created by imports rather than
+/// `load_texture()`.
+pub const DEFAULT_TEXTURE_ANISOTROPY: u32 = 16;
+
+/// Number of times the bright parts of the scene are halved in size and blurred to build up
+/// bloom. More levels spread the glow further.
+pub const BLOOM_MIP_LEVELS: u32 = 6;
+
+/// Number of samples taken around each pixel to estimate its ambient occlusion. Also defined in
+/// shaders by `shader_with_config!`.
+pub const SSAO_KERNEL_SIZE: usize = 16;
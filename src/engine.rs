@@ -1,18 +1,27 @@
-use log::{info, trace};
+use std::fs;
+
+use image::RgbaImage;
+use log::{error, info, trace};
 use winit::{
   event::{Event, WindowEvent},
   event_loop::{ControlFlow, EventLoop},
 };
 
 use crate::{
-  graphics::{MdrGraphicsContext, MdrResourceManager},
+  graphics::{MdrCaptureError, MdrGraphicsContext, MdrRenderPath, MdrResourceManager},
   input::{MdrInputContext, MdrInputState},
-  scene::MdrScene,
+  scene::{MdrScene, MdrSceneDescription, MdrSceneError},
   update::MdrUpdateContext,
 };
 
 pub struct MdrEngineOptions {
   pub debug: bool,
+  /// Number of samples taken of each pixel to smooth the scene's edges, where 1 disables
+  /// multisampling. Devices which don't support the count use the largest one they do below it.
+  pub msaa_samples: u32,
+  /// How the scene's objects are drawn and shaded. The deferred path draws with one sample per
+  /// pixel whatever `msaa_samples` is.
+  pub render_path: MdrRenderPath,
 }
 
 pub struct MdrEngine {
@@ -30,7 +39,12 @@ impl MdrEngine {
     let engine = Self {
       scene: MdrScene::new(),
 
-      graphics_context: MdrGraphicsContext::new(&event_loop, options.debug),
+      graphics_context: MdrGraphicsContext::new(
+        &event_loop,
+        options.debug,
+        options.msaa_samples,
+        options.render_path,
+      ),
       input_context: MdrInputContext::new(),
       update_context: MdrUpdateContext::new(),
     };
@@ -38,10 +52,29 @@ impl MdrEngine {
     (engine, event_loop)
   }
 
+  /// Create an engine which renders offscreen into an image of `dimensions` (`[width, height]`)
+  /// rather than into a window. Headless engines have no event loop, frames are produced by
+  /// calling `render_frame()`.
+  pub fn new_headless(options: MdrEngineOptions, dimensions: [u32; 2]) -> Self {
+    Self {
+      scene: MdrScene::new(),
+
+      graphics_context: MdrGraphicsContext::new_headless(
+        dimensions,
+        options.debug,
+        options.msaa_samples,
+        options.render_path,
+      ),
+      input_context: MdrInputContext::new(),
+      update_context: MdrUpdateContext::new(),
+    }
+  }
+
   pub fn manage_resources(&mut self) -> &mut MdrResourceManager {
     &mut self.graphics_context.resource_manager
   }
 
+  #[allow(clippy::type_complexity)]
   pub fn set_update_function(&mut self, f: Box<dyn FnMut(&mut MdrScene, &MdrInputState, f32)>) {
     self.update_context.set_update_function(f);
   }
@@ -77,6 +110,13 @@ impl MdrEngine {
         self.input_context.mouse_moved_input(position);
         None
       }
+      Event::WindowEvent {
+        event: WindowEvent::MouseWheel { delta, .. },
+        ..
+      } => {
+        self.input_context.mouse_wheel_input(&delta);
+        None
+      }
       Event::WindowEvent {
         event: WindowEvent::KeyboardInput { input, .. },
         ..
@@ -85,21 +125,117 @@ impl MdrEngine {
         None
       }
       Event::MainEventsCleared => {
-        self
-          .update_context
-          .update_scene(&mut self.scene, &self.input_context.state);
-        self.input_context.cleanup_after_update();
+        self.update();
         None
       }
       Event::RedrawEventsCleared => {
-        self
-          .graphics_context
-          .update_scene_aspect_ratio(&mut self.scene);
-
-        self.graphics_context.draw(&self.scene);
+        self.draw();
         None
       }
       _ => None,
     }
   }
+
+  /// Runs the update function and draws the scene once. This is how frames are produced by
+  /// engines created with `new_headless()`, where drawing completes before this returns.
+  pub fn render_frame(&mut self) {
+    self.update();
+    self.draw();
+  }
+
+  /// Draws the scene and returns a copy of the rendered frame. Returns `None` if no frame
+  /// could be drawn, e.g. because the window is minimized.
+  pub fn capture_frame(&mut self) -> Option<RgbaImage> {
+    self
+      .graphics_context
+      .update_scene_aspect_ratio(&mut self.scene);
+
+    self.graphics_context.capture_frame(&self.scene)
+  }
+
+  /// Draws the scene and saves the rendered frame to `path`. The image format is determined
+  /// by the extension of `path`, e.g. `.png`.
+  pub fn save_frame(&mut self, path: &str) -> Result<(), MdrCaptureError> {
+    let frame = match self.capture_frame() {
+      Some(frame) => frame,
+      None => return Err(MdrCaptureError::FrameUnavailable),
+    };
+
+    match frame.save(path) {
+      Ok(_) => Ok(()),
+      Err(e) => {
+        error!("Failed to save frame to {}: {}", path, e);
+        Err(MdrCaptureError::ImageSaveError)
+      }
+    }
+  }
+
+  /// Saves the scene, along with a manifest of the resources needed to rebuild it, to `path`
+  /// in RON format.
+  pub fn save_scene(&self, path: &str) -> Result<(), MdrSceneError> {
+    let description = MdrSceneDescription::from_scene(
+      &self.scene,
+      self.graphics_context.resource_manager.manifest(),
+    );
+
+    let contents = match ron::ser::to_string_pretty(&description, ron::ser::PrettyConfig::default())
+    {
+      Ok(contents) => contents,
+      Err(e) => {
+        error!("Failed to serialize scene: {}", e);
+        return Err(MdrSceneError::SceneWriteError);
+      }
+    };
+
+    match fs::write(path, contents) {
+      Ok(_) => Ok(()),
+      Err(e) => {
+        error!("Failed to write scene to {}: {}", path, e);
+        Err(MdrSceneError::SceneWriteError)
+      }
+    }
+  }
+
+  /// Replaces the scene with one loaded from the RON file at `path`, first loading any of its
+  /// resources which aren't already in the resource manager.
+  pub fn load_scene(&mut self, path: &str) -> Result<(), MdrSceneError> {
+    let contents = match fs::read_to_string(path) {
+      Ok(contents) => contents,
+      Err(e) => {
+        error!("Failed to read scene from {}: {}", path, e);
+        return Err(MdrSceneError::SceneReadError);
+      }
+    };
+
+    let description: MdrSceneDescription = match ron::de::from_str(&contents) {
+      Ok(description) => description,
+      Err(e) => {
+        error!("Failed to parse scene {}: {}", path, e);
+        return Err(MdrSceneError::SceneParseError);
+      }
+    };
+
+    let mut scene = description.build(&mut self.graphics_context.resource_manager)?;
+    scene.camera.aspect_ratio = self.scene.camera.aspect_ratio;
+    self.scene = scene;
+
+    Ok(())
+  }
+
+  /// Updates the scene with the user-set update function.
+  fn update(&mut self) {
+    self
+      .update_context
+      .update_scene(&mut self.scene, &self.input_context.state);
+    self.input_context.cleanup_after_update();
+  }
+
+  /// Draws the scene to the graphics context's render target.
+  fn draw(&mut self) {
+    self
+      .graphics_context
+      .update_scene_aspect_ratio(&mut self.scene);
+
+    self.graphics_context.draw(&self.scene);
+  }
 }
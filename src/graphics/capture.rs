@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use image::RgbaImage;
+use log::error;
+use vulkano::{
+  buffer::{BufferUsage, CpuAccessibleBuffer},
+  device::Device,
+  format::Format,
+};
+
+/// Creates a CPU-visible buffer which a color attachment of `dimensions` can be copied into.
+/// Only formats with four 8-bit channels are supported.
+pub(crate) fn create_readback_buffer(
+  logical_device: &Arc<Device>,
+  dimensions: [u32; 2],
+) -> Arc<CpuAccessibleBuffer<[u8]>> {
+  let byte_count = (dimensions[0] * dimensions[1] * 4) as usize;
+
+  CpuAccessibleBuffer::from_iter(
+    logical_device.clone(),
+    BufferUsage::transfer_dst(),
+    false,
+    (0..byte_count).map(|_| 0u8),
+  )
+  .unwrap()
+}
+
+/// Returns whether frames in `format` can be read back into an `RgbaImage`.
+pub(crate) fn is_readable_format(format: Format) -> bool {
+  matches!(
+    format,
+    Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB | Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB
+  )
+}
+
+/// Converts the contents of a readback buffer holding an image of `dimensions` in `format` into
+/// an `RgbaImage`. Returns `None` if the format cannot be converted.
+pub(crate) fn readback_to_image(
+  buffer: &CpuAccessibleBuffer<[u8]>,
+  dimensions: [u32; 2],
+  format: Format,
+) -> Option<RgbaImage> {
+  let mut pixels = buffer.read().unwrap().to_vec();
+
+  match format {
+    Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => (),
+    // Swizzle BGRA formats, which are common for swapchains, into RGBA
+    Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => {
+      for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+      }
+    }
+    _ => {
+      error!("Cannot read back frames with format {:?}", format);
+      return None;
+    }
+  }
+
+  RgbaImage::from_raw(dimensions[0], dimensions[1], pixels)
+}
+
+#[derive(Debug)]
+/// Error emitted when capturing a frame.
+pub enum MdrCaptureError {
+  /// Emitted when no frame could be rendered and read back, e.g. because the window is
+  /// minimized, the swapchain was out of date, or the swapchain's images can't be copied out
+  /// of or aren't in a format that can be read back.
+  FrameUnavailable,
+  /// Emitted when a captured frame fails to be written to disk.
+  ImageSaveError,
+}
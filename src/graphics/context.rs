@@ -1,56 +1,104 @@
-use log::{debug, error, info, trace};
-use nalgebra::Vector3;
+use image::RgbaImage;
+use log::{debug, error, info, trace, warn};
+use nalgebra::{Matrix4, Vector3};
 use std::sync::Arc;
 use winit::{event_loop::EventLoop, window::Window};
 
 use vulkano::{
   buffer::{BufferUsage, CpuAccessibleBuffer},
   command_buffer::{
-    AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer, RenderPassBeginInfo,
-    SubpassContents,
+    AutoCommandBufferBuilder, CommandBufferUsage, CopyImageToBufferInfo, PrimaryAutoCommandBuffer,
+    RenderPassBeginInfo, SubpassContents,
   },
   descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
   device::{
     physical::{PhysicalDevice, PhysicalDeviceType, QueueFamily},
-    Device, DeviceCreateInfo, DeviceExtensions, Queue, QueueCreateInfo,
+    Device, DeviceCreateInfo, DeviceExtensions, Features, Queue, QueueCreateInfo,
   },
-  format::{ClearValue, Format},
-  image::{view::ImageView, AttachmentImage, ImageAccess, ImageUsage, SwapchainImage},
+  format::{ClearValue, Format, NumericType},
+  image::{AttachmentImage, ImageUsage, SampleCount, SwapchainImage},
   instance::{Instance, InstanceCreateInfo, InstanceExtensions},
-  pipeline::{graphics::viewport::Viewport, Pipeline, PipelineBindPoint},
+  pipeline::{graphics::viewport::Viewport, GraphicsPipeline, Pipeline, PipelineBindPoint},
   render_pass::{Framebuffer, FramebufferCreateInfo},
   swapchain::{self, AcquireError, Surface, Swapchain, SwapchainCreateInfo},
   sync::{self, FlushError, GpuFuture},
 };
 
 use crate::{
-  config::MAX_POINT_LIGHTS,
   graphics::{
-    pipeline::MdrMeshPipeline,
+    capture,
+    hdr::{MdrHdrTarget, HDR_FORMAT},
+    pipeline::{
+      MdrDeferredLightingPipeline, MdrLightCullingPipeline, MdrMeshPipeline, MdrShadowPipeline,
+      MdrSkyboxPipeline,
+    },
+    post_processing::MdrPostProcessor,
     render_pass::MdrRenderPass,
-    shaders::mesh_vertex_shader::ty::{MdrPushConstants, MdrSceneData},
+    shaders::{
+      deferred_lighting_shader::ty::MdrDeferredLightingPushConstants,
+      mesh_vertex_shader::ty::{MdrPushConstants, MdrSceneData},
+      shadow_vertex_shader::ty::MdrShadowPushConstants,
+      skybox_vertex_shader::ty::MdrSkyboxPushConstants,
+    },
+    shadows::{MdrShadowMaps, MdrShadowViews},
+    target::MdrRenderTarget,
     window::{MdrWindow, MdrWindowOptions},
   },
-  scene::MdrScene,
+  resources::MdrEnvironment,
+  scene::{MdrDebugView, MdrLight, MdrLightKind, MdrProjection, MdrScene},
 };
 
 use super::{
   resources::MdrResourceManager,
-  shaders::mesh_vertex_shader::ty::{CameraData, PointLightData},
+  shaders::mesh_vertex_shader::ty::{CameraData, LightData},
 };
 
+/// Values of `LightData::kind`, matching the `LIGHT_KIND_*` definitions in `scene_data.glsl`.
+const LIGHT_KIND_POINT: u32 = 0;
+const LIGHT_KIND_DIRECTIONAL: u32 = 1;
+const LIGHT_KIND_SPOT: u32 = 2;
+
+/// Values of `MdrSceneData::debug_view`, matching the `DEBUG_VIEW_*` definitions in
+/// `scene_data.glsl`.
+const DEBUG_VIEW_NONE: u32 = 0;
+const DEBUG_VIEW_SHADOW_CASCADES: u32 = 1;
+
+/// The scene's lights, read by the shaders.
+type LightBuffer = Arc<CpuAccessibleBuffer<[LightData]>>;
+/// The light space matrices of the shadow map cascades, read by the shaders.
+type ShadowMatrixBuffer = Arc<CpuAccessibleBuffer<[[[f32; 4]; 4]]>>;
+
+/// The ways the scene's objects can be drawn and shaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MdrRenderPath {
+  /// Each object is shaded as it is drawn, by every light reaching it.
+  #[default]
+  Forward,
+  /// Objects are drawn into a G-buffer of their surface properties, which is then shaded once
+  /// per pixel. This avoids shading surfaces which end up hidden, but doesn't support MSAA.
+  Deferred,
+}
+
 /// A Vulkan graphics context, contains Vulkano members.
 pub struct MdrGraphicsContext {
   pub(crate) resource_manager: MdrResourceManager,
-  pub(crate) window: Arc<MdrWindow>,
 
   logical_device: Arc<Device>,
   queue: Arc<Queue>,
-  swapchain: Arc<Swapchain<Window>>,
-  swapchain_images: Vec<Arc<SwapchainImage<Window>>>,
+  target: MdrRenderTarget,
+  /// Draws the scene into the HDR target, or into its G-buffer on the deferred render path.
   render_pass: MdrRenderPass,
   viewport: Viewport,
   pipeline: MdrMeshPipeline,
+  /// Lights the G-buffer into the HDR target, on the deferred render path only.
+  deferred_lighting: Option<MdrDeferredLightingPipeline>,
+  light_culling: MdrLightCullingPipeline,
+  shadow_maps: MdrShadowMaps,
+  shadow_pipeline: MdrShadowPipeline,
+  skybox_pipeline: MdrSkyboxPipeline,
+  hdr_target: MdrHdrTarget,
+  /// Draws the HDR target into the render target.
+  post_processor: MdrPostProcessor,
   framebuffers: Vec<Arc<Framebuffer>>,
 
   window_was_resized: bool,
@@ -58,15 +106,23 @@ pub struct MdrGraphicsContext {
   updated_aspect_ratio: bool,
   frame_futures: Vec<Option<Box<dyn GpuFuture>>>,
   previous_frame_index: usize,
+
+  capture_requested: bool,
+  captured_frame: Option<RgbaImage>,
 }
 
 impl MdrGraphicsContext {
   /// Create a new MD Renderer Graphics context with optional debug.
-  pub fn new(event_loop: &EventLoop<()>, debug_enabled: bool) -> Self {
+  pub fn new(
+    event_loop: &EventLoop<()>,
+    debug_enabled: bool,
+    msaa_samples: u32,
+    render_path: MdrRenderPath,
+  ) -> Self {
     debug!("Creating graphics context");
 
     // Create instance containing Vulkan function pointers
-    let instance = Self::create_instance(debug_enabled);
+    let instance = Self::create_instance(vulkano_win::required_extensions(), debug_enabled);
     debug!("Created vulkan instance");
 
     // Create window
@@ -102,38 +158,144 @@ impl MdrGraphicsContext {
       Self::create_swapchain(&window, &logical_device, &physical_device);
     debug!("Created swapchain");
 
-    // Create render pass
-    let render_pass = MdrRenderPass::new(&logical_device, swapchain.image_format());
-    debug!("Created render pass");
+    let target = MdrRenderTarget::Window {
+      window,
+      swapchain,
+      images: swapchain_images,
+    };
+
+    Self::from_target(logical_device, queue, target, msaa_samples, render_path)
+  }
+
+  /// Create a new MD Renderer Graphics context which renders into an offscreen image of
+  /// `dimensions` (`[width, height]`) instead of a window. No window, surface, or swapchain
+  /// is created, so this works on machines without a display and with CPU Vulkan drivers.
+  pub fn new_headless(
+    dimensions: [u32; 2],
+    debug_enabled: bool,
+    msaa_samples: u32,
+    render_path: MdrRenderPath,
+  ) -> Self {
+    debug!("Creating headless graphics context");
+
+    // Create instance containing Vulkan function pointers, no surface extensions are needed
+    let instance = Self::create_instance(InstanceExtensions::none(), debug_enabled);
+    debug!("Created vulkan instance");
+
+    // Select physical device and queue
+    let (physical_device, queue_family) = Self::pick_headless_physical_device(&instance);
+    info!(
+      "Using device: {} (type: {:?})",
+      physical_device.properties().device_name,
+      physical_device.properties().device_type,
+    );
+
+    // Create logical device
+    let (logical_device, queue) =
+      Self::create_logical_device(physical_device, DeviceExtensions::none(), queue_family);
+    debug!("Created logical device");
+
+    // Create offscreen color image
+    let image = Self::create_offscreen_image(&logical_device, dimensions);
+    debug!("Created offscreen image");
 
+    let target = MdrRenderTarget::Offscreen { image };
+
+    Self::from_target(logical_device, queue, target, msaa_samples, render_path)
+  }
+
+  /// Creates the parts of the context which are shared between windowed and headless
+  /// rendering, drawing into the color images of `target` along `render_path`, with up to
+  /// `msaa_samples` samples per pixel.
+  fn from_target(
+    logical_device: Arc<Device>,
+    queue: Arc<Queue>,
+    target: MdrRenderTarget,
+    msaa_samples: u32,
+    render_path: MdrRenderPath,
+  ) -> Self {
     // Create viewport
-    let viewport = window.create_viewport();
+    let viewport = target.create_viewport();
     debug!("Created viewport");
 
-    // Create mesh pipeline
-    let pipeline = MdrMeshPipeline::new(&logical_device, &render_pass, &viewport);
+    // Create render pass and mesh pipeline, the scene is drawn in HDR and then post-processed
+    // into the target
+    let (render_pass, pipeline, deferred_lighting) = match render_path {
+      MdrRenderPath::Forward => {
+        let samples = Self::pick_sample_count(&logical_device, msaa_samples);
+        let render_pass = MdrRenderPass::new(&logical_device, HDR_FORMAT, samples);
+        debug!(
+          "Created forward render pass with {} samples per pixel",
+          samples as u32
+        );
+
+        let pipeline = MdrMeshPipeline::new(&logical_device, &render_pass, &viewport);
+        (render_pass, pipeline, None)
+      }
+      MdrRenderPath::Deferred => {
+        if msaa_samples > 1 {
+          warn!(
+            "{} samples per pixel requested, the deferred render path only supports 1",
+            msaa_samples
+          );
+        }
+        let render_pass = MdrRenderPass::new_gbuffer(&logical_device, HDR_FORMAT);
+        debug!("Created G-buffer render pass");
+
+        let pipeline = MdrMeshPipeline::new_gbuffer(&logical_device, &render_pass, &viewport);
+        let deferred_lighting = MdrDeferredLightingPipeline::new(&logical_device);
+        debug!("Created deferred lighting pipeline");
+        (render_pass, pipeline, Some(deferred_lighting))
+      }
+    };
     debug!("Created pipeline");
 
-    // Create framebuffers
-    let framebuffers = Self::create_framebuffers(&logical_device, &swapchain_images, &render_pass);
+    // Create light culling pipeline
+    let light_culling = MdrLightCullingPipeline::new(&logical_device, &queue);
+    debug!("Created light culling pipeline");
+
+    // Create shadow maps and the pipeline which draws into them
+    let shadow_maps = MdrShadowMaps::new(&logical_device, &queue);
+    let shadow_pipeline = MdrShadowPipeline::new(&logical_device, shadow_maps.render_pass());
+    debug!("Created shadow maps");
+
+    // Create skybox pipeline
+    let skybox_pipeline = MdrSkyboxPipeline::new(&logical_device, &render_pass, &viewport);
+    debug!("Created skybox pipeline");
+
+    // Create the HDR target, the post-processing passes reading it, and the framebuffers
+    let hdr_target = MdrHdrTarget::new(
+      &logical_device,
+      &render_pass,
+      deferred_lighting
+        .as_ref()
+        .map(|lighting| lighting.render_pass()),
+      target.dimensions(),
+    );
+    let post_processor = MdrPostProcessor::new(&logical_device, &hdr_target, target.image_format());
+    let framebuffers = Self::create_framebuffers(&target, post_processor.target_render_pass());
     debug!("Created framebuffers");
 
-    // Create vector of futures corresponding to each swapchain image
-    let frame_futures = Self::set_up_frame_futures(swapchain_images.len());
+    // Create vector of futures corresponding to each target image
+    let frame_futures = Self::set_up_frame_futures(framebuffers.len());
 
     // Create resource manager
     let resource_manager = MdrResourceManager::new(logical_device.clone(), queue.clone());
 
     Self {
-      window,
-
       logical_device,
       queue,
-      swapchain,
-      swapchain_images,
+      target,
       render_pass,
       viewport,
       pipeline,
+      deferred_lighting,
+      light_culling,
+      shadow_maps,
+      shadow_pipeline,
+      skybox_pipeline,
+      hdr_target,
+      post_processor,
       framebuffers,
 
       resource_manager,
@@ -143,6 +305,9 @@ impl MdrGraphicsContext {
       updated_aspect_ratio: true,
       frame_futures,
       previous_frame_index: 0,
+
+      capture_requested: false,
+      captured_frame: None,
     }
   }
 
@@ -151,16 +316,41 @@ impl MdrGraphicsContext {
     trace!("Starting draw");
 
     // Skip draw for minimized windows
-    if self.window.is_minimized() {
+    if self.target.is_minimized() {
       trace!("Window minimized");
       return;
     }
 
+    match self.target {
+      MdrRenderTarget::Window { .. } => self.draw_to_swapchain(scene),
+      MdrRenderTarget::Offscreen { .. } => self.draw_offscreen(scene),
+    }
+    trace!("Completed draw")
+  }
+
+  /// Draws the `MdrScene` referenced and copies the rendered color attachment back into CPU
+  /// memory. Returns `None` if no frame could be drawn, e.g. because the window is minimized
+  /// or the swapchain was out of date, or if the target's images can't be read back.
+  pub fn capture_frame(&mut self, scene: &MdrScene) -> Option<RgbaImage> {
+    self.capture_requested = true;
+    self.draw(scene);
+    self.capture_requested = false;
+
+    self.captured_frame.take()
+  }
+
+  /// Draws into the next available swapchain image and presents it.
+  fn draw_to_swapchain(&mut self, scene: &MdrScene) {
     self.size_dependent_updates();
 
+    let swapchain = match &self.target {
+      MdrRenderTarget::Window { swapchain, .. } => swapchain.clone(),
+      MdrRenderTarget::Offscreen { .. } => unreachable!(),
+    };
+
     // First, we acquire the index of the image to draw to
     let (image_index, is_suboptimal, acquire_future) =
-      match swapchain::acquire_next_image(self.swapchain.clone(), None) {
+      match swapchain::acquire_next_image(swapchain.clone(), None) {
         Ok(r) => r,
         Err(AcquireError::OutOfDate) => {
           debug!("Swapchain out of date, flagging for recreation");
@@ -188,24 +378,36 @@ impl MdrGraphicsContext {
     }
     // Clean up lingering finished futures
     previous_frame_end.cleanup_finished();
+    self.light_culling.report_overflow();
+
+    // Create a buffer to copy the frame into if a capture was requested
+    let readback_buffer = self.create_readback_buffer();
 
     let command_buffer = self.create_command_buffer(
       &self.logical_device,
       &self.queue,
-      &self.pipeline,
       &self.framebuffers[image_index],
       scene,
+      readback_buffer.clone(),
     );
 
     let future = previous_frame_end
       .join(acquire_future)
       .then_execute(self.queue.clone(), command_buffer)
       .unwrap()
-      .then_swapchain_present(self.queue.clone(), self.swapchain.clone(), image_index)
+      .then_swapchain_present(self.queue.clone(), swapchain, image_index)
       .then_signal_fence_and_flush();
 
     let end_of_frame_future = match future {
-      Ok(future) => future.boxed(),
+      Ok(future) => {
+        // Captures must wait for the frame to finish before reading it back
+        if let Some(buffer) = readback_buffer {
+          future.wait(None).unwrap();
+          self.read_back_frame(&buffer);
+        }
+
+        future.boxed()
+      }
       Err(FlushError::OutOfDate) => {
         self.should_recreate_swapchain = true;
         sync::now(self.logical_device.clone()).boxed()
@@ -219,7 +421,75 @@ impl MdrGraphicsContext {
     // Store future and index for this frame's completion
     self.frame_futures[image_index] = Some(end_of_frame_future);
     self.previous_frame_index = image_index;
-    trace!("Completed draw")
+  }
+
+  /// Draws into the offscreen image and blocks until the GPU has finished rendering, so
+  /// the image contents are complete when this returns.
+  fn draw_offscreen(&mut self, scene: &MdrScene) {
+    let mut previous_frame_end = sync::now(self.logical_device.clone()).boxed();
+    // If we're waiting for any resources to load, chain that in
+    if let Some(resource_future) = self.resource_manager.take_upload_futures() {
+      previous_frame_end = previous_frame_end.join(resource_future).boxed();
+    }
+
+    // Create a buffer to copy the frame into if a capture was requested
+    let readback_buffer = self.create_readback_buffer();
+
+    let command_buffer = self.create_command_buffer(
+      &self.logical_device,
+      &self.queue,
+      &self.framebuffers[0],
+      scene,
+      readback_buffer.clone(),
+    );
+
+    let future = previous_frame_end
+      .then_execute(self.queue.clone(), command_buffer)
+      .unwrap()
+      .then_signal_fence_and_flush();
+
+    match future {
+      Ok(future) => future.wait(None).unwrap(),
+      Err(e) => {
+        error!("Failed to flush future: {}", e);
+        return;
+      }
+    }
+    self.light_culling.report_overflow();
+
+    if let Some(buffer) = readback_buffer {
+      self.read_back_frame(&buffer);
+    }
+  }
+
+  /// Returns a buffer the current frame can be copied into if a capture has been requested and
+  /// the target's images can be read back.
+  fn create_readback_buffer(&self) -> Option<Arc<CpuAccessibleBuffer<[u8]>>> {
+    if !self.capture_requested {
+      return None;
+    }
+
+    // Surfaces don't have to allow copying out of swapchain images, or use a format we convert
+    if !self.target.allows_transfer_src() {
+      error!("Cannot capture frames, the target's images can't be copied from");
+      return None;
+    }
+    let format = self.target.image_format();
+    if !capture::is_readable_format(format) {
+      error!("Cannot capture frames with format {:?}", format);
+      return None;
+    }
+
+    Some(capture::create_readback_buffer(
+      &self.logical_device,
+      self.target.dimensions(),
+    ))
+  }
+
+  /// Converts the contents of a finished frame's readback buffer into the captured frame.
+  fn read_back_frame(&mut self, buffer: &CpuAccessibleBuffer<[u8]>) {
+    self.captured_frame =
+      capture::readback_to_image(buffer, self.target.dimensions(), self.target.image_format());
   }
 
   /// Performs updates based on the render surface's size.
@@ -227,24 +497,43 @@ impl MdrGraphicsContext {
     if self.window_was_resized || self.should_recreate_swapchain {
       self.should_recreate_swapchain = false;
 
+      let (window, swapchain, images) = match &mut self.target {
+        MdrRenderTarget::Window {
+          window,
+          swapchain,
+          images,
+        } => (window, swapchain, images),
+        MdrRenderTarget::Offscreen { .. } => return,
+      };
+
       // Recreate swapchain and framebuffers
       trace!("Recreating swapchain");
-      let mut recreate_info = self.swapchain.create_info();
-      recreate_info.image_extent = self.window.dimensions().into();
-      (self.swapchain, self.swapchain_images) = self.swapchain.recreate(recreate_info).unwrap();
-      self.framebuffers = Self::create_framebuffers(
+      let mut recreate_info = swapchain.create_info();
+      recreate_info.image_extent = window.dimensions().into();
+      (*swapchain, *images) = swapchain.recreate(recreate_info).unwrap();
+      self.hdr_target = MdrHdrTarget::new(
         &self.logical_device,
-        &self.swapchain_images,
         &self.render_pass,
+        self
+          .deferred_lighting
+          .as_ref()
+          .map(|lighting| lighting.render_pass()),
+        self.target.dimensions(),
       );
+      self.post_processor.resize(&self.hdr_target);
+      self.framebuffers =
+        Self::create_framebuffers(&self.target, self.post_processor.target_render_pass());
 
       if self.window_was_resized {
         self.window_was_resized = false;
 
         // Recreate viewport and pipeline
         trace!("Window resized, recreating pipeline");
-        self.viewport.dimensions = self.window.dimensions().into();
+        self.viewport = self.target.create_viewport();
         self.pipeline.recreate(&self.render_pass, &self.viewport);
+        self
+          .skybox_pipeline
+          .recreate(&self.render_pass, &self.viewport);
 
         self.updated_aspect_ratio = true;
       }
@@ -270,14 +559,15 @@ impl MdrGraphicsContext {
     }
   }
 
-  /// Generate a command buffer for drawing a `MdrScene`.
+  /// Generate a command buffer for drawing a `MdrScene` into the HDR target, then
+  /// post-processing it into `framebuffer`.
   fn create_command_buffer(
     &self,
     logical_device: &Arc<Device>,
     queue: &Arc<Queue>,
-    pipeline: &MdrMeshPipeline,
     framebuffer: &Arc<Framebuffer>,
     scene: &MdrScene,
+    readback_buffer: Option<Arc<CpuAccessibleBuffer<[u8]>>>,
   ) -> Arc<PrimaryAutoCommandBuffer> {
     // Create command buffer builder
     let mut builder = AutoCommandBufferBuilder::primary(
@@ -287,34 +577,105 @@ impl MdrGraphicsContext {
     )
     .unwrap();
 
-    // Clear color used when drawing bacground
-    let clear_color_value = ClearValue::Float([0.1, 0.1, 0.1, 1.0]);
+    // Clear color used when drawing background
+    let clear_color = scene.clear_color;
+    let clear_color_value = ClearValue::Float([clear_color.r, clear_color.g, clear_color.b, 1.0]);
     let clear_depth_value = ClearValue::Depth(1.0);
-    let mut begin_render_pass_info = RenderPassBeginInfo::framebuffer(framebuffer.clone());
-    begin_render_pass_info.clear_values = vec![Some(clear_color_value), Some(clear_depth_value)];
+    let scene_framebuffer = &self.hdr_target.framebuffer;
+    let mut begin_render_pass_info = RenderPassBeginInfo::framebuffer(scene_framebuffer.clone());
+    begin_render_pass_info.clear_values = if self.deferred_lighting.is_some() {
+      // The G-buffer is written wherever an object is drawn, and never read elsewhere
+      vec![
+        Some(clear_color_value),
+        None,
+        None,
+        None,
+        Some(clear_depth_value),
+      ]
+    } else {
+      vec![Some(clear_color_value), Some(clear_depth_value)]
+    };
+    if self.render_pass.samples() != SampleCount::Sample1 {
+      // The resolve attachment is overwritten by the resolve, so it isn't cleared
+      begin_render_pass_info.clear_values.push(None);
+    }
+
+    // Assign shadow maps to the lights which cast shadows
+    let shadow_views = MdrShadowViews::new(scene);
+    let world_matrices = scene.world_matrices();
+
+    // Upload camera transforms, lights, and shadow map transforms
+    let (scene_buffer, light_buffer, shadow_buffer) = Self::upload_scene_data(
+      logical_device,
+      scene,
+      scene_framebuffer.extent(),
+      &shadow_views,
+    );
 
     // Build command buffer
+    // Draw the shadow maps before anything samples them
+    self.draw_shadow_maps(&mut builder, scene, &shadow_views, &world_matrices);
+
+    // Sort the lights into clusters before anything is shaded
+    let compute_pipeline = self.light_culling.compute_pipeline.clone();
+    let culling_descriptor_set = PersistentDescriptorSet::new(
+      compute_pipeline
+        .layout()
+        .set_layouts()
+        .first()
+        .unwrap()
+        .clone(),
+      [
+        WriteDescriptorSet::buffer(0, scene_buffer.clone()),
+        WriteDescriptorSet::buffer(1, light_buffer.clone()),
+        WriteDescriptorSet::buffer(2, self.light_culling.cluster_buffer.clone()),
+        WriteDescriptorSet::buffer(3, self.light_culling.overflow_buffer.clone()),
+      ],
+    )
+    .unwrap();
+    builder
+      .bind_pipeline_compute(compute_pipeline.clone())
+      .bind_descriptor_sets(
+        PipelineBindPoint::Compute,
+        compute_pipeline.layout().clone(),
+        0,
+        culling_descriptor_set,
+      )
+      .dispatch(self.light_culling.workgroup_count())
+      .unwrap();
+
     // Begin render pass
     builder
       .begin_render_pass(begin_render_pass_info, SubpassContents::Inline)
       .unwrap();
 
     // Bind object pipeline
+    let pipeline = &self.pipeline;
     builder.bind_pipeline_graphics(pipeline.graphics_pipeline.clone());
 
-    // Upload camera transforms
-    let scene_buffer = Self::upload_scene_data(logical_device, scene);
-    let scene_descriptor_set = PersistentDescriptorSet::new(
-      pipeline
-        .graphics_pipeline
-        .layout()
-        .set_layouts()
-        .get(0)
-        .unwrap()
-        .clone(),
-      [WriteDescriptorSet::buffer(0, scene_buffer)],
-    )
-    .unwrap();
+    // Bind scene data. Objects drawn into the G-buffer only read the camera, the lights and
+    // environment are read by the lighting pass instead
+    let scene_descriptor_set = if self.deferred_lighting.is_some() {
+      PersistentDescriptorSet::new(
+        pipeline
+          .graphics_pipeline
+          .layout()
+          .set_layouts()
+          .first()
+          .unwrap()
+          .clone(),
+        [WriteDescriptorSet::buffer(0, scene_buffer.clone())],
+      )
+      .unwrap()
+    } else {
+      self.create_scene_descriptor_set(
+        &pipeline.graphics_pipeline,
+        scene,
+        scene_buffer.clone(),
+        light_buffer.clone(),
+        shadow_buffer.clone(),
+      )
+    };
     builder.bind_descriptor_sets(
       PipelineBindPoint::Graphics,
       pipeline.graphics_pipeline.layout().clone(),
@@ -323,7 +684,7 @@ impl MdrGraphicsContext {
     );
 
     // Render objects
-    for object in scene.scene_objects.iter() {
+    for ((_, object), world_matrix) in scene.objects().zip(world_matrices) {
       // Get handle to the mesh buffers from the resource manager
       let mesh_handle = self.resource_manager.get_mesh_handle(&object.mesh);
       // Get handle to the material buffer from the resource manager
@@ -331,7 +692,8 @@ impl MdrGraphicsContext {
 
       // Upload object's world transform as a push constant
       let push_constants = MdrPushConstants {
-        transformation_matrix: object.transform.matrix().into(),
+        transformation_matrix: world_matrix.into(),
+        receives_shadows: object.receives_shadows as u32,
       };
 
       // Bind vertex data
@@ -378,6 +740,24 @@ impl MdrGraphicsContext {
             material_handle.normal_map.image_view.clone(),
             material_handle.normal_map.sampler.clone(),
           ),
+          // Metallic map image sampler
+          WriteDescriptorSet::image_view_sampler(
+            4,
+            material_handle.metallic_map.image_view.clone(),
+            material_handle.metallic_map.sampler.clone(),
+          ),
+          // Occlusion map image sampler
+          WriteDescriptorSet::image_view_sampler(
+            5,
+            material_handle.occlusion_map.image_view.clone(),
+            material_handle.occlusion_map.sampler.clone(),
+          ),
+          // Emissive map image sampler
+          WriteDescriptorSet::image_view_sampler(
+            6,
+            material_handle.emissive_map.image_view.clone(),
+            material_handle.emissive_map.sampler.clone(),
+          ),
         ],
       )
       .unwrap();
@@ -401,29 +781,307 @@ impl MdrGraphicsContext {
         .unwrap();
     }
 
-    // End render pass and build
+    // Render the skybox behind the objects
+    if let Some(skybox) = &scene.skybox {
+      self.draw_skybox(&mut builder, scene, skybox);
+    }
+
+    // End render pass
     builder.end_render_pass().unwrap();
+
+    // Light the G-buffer into the HDR target
+    if let Some(lighting) = &self.deferred_lighting {
+      let scene_descriptor_set = self.create_scene_descriptor_set(
+        &lighting.graphics_pipeline,
+        scene,
+        scene_buffer,
+        light_buffer,
+        shadow_buffer,
+      );
+      self.draw_deferred_lighting(&mut builder, scene, lighting, scene_descriptor_set);
+    }
+
+    // Apply the scene's post-processing, resolving the HDR image into the render target
+    self
+      .post_processor
+      .record(&mut builder, scene, &self.resource_manager, framebuffer);
+
+    // Copy the rendered color attachment into the readback buffer for captures
+    if let Some(buffer) = readback_buffer {
+      let color_image = framebuffer.attachments()[0].image();
+      builder
+        .copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(color_image, buffer))
+        .unwrap();
+    }
+
+    // Build command buffer
     let command_buffer = Arc::new(builder.build().unwrap());
 
     trace!("Created command buffer");
     command_buffer
   }
 
-  /// Uploads data representing a scene's non-object data, i.e., the camera and lights.
+  /// Creates a descriptor set for set 0 of `pipeline`, holding the scene's data, lights, and
+  /// shadow maps, as well as the environment's image-based lighting maps.
+  fn create_scene_descriptor_set(
+    &self,
+    pipeline: &Arc<GraphicsPipeline>,
+    scene: &MdrScene,
+    scene_buffer: Arc<CpuAccessibleBuffer<MdrSceneData>>,
+    light_buffer: LightBuffer,
+    shadow_buffer: ShadowMatrixBuffer,
+  ) -> Arc<PersistentDescriptorSet> {
+    let environment_handle = self
+      .resource_manager
+      .get_environment_handle(scene.environment.as_ref());
+
+    PersistentDescriptorSet::new(
+      pipeline.layout().set_layouts().first().unwrap().clone(),
+      [
+        WriteDescriptorSet::buffer(0, scene_buffer),
+        WriteDescriptorSet::buffer(1, light_buffer),
+        WriteDescriptorSet::buffer(2, self.light_culling.cluster_buffer.clone()),
+        WriteDescriptorSet::image_view_sampler(
+          3,
+          self.shadow_maps.image_view.clone(),
+          self.shadow_maps.sampler.clone(),
+        ),
+        WriteDescriptorSet::buffer(4, shadow_buffer),
+        WriteDescriptorSet::image_view_sampler(
+          5,
+          environment_handle.irradiance_map.clone(),
+          environment_handle.sampler.clone(),
+        ),
+        WriteDescriptorSet::image_view_sampler(
+          6,
+          environment_handle.prefiltered_map.clone(),
+          environment_handle.sampler.clone(),
+        ),
+        WriteDescriptorSet::image_view_sampler(
+          7,
+          self.resource_manager.get_brdf_lut().clone(),
+          environment_handle.sampler.clone(),
+        ),
+      ],
+    )
+    .unwrap()
+  }
+
+  /// Records the deferred render path's lighting pass, adding the light reflected by each pixel
+  /// of the G-buffer onto the HDR target. `scene_descriptor_set` is set 0 of `lighting`'s
+  /// pipeline.
+  fn draw_deferred_lighting(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    scene: &MdrScene,
+    lighting: &MdrDeferredLightingPipeline,
+    scene_descriptor_set: Arc<PersistentDescriptorSet>,
+  ) {
+    let lighting_pipeline = &lighting.graphics_pipeline;
+    let gbuffer = self.hdr_target.gbuffer.as_ref().unwrap();
+
+    let gbuffer_descriptor_set = PersistentDescriptorSet::new(
+      lighting_pipeline
+        .layout()
+        .set_layouts()
+        .get(1)
+        .unwrap()
+        .clone(),
+      [
+        WriteDescriptorSet::image_view_sampler(
+          0,
+          gbuffer.albedo_view.clone(),
+          lighting.sampler.clone(),
+        ),
+        WriteDescriptorSet::image_view_sampler(
+          1,
+          gbuffer.normal_view.clone(),
+          lighting.sampler.clone(),
+        ),
+        WriteDescriptorSet::image_view_sampler(
+          2,
+          gbuffer.material_view.clone(),
+          lighting.sampler.clone(),
+        ),
+        WriteDescriptorSet::image_view_sampler(
+          3,
+          self.hdr_target.depth_view.clone(),
+          lighting.sampler.clone(),
+        ),
+      ],
+    )
+    .unwrap();
+
+    // Each pixel's position is found by projecting its depth back into world space
+    let view_projection = scene.camera.get_projection_matrix() * scene.camera.get_view_matrix();
+    let push_constants = MdrDeferredLightingPushConstants {
+      inverse_view_projection: view_projection
+        .try_inverse()
+        .unwrap_or_else(Matrix4::identity)
+        .into(),
+    };
+
+    let extent = gbuffer.lighting_framebuffer.extent();
+    let viewport = Viewport {
+      origin: [0.0, 0.0],
+      dimensions: [extent[0] as f32, extent[1] as f32],
+      depth_range: 0.0..1.0,
+    };
+
+    // The lighting is added onto the HDR image, so it isn't cleared
+    let mut begin_render_pass_info =
+      RenderPassBeginInfo::framebuffer(gbuffer.lighting_framebuffer.clone());
+    begin_render_pass_info.clear_values = vec![None];
+    builder
+      .begin_render_pass(begin_render_pass_info, SubpassContents::Inline)
+      .unwrap()
+      .set_viewport(0, [viewport])
+      .bind_pipeline_graphics(lighting_pipeline.clone())
+      .bind_descriptor_sets(
+        PipelineBindPoint::Graphics,
+        lighting_pipeline.layout().clone(),
+        0,
+        (scene_descriptor_set, gbuffer_descriptor_set),
+      )
+      .push_constants(lighting_pipeline.layout().clone(), 0, push_constants)
+      .draw(3, 1, 0, 0)
+      .unwrap()
+      .end_render_pass()
+      .unwrap();
+  }
+
+  /// Records a render pass drawing the scene's shadow casting objects into each shadow map in
+  /// `shadow_views`. `world_matrices` are the world matrices of the scene's objects.
+  fn draw_shadow_maps(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    scene: &MdrScene,
+    shadow_views: &MdrShadowViews,
+    world_matrices: &[Matrix4<f32>],
+  ) {
+    let shadow_pipeline = &self.shadow_pipeline.graphics_pipeline;
+
+    for (framebuffer, light_matrix) in self
+      .shadow_maps
+      .framebuffers
+      .iter()
+      .zip(shadow_views.matrices.iter())
+    {
+      let mut begin_render_pass_info = RenderPassBeginInfo::framebuffer(framebuffer.clone());
+      begin_render_pass_info.clear_values = vec![Some(ClearValue::Depth(1.0))];
+      builder
+        .begin_render_pass(begin_render_pass_info, SubpassContents::Inline)
+        .unwrap();
+      builder.bind_pipeline_graphics(shadow_pipeline.clone());
+
+      for ((_, object), world_matrix) in scene.objects().zip(world_matrices) {
+        if !object.casts_shadows {
+          continue;
+        }
+
+        // Only the positions are needed to find the object's depth
+        let mesh_handle = self.resource_manager.get_mesh_handle(&object.mesh);
+        builder
+          .bind_vertex_buffers(0, mesh_handle.positions_chunk.clone())
+          .bind_index_buffer(mesh_handle.index_chunk.clone());
+
+        // Push constants for the object's transform into the shadow map
+        let push_constants = MdrShadowPushConstants {
+          light_transformation_matrix: (light_matrix * world_matrix).into(),
+        };
+        builder.push_constants(shadow_pipeline.layout().clone(), 0, push_constants);
+
+        // Draw call
+        builder
+          .draw_indexed(mesh_handle.index_count, 1, 0, 0, 0)
+          .unwrap();
+      }
+
+      builder.end_render_pass().unwrap();
+    }
+  }
+
+  /// Records a draw of the environment `skybox` into the current render pass, wherever no object
+  /// has been drawn.
+  fn draw_skybox(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    scene: &MdrScene,
+    skybox: &MdrEnvironment,
+  ) {
+    let skybox_pipeline = &self.skybox_pipeline.graphics_pipeline;
+    let environment_handle = self.resource_manager.get_environment_handle(Some(skybox));
+
+    let skybox_descriptor_set = PersistentDescriptorSet::new(
+      skybox_pipeline
+        .layout()
+        .set_layouts()
+        .first()
+        .unwrap()
+        .clone(),
+      [WriteDescriptorSet::image_view_sampler(
+        0,
+        environment_handle.environment_map.clone(),
+        environment_handle.sampler.clone(),
+      )],
+    )
+    .unwrap();
+
+    // The skybox is infinitely far away, so only the camera's rotation moves it
+    let rotation_matrix = scene
+      .camera
+      .get_view_matrix()
+      .fixed_slice::<3, 3>(0, 0)
+      .into_owned()
+      .to_homogeneous();
+    let view_projection = scene.camera.get_projection_matrix() * rotation_matrix;
+    let push_constants = MdrSkyboxPushConstants {
+      inverse_view_projection: view_projection
+        .try_inverse()
+        .unwrap_or_else(Matrix4::identity)
+        .into(),
+    };
+
+    builder
+      .bind_pipeline_graphics(skybox_pipeline.clone())
+      .bind_descriptor_sets(
+        PipelineBindPoint::Graphics,
+        skybox_pipeline.layout().clone(),
+        0,
+        skybox_descriptor_set,
+      )
+      .push_constants(skybox_pipeline.layout().clone(), 0, push_constants)
+      .draw(3, 1, 0, 0)
+      .unwrap();
+  }
+
+  /// Uploads data representing a scene's non-object data, i.e., the camera and lights, for a
+  /// render target of `extent` pixels. Returns the scene buffer, the light buffer, and the buffer
+  /// of shadow map matrices.
   fn upload_scene_data(
     logical_device: &Arc<Device>,
     scene: &MdrScene,
-  ) -> Arc<CpuAccessibleBuffer<MdrSceneData>> {
+    extent: [u32; 2],
+    shadow_views: &MdrShadowViews,
+  ) -> (
+    Arc<CpuAccessibleBuffer<MdrSceneData>>,
+    LightBuffer,
+    ShadowMatrixBuffer,
+  ) {
     // Camera data
     let view_matrix = scene.camera.get_view_matrix();
-    let projection_matrix = scene.camera.get_projection_matrix();
-
-    let view_transform_column = view_matrix.column(3);
-    let position_vector = Vector3::new(
-      view_transform_column.x,
-      view_transform_column.y,
-      view_transform_column.z,
-    );
+    let projection_matrix = scene
+      .camera
+      .projection
+      .projection_matrix(scene.camera.aspect_ratio);
+
+    // The camera's world space position is where the inverse view puts the view space origin
+    let eye_column = view_matrix
+      .try_inverse()
+      .unwrap_or_else(Matrix4::identity)
+      .column(3)
+      .into_owned();
+    let position_vector = Vector3::new(eye_column.x, eye_column.y, eye_column.z);
     // Camera data object
     let camera = CameraData {
       position: position_vector.into(),
@@ -433,31 +1091,105 @@ impl MdrGraphicsContext {
       proj: projection_matrix.into(),
     };
 
-    // Lighting data
-    let point_lights: [PointLightData; MAX_POINT_LIGHTS] =
-      scene.lights.get_light_array().map(|light| PointLightData {
-        color: light.color.into(),
-        _dummy0: [0; 4],
-        position: light.translation.into(),
-        brightness: light.brightness,
-      });
-    CpuAccessibleBuffer::from_data(
+    // Lighting data, buffers can't be empty so scenes without lights upload an unused light
+    let mut lights: Vec<LightData> = scene
+      .lights
+      .iter()
+      .zip(shadow_views.light_indices.iter())
+      .map(|(light, shadow_index)| Self::create_light_data(scene, light, *shadow_index))
+      .collect();
+    if lights.is_empty() {
+      lights.push(Self::create_light_data(scene, &MdrLight::unused(), None));
+    }
+    let light_buffer = CpuAccessibleBuffer::from_iter(
+      logical_device.clone(),
+      BufferUsage::storage_buffer(),
+      false,
+      lights,
+    )
+    .unwrap();
+
+    let scene_buffer = CpuAccessibleBuffer::from_data(
       logical_device.clone(),
       BufferUsage::storage_buffer(),
       false,
       MdrSceneData {
         camera,
-        point_lights,
-        point_light_count: scene.lights.get_count(),
+        cascade_splits: shadow_views.cascade_splits,
+        screen_size: [extent[0] as f32, extent[1] as f32],
+        near_plane: scene.camera.projection.near_plane(),
+        far_plane: scene.camera.projection.far_plane(),
+        light_count: scene.lights.get_count(),
+        debug_view: match scene.debug_view {
+          MdrDebugView::None => DEBUG_VIEW_NONE,
+          MdrDebugView::ShadowCascades => DEBUG_VIEW_SHADOW_CASCADES,
+        },
       },
     )
-    .unwrap()
+    .unwrap();
+
+    // Shadow map data, which also can't be empty
+    let mut shadow_matrices: Vec<[[f32; 4]; 4]> = shadow_views
+      .matrices
+      .iter()
+      .map(|matrix| (*matrix).into())
+      .collect();
+    if shadow_matrices.is_empty() {
+      shadow_matrices.push(Matrix4::<f32>::identity().into());
+    }
+    let shadow_buffer = CpuAccessibleBuffer::from_iter(
+      logical_device.clone(),
+      BufferUsage::storage_buffer(),
+      false,
+      shadow_matrices,
+    )
+    .unwrap();
+
+    (scene_buffer, light_buffer, shadow_buffer)
   }
 
-  /// Create a Vulkan instance with optional debug extensions.
-  fn create_instance(debug_enabled: bool) -> Arc<Instance> {
+  /// Converts `light` into the layout used by the shaders. `shadow_index` is the light's first
+  /// shadow map, if it has any.
+  fn create_light_data(scene: &MdrScene, light: &MdrLight, shadow_index: Option<u32>) -> LightData {
+    let direction = scene
+      .light_world_direction(light)
+      .unwrap_or_else(Vector3::z);
+    let (kind, cos_inner_cone, cos_outer_cone) = match light.kind {
+      MdrLightKind::Point => (LIGHT_KIND_POINT, 1.0, 1.0),
+      MdrLightKind::Directional { .. } => (LIGHT_KIND_DIRECTIONAL, 1.0, 1.0),
+      MdrLightKind::Spot {
+        inner_cone_angle,
+        outer_cone_angle,
+        ..
+      } => {
+        // The shader fades between the cones, so the outer cone must be strictly wider
+        let cos_inner_cone = inner_cone_angle.cos();
+        let cos_outer_cone = outer_cone_angle.cos().min(cos_inner_cone - f32::EPSILON);
+        (LIGHT_KIND_SPOT, cos_inner_cone, cos_outer_cone)
+      }
+    };
+
+    LightData {
+      color: light.color.into(),
+      kind,
+      position: scene.light_world_position(light).into(),
+      brightness: light.brightness,
+      direction: direction.into(),
+      cos_inner_cone,
+      cos_outer_cone,
+      range: light.range,
+      shadow_index: shadow_index.map_or(-1, |index| index as i32),
+      _padding: 0.0,
+    }
+  }
+
+  /// Create a Vulkan instance with the `required_extensions` and optional debug extensions.
+  fn create_instance(
+    required_extensions: InstanceExtensions,
+    debug_enabled: bool,
+  ) -> Arc<Instance> {
     let required_extensions = {
-      let mut extensions = vulkano_win::required_extensions();
+      let mut extensions = required_extensions;
 
       // If debugging is enabled, add the debug utility extension
       if debug_enabled {
@@ -528,7 +1260,7 @@ impl MdrGraphicsContext {
   fn pick_physical_device(
     instance: &Arc<Instance>,
     surface: Arc<Surface<Window>>,
-  ) -> (PhysicalDevice, QueueFamily) {
+  ) -> (PhysicalDevice<'_>, QueueFamily<'_>) {
     let device_extensions = DeviceExtensions {
       khr_swapchain: true,
       ..DeviceExtensions::none()
@@ -541,13 +1273,28 @@ impl MdrGraphicsContext {
           .find(|&q| q.supports_graphics() && q.supports_surface(&surface).unwrap_or(false))
           .map(|q| (p, q))
       })
-      .min_by_key(|(p, _)| match p.properties().device_type {
-        PhysicalDeviceType::DiscreteGpu => 0,
-        PhysicalDeviceType::IntegratedGpu => 1,
-        PhysicalDeviceType::VirtualGpu => 2,
-        PhysicalDeviceType::Cpu => 3,
-        PhysicalDeviceType::Other => 4,
-      });
+      .min_by_key(|(p, _)| Self::device_type_priority(p.properties().device_type));
+
+    match device_creation_results {
+      Some(value) => value,
+      None => {
+        panic!("Failed to find physical device and queue family.");
+      }
+    }
+  }
+
+  /// Select a physical device to use for headless rendering, which only requires a graphics
+  /// queue. Returns the device and associated queue family.
+  fn pick_headless_physical_device(
+    instance: &Arc<Instance>,
+  ) -> (PhysicalDevice<'_>, QueueFamily<'_>) {
+    let device_creation_results = PhysicalDevice::enumerate(instance)
+      .filter_map(|p| {
+        p.queue_families()
+          .find(|&q| q.supports_graphics())
+          .map(|q| (p, q))
+      })
+      .min_by_key(|(p, _)| Self::device_type_priority(p.properties().device_type));
 
     match device_creation_results {
       Some(value) => value,
@@ -557,16 +1304,89 @@ impl MdrGraphicsContext {
     }
   }
 
+  /// Ranks device types for device selection, lower values are preferred.
+  fn device_type_priority(device_type: PhysicalDeviceType) -> u32 {
+    match device_type {
+      PhysicalDeviceType::DiscreteGpu => 0,
+      PhysicalDeviceType::IntegratedGpu => 1,
+      PhysicalDeviceType::VirtualGpu => 2,
+      PhysicalDeviceType::Cpu => 3,
+      PhysicalDeviceType::Other => 4,
+    }
+  }
+
+  /// Returns the largest number of samples per pixel, up to `requested`, which the device
+  /// supports for both the scene's color and depth attachments, and for sampling the depth
+  /// attachment in screen-space effects.
+  fn pick_sample_count(logical_device: &Arc<Device>, requested: u32) -> SampleCount {
+    let properties = logical_device.physical_device().properties();
+    let color_counts = properties.framebuffer_color_sample_counts;
+    let depth_counts = properties
+      .framebuffer_depth_sample_counts
+      .intersection(&properties.sampled_image_depth_sample_counts);
+    let supported_counts = [
+      (
+        SampleCount::Sample64,
+        color_counts.sample64 && depth_counts.sample64,
+      ),
+      (
+        SampleCount::Sample32,
+        color_counts.sample32 && depth_counts.sample32,
+      ),
+      (
+        SampleCount::Sample16,
+        color_counts.sample16 && depth_counts.sample16,
+      ),
+      (
+        SampleCount::Sample8,
+        color_counts.sample8 && depth_counts.sample8,
+      ),
+      (
+        SampleCount::Sample4,
+        color_counts.sample4 && depth_counts.sample4,
+      ),
+      (
+        SampleCount::Sample2,
+        color_counts.sample2 && depth_counts.sample2,
+      ),
+    ];
+
+    let samples = supported_counts
+      .into_iter()
+      .find(|(count, supported)| *supported && *count as u32 <= requested)
+      .map_or(SampleCount::Sample1, |(count, _)| count);
+    if samples as u32 != requested.max(1) {
+      warn!(
+        "{} samples per pixel requested, using {} which this device supports",
+        requested, samples as u32
+      );
+    }
+
+    samples
+  }
+
   /// Create a Vulkan logical device and queue.
   fn create_logical_device(
     physical_device: PhysicalDevice,
     device_extensions: DeviceExtensions,
     queue_family: QueueFamily,
   ) -> (Arc<Device>, Arc<Queue>) {
+    // Enable anisotropic texture filtering and compressed texture formats where the device
+    // supports them
+    let supported_features = physical_device.supported_features();
+    let enabled_features = Features {
+      sampler_anisotropy: supported_features.sampler_anisotropy,
+      texture_compression_bc: supported_features.texture_compression_bc,
+      texture_compression_etc2: supported_features.texture_compression_etc2,
+      texture_compression_astc_ldr: supported_features.texture_compression_astc_ldr,
+      ..Features::none()
+    };
+
     let device_creation_results = Device::new(
       physical_device,
       DeviceCreateInfo {
         enabled_extensions: device_extensions,
+        enabled_features,
         queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
         ..Default::default()
       },
@@ -596,21 +1416,28 @@ impl MdrGraphicsContext {
       .expect("Failed to retrieve surface capabilities.");
     // Get other settings
     let dimensions = window.dimensions();
-    let vk_image_format = Some(
-      physical_device
-        .surface_formats(surface, Default::default())
-        .unwrap()[0]
-        .0,
-    );
+    // Prefer an sRGB format, so that tone mapped colors are gamma encoded as they are written
+    let surface_formats = physical_device
+      .surface_formats(surface, Default::default())
+      .unwrap();
+    let (vk_image_format, vk_color_space) = surface_formats
+      .iter()
+      .find(|(format, _)| format.type_color() == Some(NumericType::SRGB))
+      .unwrap_or(&surface_formats[0]);
 
     let swapchain_result = Swapchain::new(
       logical_device.clone(),
       surface.clone(),
       SwapchainCreateInfo {
         min_image_count: surface_capabilities.min_image_count + 1,
-        image_format: vk_image_format,
+        image_format: Some(*vk_image_format),
+        image_color_space: *vk_color_space,
         image_extent: dimensions.into(),
-        image_usage: ImageUsage::color_attachment(),
+        // Allow copying out of swapchain images where supported so frames can be captured
+        image_usage: ImageUsage {
+          transfer_src: surface_capabilities.supported_usage_flags.transfer_src,
+          ..ImageUsage::color_attachment()
+        },
         composite_alpha: surface_capabilities
           .supported_composite_alpha
           .iter()
@@ -628,33 +1455,53 @@ impl MdrGraphicsContext {
     }
   }
 
-  fn create_framebuffers(
+  /// Create the image that headless contexts render into. It can be used as a transfer
+  /// source so that its contents can be read back, and is sRGB so that colors are gamma encoded
+  /// as they are written.
+  fn create_offscreen_image(
     logical_device: &Arc<Device>,
-    swapchain_images: &Vec<Arc<SwapchainImage<Window>>>,
+    dimensions: [u32; 2],
+  ) -> Arc<AttachmentImage> {
+    let usage = ImageUsage {
+      transfer_src: true,
+      ..ImageUsage::color_attachment()
+    };
+
+    match AttachmentImage::with_usage(
+      logical_device.clone(),
+      dimensions,
+      Format::R8G8B8A8_SRGB,
+      usage,
+    ) {
+      Ok(image) => image,
+      Err(e) => {
+        panic!("Failed to create offscreen image: {}", e);
+      }
+    }
+  }
+
+  /// Creates a framebuffer for each of the target's color images, which the tone mapping pass
+  /// draws into.
+  fn create_framebuffers(
+    target: &MdrRenderTarget,
     render_pass: &MdrRenderPass,
   ) -> Vec<Arc<Framebuffer>> {
-    let dimensions = swapchain_images[0].dimensions().width_height();
-    // Create depth buffer
-    let depth_buffer_image =
-      AttachmentImage::transient(logical_device.clone(), dimensions, Format::D16_UNORM).unwrap();
-    let depth_buffer_view = ImageView::new_default(depth_buffer_image).unwrap();
-
     // Create and return framebuffers
-    return swapchain_images
-      .iter()
-      .map(|image| {
-        let color_view = ImageView::new_default(image.clone()).unwrap();
+    target
+      .image_views()
+      .into_iter()
+      .map(|color_view| {
         Framebuffer::new(
           render_pass.get_pass(),
           FramebufferCreateInfo {
-            // Attach color and depth view
-            attachments: vec![color_view, depth_buffer_view.clone()],
+            // Attach color view
+            attachments: vec![color_view],
             ..Default::default()
           },
         )
         .unwrap()
       })
-      .collect::<Vec<_>>();
+      .collect::<Vec<_>>()
   }
 
   /// Sets up a vector of futures corresponding to each framebuffer. These futures will be used to chain
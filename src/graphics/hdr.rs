@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use vulkano::{
+  device::Device,
+  format::Format,
+  image::{
+    view::{ImageView, ImageViewAbstract},
+    AttachmentImage, ImageUsage, SampleCount,
+  },
+  render_pass::{Framebuffer, FramebufferCreateInfo},
+};
+
+use super::render_pass::MdrRenderPass;
+
+/// Format of the color image the scene is drawn into, which holds colors brighter than white and
+/// which every device supports for color attachments and sampling.
+pub(crate) const HDR_FORMAT: Format = Format::R16G16B16A16_SFLOAT;
+
+/// Formats of the G-buffer images the deferred render path draws objects into. The albedo holds
+/// each surface's base color and ambient occlusion, the normal its world space normal and whether
+/// it receives shadows, and the material its roughness and metalness.
+pub(crate) const GBUFFER_ALBEDO_FORMAT: Format = Format::R8G8B8A8_SRGB;
+pub(crate) const GBUFFER_NORMAL_FORMAT: Format = Format::R16G16B16A16_SFLOAT;
+pub(crate) const GBUFFER_MATERIAL_FORMAT: Format = Format::R8G8B8A8_UNORM;
+
+/// The floating point color image the scene is drawn into before it is tone mapped into the
+/// render target, along with the depth buffer used while drawing it. When the scene's render
+/// pass is multisampled, it draws into multisampled color and depth images which are resolved
+/// into the color image. On the deferred render path, objects are drawn into a G-buffer which is
+/// then lit into the color image.
+pub(crate) struct MdrHdrTarget {
+  /// Framebuffer of the scene's render pass, attaching the color image and depth buffer, as well
+  /// as the multisampled color image or the G-buffer images if there are any.
+  pub framebuffer: Arc<Framebuffer>,
+  /// A view of the color image, for sampling in the tone mapping pass.
+  pub image_view: Arc<ImageView<AttachmentImage>>,
+  /// A view of the depth buffer, with as many samples per pixel as the scene's render pass, for
+  /// screen-space effects.
+  pub depth_view: Arc<ImageView<AttachmentImage>>,
+  /// The surface properties the scene's objects were drawn with, on the deferred render path.
+  pub gbuffer: Option<MdrGBuffer>,
+}
+
+/// The images the deferred render path draws objects into, which the lighting pass reads to
+/// light each pixel of the HDR target.
+pub(crate) struct MdrGBuffer {
+  /// Framebuffer of the lighting pass, attaching only the HDR target's color image.
+  pub lighting_framebuffer: Arc<Framebuffer>,
+  pub albedo_view: Arc<ImageView<AttachmentImage>>,
+  pub normal_view: Arc<ImageView<AttachmentImage>>,
+  pub material_view: Arc<ImageView<AttachmentImage>>,
+}
+
+impl MdrHdrTarget {
+  /// Creates images of `dimensions` (`[width, height]`) to be drawn into with `render_pass`,
+  /// with as many samples per pixel as its attachments have. When `lighting_render_pass` is
+  /// given, `render_pass` is a G-buffer pass and the G-buffer images are created as well.
+  pub fn new(
+    logical_device: &Arc<Device>,
+    render_pass: &MdrRenderPass,
+    lighting_render_pass: Option<&MdrRenderPass>,
+    dimensions: [u32; 2],
+  ) -> Self {
+    let image_view = Self::create_sampled_image(logical_device, dimensions, HDR_FORMAT);
+
+    // The depth buffer is read after the scene is drawn, by screen-space effects and the
+    // deferred lighting pass
+    let samples = render_pass.samples();
+    let depth_buffer_image = AttachmentImage::multisampled_with_usage(
+      logical_device.clone(),
+      dimensions,
+      samples,
+      Format::D16_UNORM,
+      ImageUsage {
+        sampled: true,
+        ..ImageUsage::depth_stencil_attachment()
+      },
+    )
+    .unwrap();
+    let depth_view = ImageView::new_default(depth_buffer_image).unwrap();
+
+    if let Some(lighting_render_pass) = lighting_render_pass {
+      return Self::new_deferred(
+        logical_device,
+        render_pass,
+        lighting_render_pass,
+        dimensions,
+        image_view,
+        depth_view,
+      );
+    }
+
+    // Attachments are in the order of the render pass's
+    let attachments: Vec<Arc<dyn ImageViewAbstract>> = if samples == SampleCount::Sample1 {
+      vec![image_view.clone(), depth_view.clone()]
+    } else {
+      // The multisampled color image is only needed while the scene is drawn
+      let multisampled_image = AttachmentImage::transient_multisampled(
+        logical_device.clone(),
+        dimensions,
+        samples,
+        HDR_FORMAT,
+      )
+      .unwrap();
+      let multisampled_view = ImageView::new_default(multisampled_image).unwrap();
+
+      vec![multisampled_view, depth_view.clone(), image_view.clone()]
+    };
+
+    let framebuffer = Framebuffer::new(
+      render_pass.get_pass(),
+      FramebufferCreateInfo {
+        attachments,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    Self {
+      framebuffer,
+      image_view,
+      depth_view,
+      gbuffer: None,
+    }
+  }
+
+  fn new_deferred(
+    logical_device: &Arc<Device>,
+    render_pass: &MdrRenderPass,
+    lighting_render_pass: &MdrRenderPass,
+    dimensions: [u32; 2],
+    image_view: Arc<ImageView<AttachmentImage>>,
+    depth_view: Arc<ImageView<AttachmentImage>>,
+  ) -> Self {
+    let albedo_view = Self::create_sampled_image(logical_device, dimensions, GBUFFER_ALBEDO_FORMAT);
+    let normal_view = Self::create_sampled_image(logical_device, dimensions, GBUFFER_NORMAL_FORMAT);
+    let material_view =
+      Self::create_sampled_image(logical_device, dimensions, GBUFFER_MATERIAL_FORMAT);
+
+    // Attachments are in the order of the render pass's
+    let framebuffer = Framebuffer::new(
+      render_pass.get_pass(),
+      FramebufferCreateInfo {
+        attachments: vec![
+          image_view.clone(),
+          albedo_view.clone(),
+          normal_view.clone(),
+          material_view.clone(),
+          depth_view.clone(),
+        ],
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let lighting_framebuffer = Framebuffer::new(
+      lighting_render_pass.get_pass(),
+      FramebufferCreateInfo {
+        attachments: vec![image_view.clone()],
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    Self {
+      framebuffer,
+      image_view,
+      depth_view,
+      gbuffer: Some(MdrGBuffer {
+        lighting_framebuffer,
+        albedo_view,
+        normal_view,
+        material_view,
+      }),
+    }
+  }
+
+  /// Creates a color image of `dimensions` and `format` which can be both drawn into and
+  /// sampled.
+  fn create_sampled_image(
+    logical_device: &Arc<Device>,
+    dimensions: [u32; 2],
+    format: Format,
+  ) -> Arc<ImageView<AttachmentImage>> {
+    let image = AttachmentImage::with_usage(
+      logical_device.clone(),
+      dimensions,
+      format,
+      ImageUsage {
+        sampled: true,
+        ..ImageUsage::color_attachment()
+      },
+    )
+    .unwrap();
+    ImageView::new_default(image).unwrap()
+  }
+}
@@ -1,9 +1,17 @@
+mod capture;
 mod context;
+mod hdr;
 mod pipeline;
+mod post_processing;
 mod render_pass;
 pub mod resources;
 mod shaders;
+mod shadows;
+mod target;
 mod window;
 
-pub use context::MdrGraphicsContext;
+pub use capture::MdrCaptureError;
+pub use context::{MdrGraphicsContext, MdrRenderPath};
+// The manager's methods return `MdrResourceError`, which is kept alongside it
+#[allow(unused_imports)]
 pub use resources::{MdrResourceError, MdrResourceManager};
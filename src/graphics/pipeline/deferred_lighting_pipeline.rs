@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use vulkano::{
+  device::Device,
+  pipeline::{
+    graphics::{
+      color_blend::ColorBlendState, input_assembly::InputAssemblyState,
+      rasterization::RasterizationState, vertex_input::BuffersDefinition, viewport::ViewportState,
+    },
+    GraphicsPipeline,
+  },
+  sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+};
+
+use crate::graphics::{hdr::HDR_FORMAT, render_pass::MdrRenderPass, shaders};
+
+/// The pipeline used by the deferred render path for lighting the G-buffer. It draws one
+/// triangle covering the screen, adding the light reflected by the surface at each pixel onto
+/// the HDR image, which already holds the clear color, emitted light, and skybox.
+pub struct MdrDeferredLightingPipeline {
+  render_pass: MdrRenderPass,
+  pub graphics_pipeline: Arc<GraphicsPipeline>,
+  /// Reads the G-buffer images one texel at a time.
+  pub sampler: Arc<Sampler>,
+}
+
+impl MdrDeferredLightingPipeline {
+  pub fn new(logical_device: &Arc<Device>) -> Self {
+    // Load shader modules to GPU
+    let (vertex_shader, fragment_shader) = shaders::load_deferred_lighting_shaders(logical_device);
+
+    // The lighting pass draws onto the HDR image left by the G-buffer pass
+    let render_pass = MdrRenderPass::new_color_blended(logical_device, HDR_FORMAT);
+
+    let graphics_pipeline = GraphicsPipeline::start()
+      // The triangle's vertices are generated in the vertex shader, so there are no buffers
+      .vertex_input_state(BuffersDefinition::new())
+      // Link the vertex shader
+      .vertex_shader(vertex_shader.entry_point("main").unwrap(), ())
+      // Input assembly settings (we use the defaults)
+      .input_assembly_state(InputAssemblyState::new())
+      // The viewport is set to the HDR image's size before each draw
+      .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+      // Fixed functions of the rasterizer, the triangle is never culled
+      .rasterization_state(RasterizationState::new())
+      // Link the fragment shader
+      .fragment_shader(fragment_shader.entry_point("main").unwrap(), ())
+      // Add the reflected light onto the HDR image
+      .color_blend_state(ColorBlendState::new(1).blend_additive())
+      // The render pass to use for this pipeline
+      .render_pass(render_pass.get_subpass())
+      // Build and unwrap to get the pipeline object
+      .build(logical_device.clone())
+      .unwrap();
+
+    let sampler = Sampler::new(
+      logical_device.clone(),
+      SamplerCreateInfo {
+        mag_filter: Filter::Nearest,
+        min_filter: Filter::Nearest,
+        address_mode: [SamplerAddressMode::ClampToEdge; 3],
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    Self {
+      render_pass,
+      graphics_pipeline,
+      sampler,
+    }
+  }
+
+  /// Returns the render pass which the lighting is drawn with, attaching only the HDR image.
+  pub fn render_pass(&self) -> &MdrRenderPass {
+    &self.render_pass
+  }
+}
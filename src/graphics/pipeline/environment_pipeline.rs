@@ -0,0 +1,371 @@
+use std::sync::Arc;
+
+use vulkano::{
+  command_buffer::{AutoCommandBufferBuilder, PrimaryAutoCommandBuffer},
+  descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
+  device::{Device, DeviceOwned, Queue},
+  format::Format,
+  image::{
+    view::{ImageView, ImageViewCreateInfo, ImageViewType},
+    ImageAccess, ImageAspects, ImageCreateFlags, ImageDimensions, ImageLayout,
+    ImageSubresourceRange, ImageUsage, ImmutableImage, MipmapsCount,
+  },
+  pipeline::{ComputePipeline, Pipeline, PipelineBindPoint},
+  sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode},
+  shader::ShaderModule,
+};
+
+use crate::{
+  config::{
+    BRDF_LUT_SIZE, IRRADIANCE_MAP_SIZE, MAX_ENVIRONMENT_CUBE_SIZE, PREFILTERED_MAP_MIP_LEVELS,
+    PREFILTERED_MAP_SIZE,
+  },
+  graphics::{
+    resources::environment::MdrGpuEnvironmentHandle,
+    shaders::{self, prefilter_shader::ty::MdrPrefilterPushConstants},
+  },
+};
+
+/// Format of the images generated from environments, which every device supports for storage.
+const ENVIRONMENT_MAP_FORMAT: Format = Format::R16G16B16A16_SFLOAT;
+
+/// Width and height of the workgroups of the environment compute shaders, matching their
+/// `local_size_x` and `local_size_y`.
+const WORKGROUP_SIZE: u32 = 8;
+
+/// A view which the compute shaders write the faces of one mip level of a cubemap into.
+type StorageView = Arc<ImageView<dyn ImageAccess>>;
+
+/// The compute pipelines which turn an equirectangular environment image, or the six faces of
+/// a cubemap, into the maps used for image-based lighting: a cubemap of the environment, an
+/// irradiance map for diffuse lighting, and a prefiltered map for specular lighting, with a mip
+/// level for each roughness.
+pub struct MdrEnvironmentPipeline {
+  pub equirect_to_cube_pipeline: Arc<ComputePipeline>,
+  pub faces_to_cube_pipeline: Arc<ComputePipeline>,
+  pub irradiance_pipeline: Arc<ComputePipeline>,
+  pub prefilter_pipeline: Arc<ComputePipeline>,
+  pub brdf_lut_pipeline: Arc<ComputePipeline>,
+
+  /// Reads equirectangular images, which are stored as 32-bit floats that can't always be
+  /// filtered linearly.
+  equirect_sampler: Arc<Sampler>,
+  /// Reads the generated maps, blending between the mip levels of prefiltered maps.
+  sampler: Arc<Sampler>,
+}
+
+impl MdrEnvironmentPipeline {
+  pub fn new(logical_device: &Arc<Device>) -> Self {
+    // Load shader modules to GPU
+    let [equirect_to_cube, faces_to_cube, irradiance, prefilter, brdf_lut] =
+      shaders::load_environment_shaders(logical_device);
+
+    let equirect_sampler = Sampler::new(
+      logical_device.clone(),
+      SamplerCreateInfo {
+        mag_filter: Filter::Nearest,
+        min_filter: Filter::Nearest,
+        address_mode: [
+          SamplerAddressMode::Repeat,
+          SamplerAddressMode::ClampToEdge,
+          SamplerAddressMode::ClampToEdge,
+        ],
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let sampler = Sampler::new(
+      logical_device.clone(),
+      SamplerCreateInfo {
+        mag_filter: Filter::Linear,
+        min_filter: Filter::Linear,
+        mipmap_mode: SamplerMipmapMode::Linear,
+        address_mode: [SamplerAddressMode::ClampToEdge; 3],
+        lod: 0.0..=PREFILTERED_MAP_MIP_LEVELS as f32,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    Self {
+      equirect_to_cube_pipeline: Self::create_compute_pipeline(logical_device, &equirect_to_cube),
+      faces_to_cube_pipeline: Self::create_compute_pipeline(logical_device, &faces_to_cube),
+      irradiance_pipeline: Self::create_compute_pipeline(logical_device, &irradiance),
+      prefilter_pipeline: Self::create_compute_pipeline(logical_device, &prefilter),
+      brdf_lut_pipeline: Self::create_compute_pipeline(logical_device, &brdf_lut),
+
+      equirect_sampler,
+      sampler,
+    }
+  }
+
+  /// Records commands into `builder` which generate the image-based lighting maps of the
+  /// environment in `equirect_map`. The returned maps can be used once the commands have run.
+  pub fn generate_from_equirect(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    queue: &Arc<Queue>,
+    equirect_map: Arc<ImageView<ImmutableImage>>,
+  ) -> MdrGpuEnvironmentHandle {
+    // Give the cubemap about as many texels around its equator as the image has
+    let cube_size = (equirect_map.image().dimensions().width() / 4)
+      .next_power_of_two()
+      .clamp(PREFILTERED_MAP_SIZE, MAX_ENVIRONMENT_CUBE_SIZE);
+
+    // Convert the equirectangular image into a cubemap
+    let (environment_map, environment_storage) = self.create_cube_map(queue, cube_size, 1);
+    self.dispatch_cube(
+      builder,
+      &self.equirect_to_cube_pipeline,
+      [
+        WriteDescriptorSet::image_view_sampler(0, equirect_map, self.equirect_sampler.clone()),
+        WriteDescriptorSet::image_view(1, environment_storage[0].clone()),
+      ],
+      cube_size,
+    );
+
+    self.filter_environment(builder, queue, environment_map)
+  }
+
+  /// Records commands into `builder` which generate the image-based lighting maps of the
+  /// environment whose cubemap faces are the six layers of `face_maps`, in the order +x, -x,
+  /// +y, -y, +z, -z. The returned maps can be used once the commands have run.
+  pub fn generate_from_faces(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    queue: &Arc<Queue>,
+    face_maps: Arc<ImageView<ImmutableImage>>,
+  ) -> MdrGpuEnvironmentHandle {
+    let cube_size = face_maps.image().dimensions().width();
+
+    // Copy the faces into a cubemap
+    let (environment_map, environment_storage) = self.create_cube_map(queue, cube_size, 1);
+    self.dispatch_cube(
+      builder,
+      &self.faces_to_cube_pipeline,
+      [
+        WriteDescriptorSet::image_view_sampler(0, face_maps, self.equirect_sampler.clone()),
+        WriteDescriptorSet::image_view(1, environment_storage[0].clone()),
+      ],
+      cube_size,
+    );
+
+    self.filter_environment(builder, queue, environment_map)
+  }
+
+  /// Records commands into `builder` which filter the cubemap `environment_map` into the maps
+  /// used for image-based lighting.
+  fn filter_environment(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    queue: &Arc<Queue>,
+    environment_map: Arc<ImageView<ImmutableImage>>,
+  ) -> MdrGpuEnvironmentHandle {
+    // Convolve the cubemap into the light reaching each surface orientation
+    let (irradiance_map, irradiance_storage) = self.create_cube_map(queue, IRRADIANCE_MAP_SIZE, 1);
+    self.dispatch_cube(
+      builder,
+      &self.irradiance_pipeline,
+      [
+        WriteDescriptorSet::image_view_sampler(0, environment_map.clone(), self.sampler.clone()),
+        WriteDescriptorSet::image_view(1, irradiance_storage[0].clone()),
+      ],
+      IRRADIANCE_MAP_SIZE,
+    );
+
+    // Filter the cubemap into one mip level for each roughness
+    let (prefiltered_map, prefiltered_storage) =
+      self.create_cube_map(queue, PREFILTERED_MAP_SIZE, PREFILTERED_MAP_MIP_LEVELS);
+    for (level, storage) in prefiltered_storage.into_iter().enumerate() {
+      let push_constants = MdrPrefilterPushConstants {
+        roughness: level as f32 / (PREFILTERED_MAP_MIP_LEVELS - 1) as f32,
+      };
+      builder.push_constants(self.prefilter_pipeline.layout().clone(), 0, push_constants);
+      self.dispatch_cube(
+        builder,
+        &self.prefilter_pipeline,
+        [
+          WriteDescriptorSet::image_view_sampler(0, environment_map.clone(), self.sampler.clone()),
+          WriteDescriptorSet::image_view(1, storage),
+        ],
+        PREFILTERED_MAP_SIZE >> level,
+      );
+    }
+
+    MdrGpuEnvironmentHandle {
+      environment_map,
+      irradiance_map,
+      prefiltered_map,
+      sampler: self.sampler.clone(),
+    }
+  }
+
+  /// Records commands into `builder` which generate the lookup table of the specular BRDF used
+  /// with every environment's prefiltered map.
+  pub fn generate_brdf_lut(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    queue: &Arc<Queue>,
+  ) -> Arc<ImageView<ImmutableImage>> {
+    let (image, storage_image) = ImmutableImage::uninitialized(
+      self.brdf_lut_pipeline.device().clone(),
+      ImageDimensions::Dim2d {
+        width: BRDF_LUT_SIZE,
+        height: BRDF_LUT_SIZE,
+        array_layers: 1,
+      },
+      ENVIRONMENT_MAP_FORMAT,
+      MipmapsCount::One,
+      ImageUsage {
+        storage: true,
+        sampled: true,
+        ..ImageUsage::none()
+      },
+      ImageCreateFlags::none(),
+      ImageLayout::General,
+      [queue.family()],
+    )
+    .unwrap();
+
+    let descriptor_set = PersistentDescriptorSet::new(
+      self
+        .brdf_lut_pipeline
+        .layout()
+        .set_layouts()
+        .first()
+        .unwrap()
+        .clone(),
+      [WriteDescriptorSet::image_view(
+        0,
+        ImageView::new_default(storage_image as Arc<dyn ImageAccess>).unwrap(),
+      )],
+    )
+    .unwrap();
+    let workgroups = BRDF_LUT_SIZE.div_ceil(WORKGROUP_SIZE);
+    builder
+      .bind_pipeline_compute(self.brdf_lut_pipeline.clone())
+      .bind_descriptor_sets(
+        PipelineBindPoint::Compute,
+        self.brdf_lut_pipeline.layout().clone(),
+        0,
+        descriptor_set,
+      )
+      .dispatch([workgroups, workgroups, 1])
+      .unwrap();
+
+    ImageView::new_default(image).unwrap()
+  }
+
+  fn create_compute_pipeline(
+    logical_device: &Arc<Device>,
+    compute_shader: &Arc<ShaderModule>,
+  ) -> Arc<ComputePipeline> {
+    ComputePipeline::new(
+      logical_device.clone(),
+      compute_shader.entry_point("main").unwrap(),
+      &(),
+      None,
+      |_| {},
+    )
+    .unwrap()
+  }
+
+  /// Creates a cubemap with faces `size` texels wide and `mip_levels` mip levels. Returns a
+  /// view of the whole cubemap for sampling, and a view of the faces of each mip level for the
+  /// compute shaders to write into.
+  fn create_cube_map(
+    &self,
+    queue: &Arc<Queue>,
+    size: u32,
+    mip_levels: u32,
+  ) -> (Arc<ImageView<ImmutableImage>>, Vec<StorageView>) {
+    // The general layout allows the compute shaders to write into the image, and the mesh
+    // shader to sample it, without any transitions
+    let (image, storage_image) = ImmutableImage::uninitialized(
+      self.sampler.device().clone(),
+      ImageDimensions::Dim2d {
+        width: size,
+        height: size,
+        array_layers: 6,
+      },
+      ENVIRONMENT_MAP_FORMAT,
+      MipmapsCount::Specific(mip_levels),
+      ImageUsage {
+        storage: true,
+        sampled: true,
+        ..ImageUsage::none()
+      },
+      ImageCreateFlags {
+        cube_compatible: true,
+        ..ImageCreateFlags::none()
+      },
+      ImageLayout::General,
+      [queue.family()],
+    )
+    .unwrap();
+    // Only views of images as trait objects can be bound, as the image's initialization handle
+    // isn't `Debug`
+    let storage_image: Arc<dyn ImageAccess> = storage_image;
+
+    // Storage images can't be cubes, so each mip level is written as an array of its faces
+    let storage_views = (0..mip_levels)
+      .map(|level| {
+        ImageView::new(
+          storage_image.clone(),
+          ImageViewCreateInfo {
+            view_type: ImageViewType::Dim2dArray,
+            subresource_range: ImageSubresourceRange {
+              aspects: ImageAspects {
+                color: true,
+                ..ImageAspects::none()
+              },
+              mip_levels: level..level + 1,
+              array_layers: 0..6,
+            },
+            ..ImageViewCreateInfo::from_image(&storage_image)
+          },
+        )
+        .unwrap()
+      })
+      .collect();
+
+    let cube_view = ImageView::new(
+      image.clone(),
+      ImageViewCreateInfo {
+        view_type: ImageViewType::Cube,
+        ..ImageViewCreateInfo::from_image(&image)
+      },
+    )
+    .unwrap();
+
+    (cube_view, storage_views)
+  }
+
+  /// Records a dispatch of `pipeline` with one invocation for each texel of the six faces of a
+  /// cubemap `size` texels wide, using the descriptors in `writes` as set 0.
+  fn dispatch_cube(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    pipeline: &Arc<ComputePipeline>,
+    writes: [WriteDescriptorSet; 2],
+    size: u32,
+  ) {
+    let descriptor_set = PersistentDescriptorSet::new(
+      pipeline.layout().set_layouts().first().unwrap().clone(),
+      writes,
+    )
+    .unwrap();
+
+    let workgroups = size.div_ceil(WORKGROUP_SIZE);
+    builder
+      .bind_pipeline_compute(pipeline.clone())
+      .bind_descriptor_sets(
+        PipelineBindPoint::Compute,
+        pipeline.layout().clone(),
+        0,
+        descriptor_set,
+      )
+      .dispatch([workgroups, workgroups, 6])
+      .unwrap();
+  }
+}
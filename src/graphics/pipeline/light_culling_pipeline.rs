@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use log::warn;
+use vulkano::{
+  buffer::{BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer},
+  device::{Device, Queue},
+  pipeline::ComputePipeline,
+};
+
+use crate::{
+  config::{CLUSTER_GRID, MAX_LIGHTS_PER_CLUSTER},
+  graphics::shaders,
+};
+
+/// Number of clusters each light culling workgroup processes, matching `local_size_x` in
+/// `light_culling.comp`.
+const CLUSTERS_PER_WORKGROUP: u32 = 64;
+
+/// The compute pipeline which sorts a scene's lights into clusters of the view frustum, so
+/// that the mesh pipeline only shades the lights which can reach each fragment.
+pub struct MdrLightCullingPipeline {
+  pub compute_pipeline: Arc<ComputePipeline>,
+  /// The number of lights in each cluster followed by the indices of those lights, written
+  /// by the compute pipeline and read by the mesh pipeline.
+  pub cluster_buffer: Arc<DeviceLocalBuffer<[u32]>>,
+  /// The most lights found reaching a single cluster once any cluster has overflowed, written
+  /// by the compute pipeline and read back to warn about it.
+  pub overflow_buffer: Arc<CpuAccessibleBuffer<u32>>,
+  overflow_reported: bool,
+}
+
+impl MdrLightCullingPipeline {
+  pub fn new(logical_device: &Arc<Device>, queue: &Arc<Queue>) -> Self {
+    // Load shader module to GPU
+    let compute_shader = shaders::load_light_culling_shader(logical_device);
+
+    let compute_pipeline = ComputePipeline::new(
+      logical_device.clone(),
+      compute_shader.entry_point("main").unwrap(),
+      &(),
+      None,
+      |_| {},
+    )
+    .unwrap();
+
+    // One count followed by the maximum number of light indices for every cluster
+    let cluster_buffer_length = Self::cluster_count() as u64 * (1 + MAX_LIGHTS_PER_CLUSTER as u64);
+    let cluster_buffer = DeviceLocalBuffer::<[u32]>::array(
+      logical_device.clone(),
+      cluster_buffer_length,
+      BufferUsage::storage_buffer(),
+      [queue.family()],
+    )
+    .unwrap();
+    let overflow_buffer = CpuAccessibleBuffer::from_data(
+      logical_device.clone(),
+      BufferUsage::storage_buffer(),
+      false,
+      0,
+    )
+    .unwrap();
+
+    Self {
+      compute_pipeline,
+      cluster_buffer,
+      overflow_buffer,
+      overflow_reported: false,
+    }
+  }
+
+  /// Returns the number of workgroups to dispatch to process every cluster.
+  pub fn workgroup_count(&self) -> [u32; 3] {
+    let cluster_count = Self::cluster_count();
+    let workgroups = cluster_count.div_ceil(CLUSTERS_PER_WORKGROUP);
+
+    [workgroups, 1, 1]
+  }
+
+  /// Logs a warning the first time a cluster is found to be reached by more than
+  /// `MAX_LIGHTS_PER_CLUSTER` lights, as the extra lights are ignored. The check is skipped
+  /// while the GPU is still using the overflow buffer.
+  pub fn report_overflow(&mut self) {
+    if self.overflow_reported {
+      return;
+    }
+
+    let max_cluster_lights = match self.overflow_buffer.read() {
+      Ok(max_cluster_lights) => *max_cluster_lights,
+      Err(_) => return,
+    };
+    if max_cluster_lights > MAX_LIGHTS_PER_CLUSTER {
+      warn!(
+        "{} lights reach a single light cluster, only the first {} are shaded. Give lights a \
+         smaller range",
+        max_cluster_lights, MAX_LIGHTS_PER_CLUSTER
+      );
+      self.overflow_reported = true;
+    }
+  }
+
+  fn cluster_count() -> u32 {
+    CLUSTER_GRID.iter().product()
+  }
+}
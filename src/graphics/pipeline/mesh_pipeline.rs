@@ -4,8 +4,10 @@ use vulkano::{
   device::Device,
   pipeline::{
     graphics::{
+      color_blend::ColorBlendState,
       depth_stencil::DepthStencilState,
       input_assembly::InputAssemblyState,
+      multisample::MultisampleState,
       rasterization::{CullMode, FrontFace, RasterizationState},
       vertex_input::BuffersDefinition,
       viewport::{Viewport, ViewportState},
@@ -24,7 +26,8 @@ use crate::{
   resources::vertex::MdrVertex_tan,
 };
 
-/// The pipeline used for mesh drawing.
+/// The pipeline used for mesh drawing, either shading objects as they are drawn or writing their
+/// surface properties into a G-buffer for deferred shading.
 pub struct MdrMeshPipeline {
   logical_device: Arc<Device>,
   pub graphics_pipeline: Arc<GraphicsPipeline>,
@@ -57,6 +60,31 @@ impl MdrMeshPipeline {
     }
   }
 
+  /// Creates a pipeline which draws objects into the G-buffer attachments of `render_pass`,
+  /// created with `MdrRenderPass::new_gbuffer()`.
+  pub fn new_gbuffer(
+    logical_device: &Arc<Device>,
+    render_pass: &MdrRenderPass,
+    viewport: &Viewport,
+  ) -> Self {
+    // Load shader modules to GPU
+    let (vertex_shader, fragment_shader) = shaders::load_gbuffer_shaders(logical_device);
+
+    Self {
+      logical_device: logical_device.clone(),
+
+      graphics_pipeline: Self::create_graphics_pipeline(
+        logical_device,
+        render_pass,
+        &vertex_shader,
+        &fragment_shader,
+        viewport,
+      ),
+      vertex_shader,
+      fragment_shader,
+    }
+  }
+
   pub fn recreate(&mut self, render_pass: &MdrRenderPass, viewport: &Viewport) {
     self.graphics_pipeline = Self::create_graphics_pipeline(
       &self.logical_device,
@@ -105,6 +133,15 @@ impl MdrMeshPipeline {
       )
       // Link the fragment shader
       .fragment_shader(fragment_shader.entry_point("main").unwrap(), ())
+      // Take as many samples per pixel as the render pass's attachments have
+      .multisample_state(MultisampleState {
+        rasterization_samples: render_pass.samples(),
+        ..MultisampleState::new()
+      })
+      // Write every color attachment, of which G-buffer passes have several
+      .color_blend_state(ColorBlendState::new(
+        render_pass.get_subpass().num_color_attachments(),
+      ))
       // Settings for depth testing (to ensure correct ordering of fragments)
       .depth_stencil_state(DepthStencilState::simple_depth_test())
       // The render pass to use for this pipeline
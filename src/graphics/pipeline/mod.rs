@@ -1,5 +1,17 @@
+pub mod deferred_lighting_pipeline;
+pub mod environment_pipeline;
+pub mod light_culling_pipeline;
+#[allow(dead_code)]
 pub mod light_pipeline;
 pub mod mesh_pipeline;
+pub mod post_pipeline;
+pub mod shadow_pipeline;
+pub mod skybox_pipeline;
 
-pub use light_pipeline::MdrLightPipeline;
+pub use deferred_lighting_pipeline::MdrDeferredLightingPipeline;
+pub use environment_pipeline::MdrEnvironmentPipeline;
+pub use light_culling_pipeline::MdrLightCullingPipeline;
 pub use mesh_pipeline::MdrMeshPipeline;
+pub use post_pipeline::MdrPostPipeline;
+pub use shadow_pipeline::MdrShadowPipeline;
+pub use skybox_pipeline::MdrSkyboxPipeline;
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use vulkano::{
+  device::Device,
+  pipeline::{
+    graphics::{
+      input_assembly::InputAssemblyState, rasterization::RasterizationState,
+      vertex_input::BuffersDefinition, viewport::ViewportState, GraphicsPipelineCreationError,
+    },
+    GraphicsPipeline,
+  },
+  shader::ShaderModule,
+};
+
+use crate::graphics::{render_pass::MdrRenderPass, shaders};
+
+/// A pipeline drawing one triangle over the whole of its color attachment, shading every pixel
+/// with a post-processing fragment shader. The viewport is dynamic, so the same pipeline can
+/// draw into attachments of any size.
+pub struct MdrPostPipeline {
+  pub graphics_pipeline: Arc<GraphicsPipeline>,
+}
+
+impl MdrPostPipeline {
+  /// Creates a pipeline which replaces the contents of `render_pass`'s attachment with the
+  /// output of `fragment_shader`, which must have a `main` entry point. Fails if the shader's
+  /// interface doesn't match the vertex shader or render pass.
+  pub fn new(
+    logical_device: &Arc<Device>,
+    render_pass: &MdrRenderPass,
+    fragment_shader: &Arc<ShaderModule>,
+  ) -> Result<Self, GraphicsPipelineCreationError> {
+    let vertex_shader = shaders::load_fullscreen_shader(logical_device);
+
+    let graphics_pipeline = GraphicsPipeline::start()
+      // The triangle's vertices are generated in the vertex shader, so there are no buffers
+      .vertex_input_state(BuffersDefinition::new())
+      // Link the vertex shader
+      .vertex_shader(vertex_shader.entry_point("main").unwrap(), ())
+      // Input assembly settings (we use the defaults)
+      .input_assembly_state(InputAssemblyState::new())
+      // The viewport is set to the attachment's size before each draw
+      .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+      // Fixed functions of the rasterizer, the triangle is never culled
+      .rasterization_state(RasterizationState::new())
+      // Link the fragment shader
+      .fragment_shader(fragment_shader.entry_point("main").unwrap(), ())
+      // The render pass to use for this pipeline
+      .render_pass(render_pass.get_subpass())
+      // Build the pipeline object
+      .build(logical_device.clone())?;
+
+    Ok(Self { graphics_pipeline })
+  }
+}
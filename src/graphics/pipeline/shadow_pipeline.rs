@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use vulkano::{
+  device::Device,
+  pipeline::{
+    graphics::{
+      depth_stencil::DepthStencilState,
+      input_assembly::InputAssemblyState,
+      rasterization::{CullMode, DepthBias, DepthBiasState, FrontFace, RasterizationState},
+      vertex_input::BuffersDefinition,
+      viewport::{Viewport, ViewportState},
+    },
+    GraphicsPipeline, StateMode,
+  },
+  shader::ShaderModule,
+};
+
+use crate::{
+  config::SHADOW_MAP_SIZE,
+  graphics::{render_pass::MdrRenderPass, resources::MdrVertex_pos, shaders},
+};
+
+/// The pipeline used for drawing meshes into shadow maps. It uses the same vertex positions and
+/// depth testing as `MdrMeshPipeline`, without any shading.
+pub struct MdrShadowPipeline {
+  pub graphics_pipeline: Arc<GraphicsPipeline>,
+}
+
+impl MdrShadowPipeline {
+  pub fn new(logical_device: &Arc<Device>, render_pass: &MdrRenderPass) -> Self {
+    // Load shader modules to GPU
+    let (vertex_shader, fragment_shader) = shaders::load_shadow_shaders(logical_device);
+
+    Self {
+      graphics_pipeline: Self::create_graphics_pipeline(
+        logical_device,
+        render_pass,
+        &vertex_shader,
+        &fragment_shader,
+      ),
+    }
+  }
+
+  fn create_graphics_pipeline(
+    logical_device: &Arc<Device>,
+    render_pass: &MdrRenderPass,
+    vertex_shader: &Arc<ShaderModule>,
+    fragment_shader: &Arc<ShaderModule>,
+  ) -> Arc<GraphicsPipeline> {
+    // Shadow maps are always the same size, regardless of the render target
+    let viewport = Viewport {
+      origin: [0.0, 0.0],
+      dimensions: [SHADOW_MAP_SIZE as f32, SHADOW_MAP_SIZE as f32],
+      depth_range: 0.0..1.0,
+    };
+
+    GraphicsPipeline::start()
+      // Only positions are needed to find depth, looks like [f32; 3] in memory
+      .vertex_input_state(BuffersDefinition::new().vertex::<MdrVertex_pos>())
+      // Link the vertex shader
+      .vertex_shader(vertex_shader.entry_point("main").unwrap(), ())
+      // Input assembly settings (we use the defaults)
+      .input_assembly_state(InputAssemblyState::new())
+      // Define the viewport to be used for this render
+      .viewport_state(ViewportState::viewport_fixed_scissor_irrelevant([viewport]))
+      // Fixed functions of the rasterizer
+      .rasterization_state(RasterizationState {
+        // Push depths away from the light, more on sloped surfaces, to reduce shadow acne
+        depth_bias: Some(DepthBiasState {
+          enable_dynamic: false,
+          bias: StateMode::Fixed(DepthBias {
+            constant_factor: 1.25,
+            clamp: 0.0,
+            slope_factor: 1.75,
+          }),
+        }),
+        // Clockwise-winding faces will be treated as front-facing
+        // We cull back-facing faces to avoid unnecessary fragment threads
+        ..RasterizationState::new()
+          .front_face(FrontFace::Clockwise)
+          .cull_mode(CullMode::Back)
+      })
+      // Link the fragment shader
+      .fragment_shader(fragment_shader.entry_point("main").unwrap(), ())
+      // Settings for depth testing (to ensure correct ordering of fragments)
+      .depth_stencil_state(DepthStencilState::simple_depth_test())
+      // The render pass to use for this pipeline
+      .render_pass(render_pass.get_subpass())
+      // Build and unwrap to get the pipeline object
+      .build(logical_device.clone())
+      .unwrap()
+  }
+}
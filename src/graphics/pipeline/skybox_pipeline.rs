@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use vulkano::{
+  device::Device,
+  pipeline::{
+    graphics::{
+      color_blend::{ColorBlendState, ColorComponents},
+      depth_stencil::{CompareOp, DepthState, DepthStencilState},
+      input_assembly::InputAssemblyState,
+      multisample::MultisampleState,
+      rasterization::RasterizationState,
+      vertex_input::BuffersDefinition,
+      viewport::{Viewport, ViewportState},
+    },
+    GraphicsPipeline, StateMode,
+  },
+  shader::ShaderModule,
+};
+
+use crate::graphics::{render_pass::MdrRenderPass, shaders};
+
+/// The pipeline used for drawing the skybox behind the scene's objects. It draws one triangle
+/// covering the screen at the far plane, after the objects, so only uncovered pixels are shaded.
+pub struct MdrSkyboxPipeline {
+  logical_device: Arc<Device>,
+  pub graphics_pipeline: Arc<GraphicsPipeline>,
+
+  pub vertex_shader: Arc<ShaderModule>,
+  pub fragment_shader: Arc<ShaderModule>,
+}
+
+impl MdrSkyboxPipeline {
+  pub fn new(
+    logical_device: &Arc<Device>,
+    render_pass: &MdrRenderPass,
+    viewport: &Viewport,
+  ) -> Self {
+    // Load shader modules to GPU
+    let (vertex_shader, fragment_shader) = shaders::load_skybox_shaders(logical_device);
+
+    Self {
+      logical_device: logical_device.clone(),
+
+      graphics_pipeline: Self::create_graphics_pipeline(
+        logical_device,
+        render_pass,
+        &vertex_shader,
+        &fragment_shader,
+        viewport,
+      ),
+      vertex_shader,
+      fragment_shader,
+    }
+  }
+
+  pub fn recreate(&mut self, render_pass: &MdrRenderPass, viewport: &Viewport) {
+    self.graphics_pipeline = Self::create_graphics_pipeline(
+      &self.logical_device,
+      render_pass,
+      &self.vertex_shader,
+      &self.fragment_shader,
+      viewport,
+    );
+  }
+
+  fn create_graphics_pipeline(
+    logical_device: &Arc<Device>,
+    render_pass: &MdrRenderPass,
+    vertex_shader: &Arc<ShaderModule>,
+    fragment_shader: &Arc<ShaderModule>,
+    viewport: &Viewport,
+  ) -> Arc<GraphicsPipeline> {
+    // Only the first color attachment is drawn into, leaving any G-buffer attachments as the
+    // objects left them
+    let mut color_blend_state =
+      ColorBlendState::new(render_pass.get_subpass().num_color_attachments());
+    for attachment in color_blend_state.attachments.iter_mut().skip(1) {
+      attachment.color_write_mask = ColorComponents::none();
+    }
+
+    GraphicsPipeline::start()
+      // The triangle's vertices are generated in the vertex shader, so there are no buffers
+      .vertex_input_state(BuffersDefinition::new())
+      // Link the vertex shader
+      .vertex_shader(vertex_shader.entry_point("main").unwrap(), ())
+      // Input assembly settings (we use the defaults)
+      .input_assembly_state(InputAssemblyState::new())
+      // Define the viewport to be used for this render
+      .viewport_state(ViewportState::viewport_fixed_scissor_irrelevant([
+        viewport.clone()
+      ]))
+      // Fixed functions of the rasterizer, the triangle is never culled
+      .rasterization_state(RasterizationState::new())
+      // Link the fragment shader
+      .fragment_shader(fragment_shader.entry_point("main").unwrap(), ())
+      // Take as many samples per pixel as the render pass's attachments have
+      .multisample_state(MultisampleState {
+        rasterization_samples: render_pass.samples(),
+        ..MultisampleState::new()
+      })
+      // Write the skybox's color into the HDR image only
+      .color_blend_state(color_blend_state)
+      // Pass only where the depth buffer still holds the far plane, without writing to it
+      .depth_stencil_state(DepthStencilState {
+        depth: Some(DepthState {
+          enable_dynamic: false,
+          write_enable: StateMode::Fixed(false),
+          compare_op: StateMode::Fixed(CompareOp::LessOrEqual),
+        }),
+        ..DepthStencilState::disabled()
+      })
+      // The render pass to use for this pipeline
+      .render_pass(render_pass.get_subpass())
+      // Build and unwrap to get the pipeline object
+      .build(logical_device.clone())
+      .unwrap()
+  }
+}
@@ -0,0 +1,680 @@
+use std::{f32::consts::PI, sync::Arc};
+
+use vulkano::{
+  buffer::{BufferUsage, CpuAccessibleBuffer},
+  command_buffer::{
+    AutoCommandBufferBuilder, PrimaryAutoCommandBuffer, RenderPassBeginInfo, SubpassContents,
+  },
+  descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
+  device::Device,
+  format::{Format, NumericType},
+  image::{view::ImageView, AttachmentImage, ImageAccess, ImageUsage, SampleCount},
+  pipeline::{graphics::viewport::Viewport, GraphicsPipeline, Pipeline, PipelineBindPoint},
+  render_pass::{Framebuffer, FramebufferCreateInfo},
+  sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+  shader::ShaderModule,
+};
+
+use nalgebra::Matrix4;
+
+use crate::{
+  config::{BLOOM_MIP_LEVELS, SSAO_KERNEL_SIZE},
+  scene::{MdrAntialiasing, MdrScene, MdrTonemapper},
+};
+
+use super::{
+  hdr::{MdrHdrTarget, HDR_FORMAT},
+  pipeline::MdrPostPipeline,
+  render_pass::MdrRenderPass,
+  resources::MdrResourceManager,
+  shaders::{
+    self, bloom_downsample_shader::ty::MdrBloomDownsamplePushConstants,
+    bloom_upsample_shader::ty::MdrBloomUpsamplePushConstants,
+    fxaa_shader::ty::MdrFxaaPushConstants, ssao_blur_shader::ty::MdrSsaoBlurPushConstants,
+    ssao_shader::ty::MdrSsaoData, tonemap_fragment_shader::ty::MdrTonemapPushConstants,
+  },
+};
+
+/// Format of the tone mapped image anti-aliasing reads from, which is sRGB so that it keeps
+/// precision in dark colors.
+const LDR_FORMAT: Format = Format::R8G8B8A8_SRGB;
+
+/// Format of the image ambient occlusion is estimated into before it is blurred.
+const OCCLUSION_FORMAT: Format = Format::R8_UNORM;
+
+/// Values of `MdrTonemapPushConstants::tonemapper`, matching the `TONEMAPPER_*` definitions in
+/// `tonemap.frag`.
+const TONEMAPPER_ACES: u32 = 0;
+const TONEMAPPER_REINHARD: u32 = 1;
+const TONEMAPPER_AGX: u32 = 2;
+
+/// An image drawn into by a post-processing pass and sampled by later ones.
+struct MdrPostImage {
+  framebuffer: Arc<Framebuffer>,
+  image_view: Arc<ImageView<AttachmentImage>>,
+}
+
+impl MdrPostImage {
+  fn new(
+    logical_device: &Arc<Device>,
+    render_pass: &MdrRenderPass,
+    dimensions: [u32; 2],
+    format: Format,
+  ) -> Self {
+    let image = AttachmentImage::with_usage(
+      logical_device.clone(),
+      dimensions,
+      format,
+      ImageUsage {
+        sampled: true,
+        ..ImageUsage::color_attachment()
+      },
+    )
+    .unwrap();
+
+    Self::from_view(render_pass, ImageView::new_default(image).unwrap())
+  }
+
+  fn from_view(render_pass: &MdrRenderPass, image_view: Arc<ImageView<AttachmentImage>>) -> Self {
+    let framebuffer = Framebuffer::new(
+      render_pass.get_pass(),
+      FramebufferCreateInfo {
+        attachments: vec![image_view.clone()],
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    Self {
+      framebuffer,
+      image_view,
+    }
+  }
+}
+
+/// The images the post-processing passes draw into, which are the size of the render target.
+struct MdrPostImages {
+  /// The scene's HDR image, and a second image of the same size which custom effects alternate
+  /// between drawing into.
+  hdr_images: [MdrPostImage; 2],
+  /// Each bloom level, half the size of the one before, and the first of them half the size of
+  /// the scene.
+  bloom_downsampled: Vec<MdrPostImage>,
+  /// Each bloom level but the last with the glow of the smaller levels added.
+  bloom_upsampled: Vec<MdrPostImage>,
+  /// The tone mapped frame, read by anti-aliasing.
+  ldr_image: MdrPostImage,
+  /// The unblurred ambient occlusion of each pixel.
+  occlusion_image: MdrPostImage,
+  /// The scene's depth buffer and, on the deferred render path, its normals, read by ambient
+  /// occlusion.
+  depth_view: Arc<ImageView<AttachmentImage>>,
+  normal_view: Option<Arc<ImageView<AttachmentImage>>>,
+}
+
+impl MdrPostImages {
+  /// Creates the images for `hdr_target`, sharing its color image.
+  fn new(
+    logical_device: &Arc<Device>,
+    hdr_render_pass: &MdrRenderPass,
+    ldr_render_pass: &MdrRenderPass,
+    occlusion_render_pass: &MdrRenderPass,
+    hdr_target: &MdrHdrTarget,
+  ) -> Self {
+    let dimensions = hdr_target.framebuffer.extent();
+
+    let hdr_images = [
+      MdrPostImage::from_view(hdr_render_pass, hdr_target.image_view.clone()),
+      MdrPostImage::new(logical_device, hdr_render_pass, dimensions, HDR_FORMAT),
+    ];
+
+    // Stop halving before the smallest side would shrink below one texel
+    let smallest_side = dimensions[0].min(dimensions[1]).max(2);
+    let level_count = BLOOM_MIP_LEVELS
+      .min(31 - smallest_side.leading_zeros())
+      .max(1);
+    let bloom_downsampled: Vec<MdrPostImage> = (1..=level_count)
+      .map(|level| {
+        let level_dimensions = dimensions.map(|side| (side >> level).max(1));
+        MdrPostImage::new(
+          logical_device,
+          hdr_render_pass,
+          level_dimensions,
+          HDR_FORMAT,
+        )
+      })
+      .collect();
+    // The smallest level has nothing to add to it, so it isn't upsampled
+    let bloom_upsampled = bloom_downsampled[..bloom_downsampled.len() - 1]
+      .iter()
+      .map(|level| {
+        MdrPostImage::new(
+          logical_device,
+          hdr_render_pass,
+          level.framebuffer.extent(),
+          HDR_FORMAT,
+        )
+      })
+      .collect();
+
+    let ldr_image = MdrPostImage::new(logical_device, ldr_render_pass, dimensions, LDR_FORMAT);
+    let occlusion_image = MdrPostImage::new(
+      logical_device,
+      occlusion_render_pass,
+      dimensions,
+      OCCLUSION_FORMAT,
+    );
+
+    Self {
+      hdr_images,
+      bloom_downsampled,
+      bloom_upsampled,
+      ldr_image,
+      occlusion_image,
+      depth_view: hdr_target.depth_view.clone(),
+      normal_view: hdr_target
+        .gbuffer
+        .as_ref()
+        .map(|gbuffer| gbuffer.normal_view.clone()),
+    }
+  }
+}
+
+/// The passes which turn the scene's HDR image into the finished frame: ambient occlusion,
+/// custom effects, bloom, tone mapping with color grading and the vignette, and anti-aliasing.
+pub(crate) struct MdrPostProcessor {
+  logical_device: Arc<Device>,
+  /// Draws into HDR images, for custom effects and bloom.
+  hdr_render_pass: MdrRenderPass,
+  /// Draws into the tone mapped image read by anti-aliasing.
+  ldr_render_pass: MdrRenderPass,
+  /// Draws into the render target.
+  target_render_pass: MdrRenderPass,
+  /// Draws into the ambient occlusion image.
+  occlusion_render_pass: MdrRenderPass,
+  /// Whether the shaders writing into the render target must gamma encode their output.
+  encode_srgb: bool,
+
+  tonemap_pipeline: MdrPostPipeline,
+  tonemap_ldr_pipeline: MdrPostPipeline,
+  bloom_downsample_pipeline: MdrPostPipeline,
+  bloom_upsample_pipeline: MdrPostPipeline,
+  fxaa_pipeline: MdrPostPipeline,
+  ssao_pipeline: MdrPostPipeline,
+  ssao_blur_pipeline: MdrPostPipeline,
+  /// Offsets around each pixel sampled by ambient occlusion.
+  ssao_kernel: [[f32; 4]; SSAO_KERNEL_SIZE],
+
+  /// Reads images one texel per pixel, for passes whose output is the size of their input.
+  nearest_sampler: Arc<Sampler>,
+  /// Blends between texels, for resizing images and reading lookup tables.
+  linear_sampler: Arc<Sampler>,
+
+  images: MdrPostImages,
+}
+
+impl MdrPostProcessor {
+  /// Creates the post-processing passes for drawing `hdr_target` into a render target of
+  /// `target_format`.
+  pub fn new(
+    logical_device: &Arc<Device>,
+    hdr_target: &MdrHdrTarget,
+    target_format: Format,
+  ) -> Self {
+    let hdr_render_pass = MdrRenderPass::new_color_only(logical_device, HDR_FORMAT);
+    let ldr_render_pass = MdrRenderPass::new_color_only(logical_device, LDR_FORMAT);
+    let target_render_pass = MdrRenderPass::new_color_only(logical_device, target_format);
+    let occlusion_render_pass = MdrRenderPass::new_color_only(logical_device, OCCLUSION_FORMAT);
+
+    // Load shader modules to GPU
+    let (tonemap_shader, bloom_downsample_shader, bloom_upsample_shader, fxaa_shader) =
+      shaders::load_post_processing_shaders(logical_device);
+    let multisampled_depth = hdr_target.depth_view.image().samples() != SampleCount::Sample1;
+    let (ssao_shader, ssao_blur_shader) =
+      shaders::load_ssao_shaders(logical_device, multisampled_depth);
+    let create_pipeline = |render_pass: &MdrRenderPass, shader: &Arc<ShaderModule>| {
+      MdrPostPipeline::new(logical_device, render_pass, shader).unwrap()
+    };
+
+    let nearest_sampler = Sampler::new(
+      logical_device.clone(),
+      SamplerCreateInfo {
+        mag_filter: Filter::Nearest,
+        min_filter: Filter::Nearest,
+        address_mode: [SamplerAddressMode::ClampToEdge; 3],
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let linear_sampler = Sampler::new(
+      logical_device.clone(),
+      SamplerCreateInfo {
+        mag_filter: Filter::Linear,
+        min_filter: Filter::Linear,
+        address_mode: [SamplerAddressMode::ClampToEdge; 3],
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    let images = MdrPostImages::new(
+      logical_device,
+      &hdr_render_pass,
+      &ldr_render_pass,
+      &occlusion_render_pass,
+      hdr_target,
+    );
+
+    Self {
+      logical_device: logical_device.clone(),
+      encode_srgb: target_format.type_color() != Some(NumericType::SRGB),
+
+      tonemap_pipeline: create_pipeline(&target_render_pass, &tonemap_shader),
+      tonemap_ldr_pipeline: create_pipeline(&ldr_render_pass, &tonemap_shader),
+      bloom_downsample_pipeline: create_pipeline(&hdr_render_pass, &bloom_downsample_shader),
+      bloom_upsample_pipeline: create_pipeline(&hdr_render_pass, &bloom_upsample_shader),
+      fxaa_pipeline: create_pipeline(&target_render_pass, &fxaa_shader),
+      ssao_pipeline: create_pipeline(&occlusion_render_pass, &ssao_shader),
+      ssao_blur_pipeline: create_pipeline(&hdr_render_pass, &ssao_blur_shader),
+      ssao_kernel: create_ssao_kernel(),
+
+      hdr_render_pass,
+      ldr_render_pass,
+      target_render_pass,
+      occlusion_render_pass,
+
+      nearest_sampler,
+      linear_sampler,
+
+      images,
+    }
+  }
+
+  /// Returns the render pass the framebuffers of the render target must be created for.
+  pub fn target_render_pass(&self) -> &MdrRenderPass {
+    &self.target_render_pass
+  }
+
+  /// Recreates the images the passes draw into to match a resized `hdr_target`.
+  pub fn resize(&mut self, hdr_target: &MdrHdrTarget) {
+    self.images = MdrPostImages::new(
+      &self.logical_device,
+      &self.hdr_render_pass,
+      &self.ldr_render_pass,
+      &self.occlusion_render_pass,
+      hdr_target,
+    );
+  }
+
+  /// Records every post-processing pass enabled in `scene`, reading the scene's HDR image and
+  /// finishing in `target_framebuffer`.
+  pub fn record(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    scene: &MdrScene,
+    resource_manager: &MdrResourceManager,
+    target_framebuffer: &Arc<Framebuffer>,
+  ) {
+    let settings = &scene.post_processing;
+
+    // Darken the creases of the scene, from the first HDR image into the second
+    let mut current_image = 0;
+    if settings.ambient_occlusion.enabled {
+      self.record_ambient_occlusion(builder, scene);
+      current_image = 1;
+    }
+
+    // Custom effects alternate between the two HDR images
+    for custom_effect in settings
+      .custom_effects
+      .iter()
+      .filter(|effect| effect.enabled)
+    {
+      let effect_handle = resource_manager.get_post_effect_handle(&custom_effect.effect);
+      let pipeline = &effect_handle.graphics_pipeline;
+      let source = &self.images.hdr_images[current_image];
+      let destination = &self.images.hdr_images[1 - current_image];
+
+      Self::begin_pass(
+        builder,
+        pipeline,
+        &destination.framebuffer,
+        [WriteDescriptorSet::image_view_sampler(
+          0,
+          source.image_view.clone(),
+          self.nearest_sampler.clone(),
+        )],
+      );
+      if effect_handle.takes_parameters {
+        builder.push_constants(pipeline.layout().clone(), 0, custom_effect.parameters);
+      }
+      Self::end_pass(builder);
+
+      current_image = 1 - current_image;
+    }
+    let scene_image = &self.images.hdr_images[current_image].image_view;
+
+    // Blur the bright parts of the scene at decreasing sizes, then add them back up, so the
+    // glow spreads far without a large filter
+    let bloom_image = if settings.bloom.enabled {
+      self.record_bloom(builder, scene, scene_image)
+    } else {
+      // The bloom intensity is 0, but something must still be bound
+      scene_image
+    };
+
+    // Tone map into the target, or into the image anti-aliasing reads from
+    let fxaa_enabled = settings.antialiasing == MdrAntialiasing::Fxaa;
+    let (tonemap_pipeline, tonemap_framebuffer) = if fxaa_enabled {
+      (
+        &self.tonemap_ldr_pipeline,
+        &self.images.ldr_image.framebuffer,
+      )
+    } else {
+      (&self.tonemap_pipeline, target_framebuffer)
+    };
+    let color_lut = resource_manager.get_color_lut_handle(settings.color_grading.lut.as_ref());
+    let push_constants = MdrTonemapPushConstants {
+      exposure: scene.camera.exposure.exp2(),
+      tonemapper: match scene.camera.tonemapper {
+        MdrTonemapper::Aces => TONEMAPPER_ACES,
+        MdrTonemapper::Reinhard => TONEMAPPER_REINHARD,
+        MdrTonemapper::AgX => TONEMAPPER_AGX,
+      },
+      encode_srgb: (self.encode_srgb && !fxaa_enabled) as u32,
+      bloom_intensity: if settings.bloom.enabled {
+        settings.bloom.intensity
+      } else {
+        0.0
+      },
+      lut_intensity: if settings.color_grading.lut.is_some() {
+        settings.color_grading.intensity
+      } else {
+        0.0
+      },
+      vignette_intensity: if settings.vignette.enabled {
+        settings.vignette.intensity
+      } else {
+        0.0
+      },
+      vignette_radius: settings.vignette.radius,
+      // The vignette is undefined if it fades in over no distance at all
+      vignette_smoothness: settings.vignette.smoothness.max(0.0001),
+    };
+    Self::begin_pass(
+      builder,
+      &tonemap_pipeline.graphics_pipeline,
+      tonemap_framebuffer,
+      [
+        WriteDescriptorSet::image_view_sampler(
+          0,
+          scene_image.clone(),
+          self.nearest_sampler.clone(),
+        ),
+        WriteDescriptorSet::image_view_sampler(1, bloom_image.clone(), self.linear_sampler.clone()),
+        WriteDescriptorSet::image_view_sampler(
+          2,
+          color_lut.image_view.clone(),
+          self.linear_sampler.clone(),
+        ),
+      ],
+    );
+    builder.push_constants(
+      tonemap_pipeline.graphics_pipeline.layout().clone(),
+      0,
+      push_constants,
+    );
+    Self::end_pass(builder);
+
+    // Smooth the tone mapped frame's edges into the target
+    if fxaa_enabled {
+      let fxaa_pipeline = &self.fxaa_pipeline.graphics_pipeline;
+      Self::begin_pass(
+        builder,
+        fxaa_pipeline,
+        target_framebuffer,
+        [WriteDescriptorSet::image_view_sampler(
+          0,
+          self.images.ldr_image.image_view.clone(),
+          self.linear_sampler.clone(),
+        )],
+      );
+      builder.push_constants(
+        fxaa_pipeline.layout().clone(),
+        0,
+        MdrFxaaPushConstants {
+          encode_srgb: self.encode_srgb as u32,
+        },
+      );
+      Self::end_pass(builder);
+    }
+  }
+
+  /// Records the ambient occlusion passes, which estimate how much of the ambient light reaches
+  /// each pixel from the depth buffer, then blur it over the noise in its sampling and multiply
+  /// the first HDR image by it into the second.
+  fn record_ambient_occlusion(
+    &self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    scene: &MdrScene,
+  ) {
+    let ambient_occlusion = &scene.post_processing.ambient_occlusion;
+    let scene_image = &self.images.hdr_images[0].image_view;
+
+    let projection = scene.camera.get_projection_matrix();
+    let ssao_buffer = CpuAccessibleBuffer::from_data(
+      self.logical_device.clone(),
+      BufferUsage::uniform_buffer(),
+      false,
+      MdrSsaoData {
+        projection: projection.into(),
+        inverse_projection: projection
+          .try_inverse()
+          .unwrap_or_else(Matrix4::identity)
+          .into(),
+        view: scene.camera.get_view_matrix().into(),
+        kernel: self.ssao_kernel,
+        radius: ambient_occlusion.radius,
+        bias: ambient_occlusion.bias,
+        use_normals: self.images.normal_view.is_some() as u32,
+      },
+    )
+    .unwrap();
+
+    // Without a G-buffer the normals are found from the depths, but something must still be
+    // bound
+    let normal_view = match &self.images.normal_view {
+      Some(normal_view) => normal_view,
+      None => scene_image,
+    };
+    let ssao_pipeline = &self.ssao_pipeline.graphics_pipeline;
+    Self::begin_pass(
+      builder,
+      ssao_pipeline,
+      &self.images.occlusion_image.framebuffer,
+      [
+        WriteDescriptorSet::image_view_sampler(
+          0,
+          self.images.depth_view.clone(),
+          self.nearest_sampler.clone(),
+        ),
+        WriteDescriptorSet::image_view_sampler(
+          1,
+          normal_view.clone(),
+          self.nearest_sampler.clone(),
+        ),
+        WriteDescriptorSet::buffer(2, ssao_buffer),
+      ],
+    );
+    Self::end_pass(builder);
+
+    let blur_pipeline = &self.ssao_blur_pipeline.graphics_pipeline;
+    Self::begin_pass(
+      builder,
+      blur_pipeline,
+      &self.images.hdr_images[1].framebuffer,
+      [
+        WriteDescriptorSet::image_view_sampler(
+          0,
+          scene_image.clone(),
+          self.nearest_sampler.clone(),
+        ),
+        WriteDescriptorSet::image_view_sampler(
+          1,
+          self.images.occlusion_image.image_view.clone(),
+          self.nearest_sampler.clone(),
+        ),
+      ],
+    );
+    builder.push_constants(
+      blur_pipeline.layout().clone(),
+      0,
+      MdrSsaoBlurPushConstants {
+        intensity: ambient_occlusion.intensity.clamp(0.0, 1.0),
+      },
+    );
+    Self::end_pass(builder);
+  }
+
+  /// Records the bloom passes for `scene_image`, returning the image holding the finished glow.
+  fn record_bloom<'a>(
+    &'a self,
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    scene: &MdrScene,
+    scene_image: &'a Arc<ImageView<AttachmentImage>>,
+  ) -> &'a Arc<ImageView<AttachmentImage>> {
+    let bloom = &scene.post_processing.bloom;
+
+    // Halve the scene repeatedly, keeping only its bright parts at the first step
+    let downsample_pipeline = &self.bloom_downsample_pipeline.graphics_pipeline;
+    for (level, destination) in self.images.bloom_downsampled.iter().enumerate() {
+      let source = match level {
+        0 => scene_image,
+        _ => &self.images.bloom_downsampled[level - 1].image_view,
+      };
+
+      Self::begin_pass(
+        builder,
+        downsample_pipeline,
+        &destination.framebuffer,
+        [WriteDescriptorSet::image_view_sampler(
+          0,
+          source.clone(),
+          self.linear_sampler.clone(),
+        )],
+      );
+      builder.push_constants(
+        downsample_pipeline.layout().clone(),
+        0,
+        MdrBloomDownsamplePushConstants {
+          threshold: bloom.threshold,
+          knee: bloom.knee,
+          apply_threshold: (level == 0) as u32,
+        },
+      );
+      Self::end_pass(builder);
+    }
+
+    // Double the smallest level back up, adding each larger level's glow on the way
+    let upsample_pipeline = &self.bloom_upsample_pipeline.graphics_pipeline;
+    let mut source = &self.images.bloom_downsampled.last().unwrap().image_view;
+    for (level, destination) in self.images.bloom_upsampled.iter().enumerate().rev() {
+      Self::begin_pass(
+        builder,
+        upsample_pipeline,
+        &destination.framebuffer,
+        [
+          WriteDescriptorSet::image_view_sampler(0, source.clone(), self.linear_sampler.clone()),
+          WriteDescriptorSet::image_view_sampler(
+            1,
+            self.images.bloom_downsampled[level].image_view.clone(),
+            self.linear_sampler.clone(),
+          ),
+        ],
+      );
+      builder.push_constants(
+        upsample_pipeline.layout().clone(),
+        0,
+        MdrBloomUpsamplePushConstants {
+          radius: bloom.radius,
+        },
+      );
+      Self::end_pass(builder);
+
+      source = &destination.image_view;
+    }
+
+    source
+  }
+
+  /// Begins a render pass drawing over the whole of `framebuffer` with `pipeline`, binding the
+  /// images in `descriptor_writes`. Push constants are left to the caller.
+  fn begin_pass(
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    pipeline: &Arc<GraphicsPipeline>,
+    framebuffer: &Arc<Framebuffer>,
+    descriptor_writes: impl IntoIterator<Item = WriteDescriptorSet>,
+  ) {
+    let descriptor_set = PersistentDescriptorSet::new(
+      pipeline.layout().set_layouts().first().unwrap().clone(),
+      descriptor_writes,
+    )
+    .unwrap();
+
+    let extent = framebuffer.extent();
+    let viewport = Viewport {
+      origin: [0.0, 0.0],
+      dimensions: [extent[0] as f32, extent[1] as f32],
+      depth_range: 0.0..1.0,
+    };
+
+    // Every pixel is overwritten, so the attachment isn't cleared
+    let mut begin_render_pass_info = RenderPassBeginInfo::framebuffer(framebuffer.clone());
+    begin_render_pass_info.clear_values = vec![None];
+    builder
+      .begin_render_pass(begin_render_pass_info, SubpassContents::Inline)
+      .unwrap()
+      .set_viewport(0, [viewport])
+      .bind_pipeline_graphics(pipeline.clone())
+      .bind_descriptor_sets(
+        PipelineBindPoint::Graphics,
+        pipeline.layout().clone(),
+        0,
+        descriptor_set,
+      );
+  }
+
+  /// Draws the full-screen triangle and ends the render pass started by `begin_pass()`.
+  fn end_pass(builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>) {
+    builder.draw(3, 1, 0, 0).unwrap().end_render_pass().unwrap();
+  }
+}
+
+/// Returns the offsets sampled around each pixel by ambient occlusion, within the unit
+/// hemisphere around +z. Their directions spiral out from the pole towards the horizon, and
+/// their lengths are spread so that more of them lie close to the surface, where occluders
+/// matter most.
+fn create_ssao_kernel() -> [[f32; 4]; SSAO_KERNEL_SIZE] {
+  let golden_angle = PI * (3.0 - 5.0_f32.sqrt());
+
+  let mut kernel = [[0.0; 4]; SSAO_KERNEL_SIZE];
+  for (index, offset) in kernel.iter_mut().enumerate() {
+    // Directions stay a little above the horizon, where samples would lie in the surface
+    let cos_theta = 1.0 - 0.9 * (index as f32 + 0.5) / SSAO_KERNEL_SIZE as f32;
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    let phi = index as f32 * golden_angle;
+
+    // Lengths are shuffled so they don't grow along with the angle from the pole
+    let length_fraction = ((index * 7) % SSAO_KERNEL_SIZE) as f32 / SSAO_KERNEL_SIZE as f32;
+    let length = 0.1 + 0.9 * length_fraction * length_fraction;
+
+    *offset = [
+      sin_theta * phi.cos() * length,
+      sin_theta * phi.sin() * length,
+      cos_theta * length,
+      0.0,
+    ];
+  }
+
+  kernel
+}
@@ -3,27 +3,38 @@ use std::sync::Arc;
 use vulkano::{
   device::Device,
   format::Format,
+  image::SampleCount,
   render_pass::{RenderPass, Subpass},
 };
 
+use super::hdr::{GBUFFER_ALBEDO_FORMAT, GBUFFER_MATERIAL_FORMAT, GBUFFER_NORMAL_FORMAT};
+
 pub struct MdrRenderPass {
   render_pass: Arc<RenderPass>,
 }
 
 impl MdrRenderPass {
-  pub fn new(logical_device: &Arc<Device>, swapchain_image_format: Format) -> Self {
+  /// Creates a render pass which draws into a color attachment of `color_format`, testing
+  /// against a depth buffer which is kept for screen-space effects. With more than one sample
+  /// per pixel, the scene is drawn into multisampled color and depth attachments and the color
+  /// is resolved into a third, single-sampled attachment of `color_format`.
+  pub fn new(logical_device: &Arc<Device>, color_format: Format, samples: SampleCount) -> Self {
+    if samples != SampleCount::Sample1 {
+      return Self::new_multisampled(logical_device, color_format, samples);
+    }
+
     let render_pass = vulkano::single_pass_renderpass!(
       logical_device.clone(),
       attachments: {
         color: {
           load: Clear,
           store: Store,
-          format: swapchain_image_format,
+          format: color_format,
           samples: 1,
         },
         depth: {
           load: Clear,
-          store: DontCare,
+          store: Store,
           format: Format::D16_UNORM,
           samples: 1,
         }
@@ -38,6 +49,163 @@ impl MdrRenderPass {
     Self { render_pass }
   }
 
+  fn new_multisampled(
+    logical_device: &Arc<Device>,
+    color_format: Format,
+    samples: SampleCount,
+  ) -> Self {
+    let render_pass = vulkano::single_pass_renderpass!(
+      logical_device.clone(),
+      attachments: {
+        // Only the resolved colors and the depth are kept once the pass ends
+        multisampled_color: {
+          load: Clear,
+          store: DontCare,
+          format: color_format,
+          samples: samples as u32,
+        },
+        depth: {
+          load: Clear,
+          store: Store,
+          format: Format::D16_UNORM,
+          samples: samples as u32,
+        },
+        color: {
+          load: DontCare,
+          store: Store,
+          format: color_format,
+          samples: 1,
+        }
+      },
+      pass: {
+        color: [multisampled_color],
+        depth_stencil: {depth},
+        resolve: [color]
+      }
+    )
+    .unwrap();
+
+    Self { render_pass }
+  }
+
+  /// Creates a render pass which draws objects into a G-buffer for deferred shading, testing
+  /// against a depth buffer which is kept for the lighting pass and screen-space effects. The
+  /// first color attachment, of `color_format`, receives the clear color, emitted light, and
+  /// skybox, and the rest hold the surface properties the lighting pass reads.
+  pub fn new_gbuffer(logical_device: &Arc<Device>, color_format: Format) -> Self {
+    let render_pass = vulkano::single_pass_renderpass!(
+      logical_device.clone(),
+      attachments: {
+        color: {
+          load: Clear,
+          store: Store,
+          format: color_format,
+          samples: 1,
+        },
+        // Only pixels covered by an object are read, and those are always written
+        albedo: {
+          load: DontCare,
+          store: Store,
+          format: GBUFFER_ALBEDO_FORMAT,
+          samples: 1,
+        },
+        normal: {
+          load: DontCare,
+          store: Store,
+          format: GBUFFER_NORMAL_FORMAT,
+          samples: 1,
+        },
+        material: {
+          load: DontCare,
+          store: Store,
+          format: GBUFFER_MATERIAL_FORMAT,
+          samples: 1,
+        },
+        depth: {
+          load: Clear,
+          store: Store,
+          format: Format::D16_UNORM,
+          samples: 1,
+        }
+      },
+      pass: {
+        color: [color, albedo, normal, material],
+        depth_stencil: {depth}
+      }
+    )
+    .unwrap();
+
+    Self { render_pass }
+  }
+
+  /// Creates a render pass which only writes a depth attachment, for rendering shadow maps.
+  pub fn new_depth_only(logical_device: &Arc<Device>, depth_format: Format) -> Self {
+    let render_pass = vulkano::single_pass_renderpass!(
+      logical_device.clone(),
+      attachments: {
+        depth: {
+          load: Clear,
+          store: Store,
+          format: depth_format,
+          samples: 1,
+        }
+      },
+      pass: {
+        color: [],
+        depth_stencil: {depth}
+      }
+    )
+    .unwrap();
+
+    Self { render_pass }
+  }
+
+  /// Creates a render pass which overwrites every pixel of a color attachment of `color_format`
+  /// without a depth buffer, for passes which cover the whole screen.
+  pub fn new_color_only(logical_device: &Arc<Device>, color_format: Format) -> Self {
+    let render_pass = vulkano::single_pass_renderpass!(
+      logical_device.clone(),
+      attachments: {
+        color: {
+          load: DontCare,
+          store: Store,
+          format: color_format,
+          samples: 1,
+        }
+      },
+      pass: {
+        color: [color],
+        depth_stencil: {}
+      }
+    )
+    .unwrap();
+
+    Self { render_pass }
+  }
+
+  /// Creates a render pass which draws onto the existing contents of a color attachment of
+  /// `color_format` without a depth buffer, for passes which add to an image.
+  pub fn new_color_blended(logical_device: &Arc<Device>, color_format: Format) -> Self {
+    let render_pass = vulkano::single_pass_renderpass!(
+      logical_device.clone(),
+      attachments: {
+        color: {
+          load: Load,
+          store: Store,
+          format: color_format,
+          samples: 1,
+        }
+      },
+      pass: {
+        color: [color],
+        depth_stencil: {}
+      }
+    )
+    .unwrap();
+
+    Self { render_pass }
+  }
+
   pub fn get_pass(&self) -> Arc<RenderPass> {
     self.render_pass.clone()
   }
@@ -45,4 +213,12 @@ impl MdrRenderPass {
   pub fn get_subpass(&self) -> Subpass {
     Subpass::from(self.render_pass.clone(), 0).unwrap()
   }
+
+  /// Returns the number of samples per pixel the render pass draws with.
+  pub fn samples(&self) -> SampleCount {
+    self
+      .get_subpass()
+      .num_samples()
+      .unwrap_or(SampleCount::Sample1)
+  }
 }
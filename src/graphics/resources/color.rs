@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum MdrColor {
   RGB(MdrRgb),
   RGBA(MdrRgba),
@@ -15,7 +18,7 @@ impl From<[f32; 4]> for MdrColor {
   }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct MdrRgb {
   pub r: f32,
   pub g: f32,
@@ -80,7 +83,7 @@ impl From<[f32; 3]> for MdrRgb {
   }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct MdrRgba {
   pub r: f32,
   pub g: f32,
@@ -106,6 +109,7 @@ impl From<[f32; 4]> for MdrRgba {
 }
 
 /// How the GPU will interpret a color value
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum MdrColorType {
   /// A Standardized RGBA color, with pre-gamma RGB values and an alpha channel.
   SRGBA,
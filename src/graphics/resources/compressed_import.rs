@@ -0,0 +1,395 @@
+use std::{fs, path::Path};
+
+use ddsfile::{D3DFormat, Dds, DxgiFormat};
+use image::{DynamicImage, RgbaImage};
+use ktx2::{Format as Ktx2Format, Reader as Ktx2Reader};
+use log::{error, warn};
+use vulkano::{
+  format::{CompressionType, Format, NumericType},
+  DeviceSize,
+};
+
+/// The block-compressed formats which can be loaded from KTX2 files, paired with the Vulkan
+/// format they're uploaded as.
+const KTX2_FORMATS: [(Ktx2Format, Format); 32] = [
+  (Ktx2Format::BC1_RGB_UNORM_BLOCK, Format::BC1_RGB_UNORM_BLOCK),
+  (Ktx2Format::BC1_RGB_SRGB_BLOCK, Format::BC1_RGB_SRGB_BLOCK),
+  (
+    Ktx2Format::BC1_RGBA_UNORM_BLOCK,
+    Format::BC1_RGBA_UNORM_BLOCK,
+  ),
+  (Ktx2Format::BC1_RGBA_SRGB_BLOCK, Format::BC1_RGBA_SRGB_BLOCK),
+  (Ktx2Format::BC2_UNORM_BLOCK, Format::BC2_UNORM_BLOCK),
+  (Ktx2Format::BC2_SRGB_BLOCK, Format::BC2_SRGB_BLOCK),
+  (Ktx2Format::BC3_UNORM_BLOCK, Format::BC3_UNORM_BLOCK),
+  (Ktx2Format::BC3_SRGB_BLOCK, Format::BC3_SRGB_BLOCK),
+  (Ktx2Format::BC4_UNORM_BLOCK, Format::BC4_UNORM_BLOCK),
+  (Ktx2Format::BC5_UNORM_BLOCK, Format::BC5_UNORM_BLOCK),
+  (Ktx2Format::BC6H_UFLOAT_BLOCK, Format::BC6H_UFLOAT_BLOCK),
+  (Ktx2Format::BC6H_SFLOAT_BLOCK, Format::BC6H_SFLOAT_BLOCK),
+  (Ktx2Format::BC7_UNORM_BLOCK, Format::BC7_UNORM_BLOCK),
+  (Ktx2Format::BC7_SRGB_BLOCK, Format::BC7_SRGB_BLOCK),
+  (
+    Ktx2Format::ETC2_R8G8B8_UNORM_BLOCK,
+    Format::ETC2_R8G8B8_UNORM_BLOCK,
+  ),
+  (
+    Ktx2Format::ETC2_R8G8B8_SRGB_BLOCK,
+    Format::ETC2_R8G8B8_SRGB_BLOCK,
+  ),
+  (
+    Ktx2Format::ETC2_R8G8B8A1_UNORM_BLOCK,
+    Format::ETC2_R8G8B8A1_UNORM_BLOCK,
+  ),
+  (
+    Ktx2Format::ETC2_R8G8B8A1_SRGB_BLOCK,
+    Format::ETC2_R8G8B8A1_SRGB_BLOCK,
+  ),
+  (
+    Ktx2Format::ETC2_R8G8B8A8_UNORM_BLOCK,
+    Format::ETC2_R8G8B8A8_UNORM_BLOCK,
+  ),
+  (
+    Ktx2Format::ETC2_R8G8B8A8_SRGB_BLOCK,
+    Format::ETC2_R8G8B8A8_SRGB_BLOCK,
+  ),
+  (
+    Ktx2Format::ASTC_4x4_UNORM_BLOCK,
+    Format::ASTC_4x4_UNORM_BLOCK,
+  ),
+  (Ktx2Format::ASTC_4x4_SRGB_BLOCK, Format::ASTC_4x4_SRGB_BLOCK),
+  (
+    Ktx2Format::ASTC_5x5_UNORM_BLOCK,
+    Format::ASTC_5x5_UNORM_BLOCK,
+  ),
+  (Ktx2Format::ASTC_5x5_SRGB_BLOCK, Format::ASTC_5x5_SRGB_BLOCK),
+  (
+    Ktx2Format::ASTC_6x6_UNORM_BLOCK,
+    Format::ASTC_6x6_UNORM_BLOCK,
+  ),
+  (Ktx2Format::ASTC_6x6_SRGB_BLOCK, Format::ASTC_6x6_SRGB_BLOCK),
+  (
+    Ktx2Format::ASTC_8x8_UNORM_BLOCK,
+    Format::ASTC_8x8_UNORM_BLOCK,
+  ),
+  (Ktx2Format::ASTC_8x8_SRGB_BLOCK, Format::ASTC_8x8_SRGB_BLOCK),
+  (
+    Ktx2Format::ASTC_10x10_UNORM_BLOCK,
+    Format::ASTC_10x10_UNORM_BLOCK,
+  ),
+  (
+    Ktx2Format::ASTC_10x10_SRGB_BLOCK,
+    Format::ASTC_10x10_SRGB_BLOCK,
+  ),
+  (
+    Ktx2Format::ASTC_12x12_UNORM_BLOCK,
+    Format::ASTC_12x12_UNORM_BLOCK,
+  ),
+  (
+    Ktx2Format::ASTC_12x12_SRGB_BLOCK,
+    Format::ASTC_12x12_SRGB_BLOCK,
+  ),
+];
+
+/// A block-compressed image and its pre-built mip levels, which can be copied to the GPU
+/// without being decoded.
+pub struct MdrCompressedImage {
+  pub format: Format,
+  pub width: u32,
+  pub height: u32,
+  /// The data of each mip level, starting with the full size image.
+  pub levels: Vec<Vec<u8>>,
+}
+
+impl MdrCompressedImage {
+  /// Returns whether the image's color data is in standard (gamma-corrected) color space.
+  pub fn is_srgb(&self) -> bool {
+    self.format.type_color() == Some(NumericType::SRGB)
+  }
+
+  /// Decodes the full size image on the CPU, for devices which can't sample its format. HDR
+  /// formats are clamped to 8 bits per channel. Returns `None` if the format can't be decoded.
+  pub fn decompress(&self) -> Option<DynamicImage> {
+    let width = self.width as usize;
+    let height = self.height as usize;
+    let data = self.levels[0].as_slice();
+
+    // The decoder writes each pixel as a u32 holding BGRA bytes
+    let mut pixels = vec![0u32; width * height];
+    let result = match self.format {
+      Format::BC1_RGB_UNORM_BLOCK
+      | Format::BC1_RGB_SRGB_BLOCK
+      | Format::BC1_RGBA_UNORM_BLOCK
+      | Format::BC1_RGBA_SRGB_BLOCK => {
+        texture2ddecoder::decode_bc1(data, width, height, &mut pixels)
+      }
+      Format::BC2_UNORM_BLOCK | Format::BC2_SRGB_BLOCK => {
+        decode_bc2(data, width, height, &mut pixels)
+      }
+      Format::BC3_UNORM_BLOCK | Format::BC3_SRGB_BLOCK => {
+        texture2ddecoder::decode_bc3(data, width, height, &mut pixels)
+      }
+      Format::BC4_UNORM_BLOCK => texture2ddecoder::decode_bc4(data, width, height, &mut pixels),
+      Format::BC5_UNORM_BLOCK => texture2ddecoder::decode_bc5(data, width, height, &mut pixels),
+      Format::BC6H_UFLOAT_BLOCK => {
+        texture2ddecoder::decode_bc6(data, width, height, &mut pixels, false)
+      }
+      Format::BC6H_SFLOAT_BLOCK => {
+        texture2ddecoder::decode_bc6(data, width, height, &mut pixels, true)
+      }
+      Format::BC7_UNORM_BLOCK | Format::BC7_SRGB_BLOCK => {
+        texture2ddecoder::decode_bc7(data, width, height, &mut pixels)
+      }
+      Format::ETC2_R8G8B8_UNORM_BLOCK | Format::ETC2_R8G8B8_SRGB_BLOCK => {
+        texture2ddecoder::decode_etc2_rgb(data, width, height, &mut pixels)
+      }
+      Format::ETC2_R8G8B8A1_UNORM_BLOCK | Format::ETC2_R8G8B8A1_SRGB_BLOCK => {
+        texture2ddecoder::decode_etc2_rgba1(data, width, height, &mut pixels)
+      }
+      Format::ETC2_R8G8B8A8_UNORM_BLOCK | Format::ETC2_R8G8B8A8_SRGB_BLOCK => {
+        texture2ddecoder::decode_etc2_rgba8(data, width, height, &mut pixels)
+      }
+      format if format.compression() == Some(CompressionType::ASTC_LDR) => {
+        let [block_width, block_height, _] = format.block_extent();
+        texture2ddecoder::decode_astc(
+          data,
+          width,
+          height,
+          block_width as usize,
+          block_height as usize,
+          &mut pixels,
+        )
+      }
+      _ => return None,
+    };
+
+    if let Err(e) = result {
+      error!(
+        "Failed to decompress {:?} image, reason: {}",
+        self.format, e
+      );
+      return None;
+    }
+
+    let rgba = pixels
+      .iter()
+      .flat_map(|pixel| {
+        let [b, g, r, a] = pixel.to_le_bytes();
+        [r, g, b, a]
+      })
+      .collect();
+    RgbaImage::from_raw(self.width, self.height, rgba).map(DynamicImage::ImageRgba8)
+  }
+}
+
+/// Decodes a BC2 image into `pixels`, in the decoder's BGRA layout. The decoder has no BC2
+/// support, but each block is only a BC1 color block following 4-bit alpha values.
+fn decode_bc2(
+  data: &[u8],
+  width: usize,
+  height: usize,
+  pixels: &mut [u32],
+) -> Result<(), &'static str> {
+  let blocks_x = width.div_ceil(4);
+  let blocks_y = height.div_ceil(4);
+  if data.len() < blocks_x * blocks_y * 16 {
+    return Err("Not enough data to decode image");
+  }
+
+  let mut block_pixels = [0u32; 16];
+  for (index, block) in data.chunks_exact(16).take(blocks_x * blocks_y).enumerate() {
+    texture2ddecoder::decode_bc1_block(&block[8..], &mut block_pixels);
+
+    let (block_x, block_y) = (index % blocks_x * 4, index / blocks_x * 4);
+    for (i, pixel) in block_pixels.iter().enumerate() {
+      let (x, y) = (block_x + i % 4, block_y + i / 4);
+      if x >= width || y >= height {
+        continue;
+      }
+
+      // Each byte holds the alpha of two pixels, the first in its low bits
+      let alpha = (block[i / 2] >> (i % 2 * 4)) & 0xf;
+      pixels[y * width + x] = (pixel & 0x00ff_ffff) | ((alpha as u32 * 17) << 24);
+    }
+  }
+
+  Ok(())
+}
+
+/// Returns whether the file at `path` is a KTX2 or DDS file, which hold block-compressed
+/// images.
+pub fn is_compressed_file(path: &Path) -> bool {
+  let extension = path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .map(|extension| extension.to_ascii_lowercase());
+
+  matches!(extension.as_deref(), Some("ktx2") | Some("dds"))
+}
+
+/// Loads a block-compressed 2D image and its mip levels from the .ktx2 or .dds file at `path`.
+pub fn open_compressed_image(path: &Path) -> Option<MdrCompressedImage> {
+  // Load data from disk
+  let bytes = match fs::read(path) {
+    Ok(bytes) => bytes,
+    Err(e) => {
+      error!(
+        "Failed to load compressed image file: {}, reason: {}",
+        path.display(),
+        e
+      );
+      return None;
+    }
+  };
+
+  let is_ktx2 = path
+    .extension()
+    .is_some_and(|extension| extension.eq_ignore_ascii_case("ktx2"));
+  let image = if is_ktx2 {
+    parse_ktx2(&bytes)
+  } else {
+    parse_dds(&bytes)
+  };
+
+  if image.is_none() {
+    error!("Unsupported compressed image file: {}", path.display());
+  }
+  image
+}
+
+/// Reads the image in the contents of a KTX2 file.
+fn parse_ktx2(bytes: &[u8]) -> Option<MdrCompressedImage> {
+  let reader = match Ktx2Reader::new(bytes) {
+    Ok(reader) => reader,
+    Err(e) => {
+      warn!("Failed to parse KTX2 file, reason: {:?}", e);
+      return None;
+    }
+  };
+
+  let header = reader.header();
+  if header.supercompression_scheme.is_some() {
+    warn!("Supercompressed KTX2 files are not supported");
+    return None;
+  }
+  if header.pixel_depth > 1 || header.layer_count > 1 || header.face_count > 1 {
+    warn!("Only 2D KTX2 images are supported");
+    return None;
+  }
+
+  let format = KTX2_FORMATS
+    .iter()
+    .find(|(ktx2_format, _)| Some(*ktx2_format) == header.format)
+    .map(|(_, format)| *format);
+  let format = match format {
+    Some(format) => format,
+    None => {
+      warn!("Unsupported KTX2 format: {:?}", header.format);
+      return None;
+    }
+  };
+
+  Some(MdrCompressedImage {
+    format,
+    width: header.pixel_width,
+    height: header.pixel_height.max(1),
+    levels: reader.levels().map(|level| level.to_vec()).collect(),
+  })
+}
+
+/// Reads the image in the contents of a DDS file, which can hold BC1 to BC7 images.
+fn parse_dds(bytes: &[u8]) -> Option<MdrCompressedImage> {
+  let dds = match Dds::read(bytes) {
+    Ok(dds) => dds,
+    Err(e) => {
+      warn!("Failed to parse DDS file, reason: {}", e);
+      return None;
+    }
+  };
+
+  if dds.get_depth() > 1 || dds.get_num_array_layers() > 1 {
+    warn!("Only 2D DDS images are supported");
+    return None;
+  }
+
+  // Newer files give a DXGI format, older files a D3D one
+  let format = match dds.get_dxgi_format() {
+    Some(dxgi_format) => dxgi_to_format(dxgi_format),
+    None => dds.get_d3d_format().and_then(d3d_to_format),
+  };
+  let format = match format {
+    Some(format) => format,
+    None => {
+      warn!("Unsupported DDS format");
+      return None;
+    }
+  };
+
+  // Mip levels are stored one after another, each a whole number of blocks
+  let width = dds.get_width();
+  let height = dds.get_height();
+  let mut levels = Vec::new();
+  let mut offset = 0;
+  for level in 0..dds.get_num_mipmap_levels() {
+    let size = level_size(format, mip_extent(width, level), mip_extent(height, level)) as usize;
+    match dds.data.get(offset..offset + size) {
+      Some(data) => levels.push(data.to_vec()),
+      None => {
+        warn!("DDS file is missing mip level {}", level);
+        return None;
+      }
+    }
+    offset += size;
+  }
+
+  Some(MdrCompressedImage {
+    format,
+    width,
+    height,
+    levels,
+  })
+}
+
+/// Returns the Vulkan format matching a block-compressed DXGI format.
+fn dxgi_to_format(dxgi_format: DxgiFormat) -> Option<Format> {
+  match dxgi_format {
+    DxgiFormat::BC1_UNorm => Some(Format::BC1_RGBA_UNORM_BLOCK),
+    DxgiFormat::BC1_UNorm_sRGB => Some(Format::BC1_RGBA_SRGB_BLOCK),
+    DxgiFormat::BC2_UNorm => Some(Format::BC2_UNORM_BLOCK),
+    DxgiFormat::BC2_UNorm_sRGB => Some(Format::BC2_SRGB_BLOCK),
+    DxgiFormat::BC3_UNorm => Some(Format::BC3_UNORM_BLOCK),
+    DxgiFormat::BC3_UNorm_sRGB => Some(Format::BC3_SRGB_BLOCK),
+    DxgiFormat::BC4_UNorm => Some(Format::BC4_UNORM_BLOCK),
+    DxgiFormat::BC5_UNorm => Some(Format::BC5_UNORM_BLOCK),
+    DxgiFormat::BC6H_UF16 => Some(Format::BC6H_UFLOAT_BLOCK),
+    DxgiFormat::BC6H_SF16 => Some(Format::BC6H_SFLOAT_BLOCK),
+    DxgiFormat::BC7_UNorm => Some(Format::BC7_UNORM_BLOCK),
+    DxgiFormat::BC7_UNorm_sRGB => Some(Format::BC7_SRGB_BLOCK),
+    _ => None,
+  }
+}
+
+/// Returns the Vulkan format matching a block-compressed D3D format.
+fn d3d_to_format(d3d_format: D3DFormat) -> Option<Format> {
+  match d3d_format {
+    D3DFormat::DXT1 => Some(Format::BC1_RGBA_UNORM_BLOCK),
+    D3DFormat::DXT3 => Some(Format::BC2_UNORM_BLOCK),
+    D3DFormat::DXT5 => Some(Format::BC3_UNORM_BLOCK),
+    _ => None,
+  }
+}
+
+/// Returns the width or height of mip level `level` of an image which is `size` texels across.
+pub fn mip_extent(size: u32, level: u32) -> u32 {
+  (size >> level).max(1)
+}
+
+/// Returns the number of bytes in a `width` by `height` texel image of the block-compressed
+/// `format`. Partial blocks at the edges are stored whole.
+fn level_size(format: Format, width: u32, height: u32) -> DeviceSize {
+  let [block_width, block_height, _] = format.block_extent();
+  let blocks_wide = width.div_ceil(block_width);
+  let blocks_high = height.div_ceil(block_height);
+
+  (blocks_wide * blocks_high) as DeviceSize * format.block_size().unwrap()
+}
@@ -0,0 +1,87 @@
+use log::{error, warn};
+
+/// A 3D color lookup table read from a .cube file.
+pub(crate) struct MdrCubeLut {
+  /// Number of entries along each axis of the table.
+  pub size: u32,
+  /// The output color of each entry, with red changing fastest and blue slowest.
+  pub entries: Vec<[f32; 3]>,
+}
+
+impl MdrCubeLut {
+  /// Returns the table's entries as 8-bit RGBA texels.
+  pub fn to_rgba8(&self) -> Vec<u8> {
+    self
+      .entries
+      .iter()
+      .flat_map(|entry| {
+        let [r, g, b] = entry.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+        [r, g, b, 255]
+      })
+      .collect()
+  }
+}
+
+/// Parses the contents of a .cube file in the Adobe Cube LUT format. Returns `None` if the file
+/// doesn't hold a valid 3D table.
+pub(crate) fn parse_cube_lut(contents: &str) -> Option<MdrCubeLut> {
+  let mut size = None;
+  let mut entries = Vec::new();
+
+  for line in contents.lines() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+
+    let mut words = line.split_whitespace();
+    let first_word = words.next().unwrap();
+    match first_word {
+      "TITLE" => (),
+      "LUT_3D_SIZE" => {
+        size = words.next().and_then(|word| word.parse::<u32>().ok());
+        if size.is_none_or(|size| size < 2) {
+          error!("Invalid .cube table size: {}", line);
+          return None;
+        }
+      }
+      "LUT_1D_SIZE" => {
+        error!("1D .cube tables are not supported");
+        return None;
+      }
+      "DOMAIN_MIN" | "DOMAIN_MAX" => {
+        warn!("Ignoring .cube input range {}, 0 to 1 is assumed", line);
+      }
+      _ => {
+        // Every other line is an entry of three numbers
+        let entry: Vec<f32> = line
+          .split_whitespace()
+          .filter_map(|word| word.parse::<f32>().ok())
+          .collect();
+        if entry.len() != 3 {
+          error!("Invalid .cube line: {}", line);
+          return None;
+        }
+        entries.push([entry[0], entry[1], entry[2]]);
+      }
+    }
+  }
+
+  let size = match size {
+    Some(size) => size,
+    None => {
+      error!(".cube file has no LUT_3D_SIZE");
+      return None;
+    }
+  };
+  if entries.len() != (size * size * size) as usize {
+    error!(
+      ".cube file has {} entries rather than {}",
+      entries.len(),
+      size * size * size
+    );
+    return None;
+  }
+
+  Some(MdrCubeLut { size, entries })
+}
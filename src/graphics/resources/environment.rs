@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use vulkano::{
+  image::{view::ImageView, ImmutableImage},
+  sampler::Sampler,
+};
+
+/// An environment in the resource manager's environment library, which lights a scene from
+/// every direction when set as the scene's `environment`, or is drawn behind it when set as the
+/// scene's `skybox`.
+#[derive(Debug)]
+pub struct MdrEnvironment {
+  pub name: String,
+}
+
+/// The maps generated from an environment for image-based lighting, all of which are cubemaps.
+#[derive(Clone)]
+pub struct MdrGpuEnvironmentHandle {
+  /// The environment itself.
+  pub(crate) environment_map: Arc<ImageView<ImmutableImage>>,
+  /// The light reaching a surface facing each direction, divided by pi.
+  pub(crate) irradiance_map: Arc<ImageView<ImmutableImage>>,
+  /// The environment as reflected by surfaces of increasing roughness in each mip level.
+  pub(crate) prefiltered_map: Arc<ImageView<ImmutableImage>>,
+  pub(crate) sampler: Arc<Sampler>,
+}
@@ -0,0 +1,411 @@
+use gltf::{
+  image::Format as GltfImageFormat,
+  mesh::Mode,
+  texture::{MagFilter, MinFilter, WrappingMode},
+};
+use image::{DynamicImage, ImageBuffer};
+use log::{error, warn};
+use nalgebra::{Matrix4, Vector3};
+
+use crate::scene::transform::MdrTransform;
+
+use super::{
+  mesh::calculate_mesh_tangents, MdrFilter, MdrMeshData, MdrMipmapMode, MdrSamplerMode,
+  MdrVertex_norm, MdrVertex_pos, MdrVertex_uv,
+};
+
+/// The contents of a glTF file converted into engine types, ready to be uploaded to the GPU.
+pub struct MdrGltfData {
+  /// The primitives of each mesh in the file, indexed by glTF mesh index.
+  pub meshes: Vec<Vec<MdrGltfPrimitive>>,
+  /// The images in the file, indexed by glTF image index. Images with unsupported
+  /// pixel formats are `None`.
+  pub images: Vec<Option<DynamicImage>>,
+  /// The materials in the file, indexed by glTF material index.
+  pub materials: Vec<MdrGltfMaterial>,
+  /// The nodes of the file's default scene which draw a mesh, with parents before their
+  /// children.
+  pub nodes: Vec<MdrGltfNode>,
+}
+
+/// A single drawable part of a glTF mesh.
+pub struct MdrGltfPrimitive {
+  pub mesh_data: MdrMeshData,
+  /// Index of the primitive's material, `None` if it uses the default material.
+  pub material: Option<usize>,
+}
+
+/// The parts of a glTF metal/roughness material that the engine can use.
+pub struct MdrGltfMaterial {
+  pub base_color_texture: Option<MdrGltfTexture>,
+  pub base_color_factor: [f32; 4],
+  pub metallic_roughness_texture: Option<MdrGltfTexture>,
+  pub metallic_factor: f32,
+  pub roughness_factor: f32,
+  pub normal_texture: Option<MdrGltfTexture>,
+  pub occlusion_texture: Option<MdrGltfTexture>,
+  pub emissive_texture: Option<MdrGltfTexture>,
+  pub emissive_factor: [f32; 3],
+}
+
+/// A texture used by a glTF material: an image along with how it is sampled.
+#[derive(Clone, Copy)]
+pub struct MdrGltfTexture {
+  /// Index of the texture in the glTF file. Materials using the same texture share it.
+  pub index: usize,
+  /// Index of the texture's image.
+  pub image: usize,
+  pub sampler_mode: MdrSamplerMode,
+  pub filter: MdrFilter,
+  pub mipmap_mode: MdrMipmapMode,
+}
+
+impl Default for MdrGltfMaterial {
+  /// The glTF default material, used by primitives without a material.
+  fn default() -> Self {
+    Self {
+      base_color_texture: None,
+      base_color_factor: [1.0; 4],
+      metallic_roughness_texture: None,
+      metallic_factor: 1.0,
+      roughness_factor: 1.0,
+      normal_texture: None,
+      occlusion_texture: None,
+      emissive_texture: None,
+      emissive_factor: [0.0; 3],
+    }
+  }
+}
+
+/// A node in a glTF scene which draws a mesh.
+pub struct MdrGltfNode {
+  pub name: Option<String>,
+  /// Index of the mesh drawn by the node.
+  pub mesh: usize,
+  /// Index in `MdrGltfData::nodes` of the nearest ancestor which draws a mesh, `None` for
+  /// nodes without one.
+  pub parent: Option<usize>,
+  /// The node's transform relative to `parent`, combining the transforms of any ancestors in
+  /// between which don't draw a mesh.
+  pub transform: MdrTransform,
+}
+
+/// Loads a .gltf or .glb file from `path`, including any buffers and images it references.
+pub fn open_gltf(path: &str) -> Option<MdrGltfData> {
+  // Load data from disk
+  let (document, buffers, images) = match gltf::import(path) {
+    Ok(value) => value,
+    Err(e) => {
+      error!("Failed to load gltf file: {}, reason: {}", path, e);
+      return None;
+    }
+  };
+
+  // Convert mesh primitives
+  let meshes: Vec<Vec<MdrGltfPrimitive>> = document
+    .meshes()
+    .map(|mesh| {
+      mesh
+        .primitives()
+        .filter_map(|primitive| {
+          if primitive.mode() != Mode::Triangles {
+            warn!(
+              "Skipping primitive of mesh {} in {}, only triangles are supported",
+              mesh.index(),
+              path
+            );
+            return None;
+          }
+
+          let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+          let positions: Vec<[f32; 3]> = match reader.read_positions() {
+            Some(positions) => positions.collect(),
+            None => {
+              warn!(
+                "Skipping primitive of mesh {} in {}, it has no vertex positions",
+                mesh.index(),
+                path
+              );
+              return None;
+            }
+          };
+          let vertex_count = positions.len();
+
+          let indices: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..vertex_count as u32).collect(),
+          };
+          let normals: Vec<[f32; 3]> = match reader.read_normals() {
+            Some(normals) => normals.collect(),
+            None => calculate_mesh_normals(&positions, &indices),
+          };
+          let uvs: Vec<[f32; 2]> = match reader.read_tex_coords(0) {
+            Some(uvs) => uvs.into_f32().collect(),
+            None => vec![[0.0, 0.0]; vertex_count],
+          };
+
+          let positions: Vec<MdrVertex_pos> = positions
+            .into_iter()
+            .map(|a_position| MdrVertex_pos { a_position })
+            .collect();
+          let normals = normals
+            .into_iter()
+            .map(|a_normal| MdrVertex_norm { a_normal })
+            .collect();
+          let uvs: Vec<MdrVertex_uv> = uvs.into_iter().map(|a_uv| MdrVertex_uv { a_uv }).collect();
+          let tangents = calculate_mesh_tangents(&positions, &uvs, &indices);
+
+          Some(MdrGltfPrimitive {
+            mesh_data: MdrMeshData {
+              positions,
+              normals,
+              uvs,
+              tangents,
+
+              index_count: indices.len() as u32,
+              indices,
+            },
+            material: primitive.material().index(),
+          })
+        })
+        .collect()
+    })
+    .collect();
+
+  // Convert images
+  let images = images.into_iter().map(convert_image).collect();
+
+  // Convert materials
+  let materials = document
+    .materials()
+    .map(|material| {
+      let pbr = material.pbr_metallic_roughness();
+      MdrGltfMaterial {
+        base_color_texture: pbr
+          .base_color_texture()
+          .and_then(|info| convert_texture(&info.texture(), info.tex_coord(), path)),
+        base_color_factor: pbr.base_color_factor(),
+        metallic_roughness_texture: pbr
+          .metallic_roughness_texture()
+          .and_then(|info| convert_texture(&info.texture(), info.tex_coord(), path)),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        normal_texture: material
+          .normal_texture()
+          .and_then(|normal| convert_texture(&normal.texture(), normal.tex_coord(), path)),
+        occlusion_texture: material
+          .occlusion_texture()
+          .and_then(|occlusion| convert_texture(&occlusion.texture(), occlusion.tex_coord(), path)),
+        emissive_texture: material
+          .emissive_texture()
+          .and_then(|info| convert_texture(&info.texture(), info.tex_coord(), path)),
+        emissive_factor: material.emissive_factor(),
+      }
+    })
+    .collect();
+
+  // Collect the nodes drawing meshes, keeping the hierarchy between them
+  let mut nodes = Vec::<MdrGltfNode>::new();
+  match document
+    .default_scene()
+    .or_else(|| document.scenes().next())
+  {
+    Some(scene) => {
+      for node in scene.nodes() {
+        collect_nodes(&node, &meshes, None, &Matrix4::identity(), &mut nodes);
+      }
+    }
+    None => warn!("No scene found in gltf file: {}", path),
+  }
+
+  Some(MdrGltfData {
+    meshes,
+    images,
+    materials,
+    nodes,
+  })
+}
+
+/// Converts a texture used by a material, mapping its glTF sampler onto the engine's sampling
+/// options. Only the first set of texture coordinates is imported, so textures which read
+/// from another set are skipped.
+fn convert_texture(texture: &gltf::Texture, tex_coord: u32, path: &str) -> Option<MdrGltfTexture> {
+  if tex_coord != 0 {
+    warn!(
+      "Skipping texture {} in {}, it uses texture coordinate set {} but only set 0 is supported",
+      texture.index(),
+      path,
+      tex_coord
+    );
+    return None;
+  }
+
+  // The engine samples both axes the same way, so the u axis decides
+  let sampler = texture.sampler();
+  if sampler.wrap_s() != sampler.wrap_t() {
+    warn!(
+      "Texture {} in {} wraps its axes differently, using its u axis mode for both",
+      texture.index(),
+      path
+    );
+  }
+  let sampler_mode = match sampler.wrap_s() {
+    WrappingMode::Repeat => MdrSamplerMode::Repeat,
+    WrappingMode::ClampToEdge => MdrSamplerMode::ClampToEdge,
+    WrappingMode::MirroredRepeat => MdrSamplerMode::MirroredRepeat,
+  };
+
+  // Unspecified filters are left to the engine, which filters linearly. The magnification
+  // filter is used for minification too, and the minification filter picks the mipmap mode
+  let filter = match sampler.mag_filter() {
+    Some(MagFilter::Nearest) => MdrFilter::Nearest,
+    Some(MagFilter::Linear) | None => MdrFilter::Linear,
+  };
+  let mipmap_mode = match sampler.min_filter() {
+    Some(MinFilter::Nearest | MinFilter::Linear) => MdrMipmapMode::None,
+    Some(MinFilter::NearestMipmapNearest | MinFilter::LinearMipmapNearest) => {
+      MdrMipmapMode::Nearest
+    }
+    Some(MinFilter::NearestMipmapLinear | MinFilter::LinearMipmapLinear) | None => {
+      MdrMipmapMode::Linear
+    }
+  };
+
+  Some(MdrGltfTexture {
+    index: texture.index(),
+    image: texture.source().index(),
+    sampler_mode,
+    filter,
+    mipmap_mode,
+  })
+}
+
+/// Recursively collects `node` and its children into `nodes` if they draw one of `meshes`.
+/// `parent` is the index of the nearest collected ancestor, and `parent_matrix` holds the
+/// transforms of the ancestors below it, which are composed into the node's own.
+fn collect_nodes(
+  node: &gltf::Node,
+  meshes: &[Vec<MdrGltfPrimitive>],
+  parent: Option<usize>,
+  parent_matrix: &Matrix4<f32>,
+  nodes: &mut Vec<MdrGltfNode>,
+) {
+  let matrix = parent_matrix * Matrix4::from(node.transform().matrix());
+
+  // Nodes without anything to draw are folded into their children's transforms
+  match node.mesh().filter(|mesh| !meshes[mesh.index()].is_empty()) {
+    Some(mesh) => {
+      nodes.push(MdrGltfNode {
+        name: node.name().map(String::from),
+        mesh: mesh.index(),
+        parent,
+        transform: MdrTransform::from_matrix(&matrix),
+      });
+
+      let index = Some(nodes.len() - 1);
+      for child in node.children() {
+        collect_nodes(&child, meshes, index, &Matrix4::identity(), nodes);
+      }
+    }
+    None => {
+      for child in node.children() {
+        collect_nodes(&child, meshes, parent, &matrix, nodes);
+      }
+    }
+  }
+}
+
+/// Multiplies the colors of a base color map by `factor`, which is linear, as glTF does.
+/// The map's colors are sRGB encoded.
+pub(super) fn scale_base_color(image: &DynamicImage, factor: [f32; 4]) -> DynamicImage {
+  let mut scaled_image = image.to_rgba8();
+  for pixel in scaled_image.pixels_mut() {
+    for channel in 0..3 {
+      let linear = srgb_to_linear(pixel[channel] as f32 / 255.0) * factor[channel];
+      pixel[channel] = (linear_to_srgb(linear) * 255.0).round() as u8;
+    }
+    pixel[3] = (pixel[3] as f32 * factor[3]).round() as u8;
+  }
+
+  DynamicImage::ImageRgba8(scaled_image)
+}
+
+/// Encodes a linear base color factor as the sRGB color of a solid base color map.
+pub(super) fn encode_base_color(factor: [f32; 4]) -> [f32; 4] {
+  [
+    linear_to_srgb(factor[0]),
+    linear_to_srgb(factor[1]),
+    linear_to_srgb(factor[2]),
+    factor[3],
+  ]
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+  if value <= 0.04045 {
+    value / 12.92
+  } else {
+    ((value + 0.055) / 1.055).powf(2.4)
+  }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+  let value = value.clamp(0.0, 1.0);
+  if value <= 0.0031308 {
+    value * 12.92
+  } else {
+    1.055 * value.powf(1.0 / 2.4) - 0.055
+  }
+}
+
+/// Converts decoded glTF image data into a `DynamicImage`. Returns `None` for pixel formats
+/// other than 8-bit.
+fn convert_image(data: gltf::image::Data) -> Option<DynamicImage> {
+  let (width, height) = (data.width, data.height);
+  match data.format {
+    GltfImageFormat::R8 => {
+      ImageBuffer::from_raw(width, height, data.pixels).map(DynamicImage::ImageLuma8)
+    }
+    GltfImageFormat::R8G8 => {
+      ImageBuffer::from_raw(width, height, data.pixels).map(DynamicImage::ImageLumaA8)
+    }
+    GltfImageFormat::R8G8B8 => {
+      ImageBuffer::from_raw(width, height, data.pixels).map(DynamicImage::ImageRgb8)
+    }
+    GltfImageFormat::R8G8B8A8 => {
+      ImageBuffer::from_raw(width, height, data.pixels).map(DynamicImage::ImageRgba8)
+    }
+    format => {
+      warn!("Unsupported gltf image format: {:?}", format);
+      None
+    }
+  }
+}
+
+/// Calculates smooth vertex normals for meshes which don't provide them by averaging the
+/// normals of the triangles sharing each vertex.
+fn calculate_mesh_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+  let mut normals = vec![Vector3::<f32>::zeros(); positions.len()];
+
+  for triangle in indices.chunks_exact(3) {
+    let [v1, v2, v3] = [
+      Vector3::from(positions[triangle[0] as usize]),
+      Vector3::from(positions[triangle[1] as usize]),
+      Vector3::from(positions[triangle[2] as usize]),
+    ];
+    // Area-weighted face normal
+    let face_normal = (v2 - v1).cross(&(v3 - v1));
+    for &index in triangle {
+      normals[index as usize] += face_normal;
+    }
+  }
+
+  normals
+    .into_iter()
+    .map(|normal| {
+      normal
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(Vector3::y())
+        .into()
+    })
+    .collect()
+}
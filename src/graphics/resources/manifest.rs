@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+  color::{MdrColor, MdrColorType, MdrRgb},
+  texture::{MdrFilter, MdrMipmapMode, MdrSamplerMode},
+};
+
+/// A record of how each resource in an `MdrResourceManager` was created, from which the
+/// resources can be recreated, e.g. when loading a saved scene. Fields added since a manifest
+/// was saved take their defaults, so older manifests still load.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MdrResourceManifest {
+  /// Files imported with `load_gltf()` or `load_obj_models()`.
+  pub imports: Vec<MdrImportSource>,
+  pub meshes: Vec<MdrMeshSource>,
+  pub textures: Vec<MdrTextureSource>,
+  pub materials: Vec<MdrMaterialSource>,
+  pub environments: Vec<MdrEnvironmentSource>,
+  pub color_luts: Vec<MdrColorLutSource>,
+  pub post_effects: Vec<MdrPostEffectSource>,
+}
+
+impl MdrResourceManifest {
+  /// Forget every resource named `name` in the manifest.
+  pub(crate) fn remove(&mut self, name: &str) {
+    self.meshes.retain(|mesh| mesh.name != name);
+    self.textures.retain(|texture| texture.name != name);
+    self.materials.retain(|material| material.name != name);
+    self
+      .environments
+      .retain(|environment| environment.name != name);
+    self.color_luts.retain(|color_lut| color_lut.name != name);
+    self
+      .post_effects
+      .retain(|post_effect| post_effect.name != name);
+  }
+}
+
+/// A file whose contents were imported into the resource libraries with names prefixed
+/// by `name`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MdrImportSource {
+  pub name: String,
+  pub path: String,
+  pub format: MdrImportFormat,
+}
+
+/// File formats which can be imported as a whole.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum MdrImportFormat {
+  /// A .gltf or .glb file, loaded with `load_gltf()`.
+  Gltf,
+  /// An .obj file, loaded with `load_obj_models()`.
+  ObjModels,
+}
+
+/// A mesh loaded from an .obj file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MdrMeshSource {
+  pub name: String,
+  pub path: String,
+}
+
+/// A texture and the source of its image.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MdrTextureSource {
+  pub name: String,
+  pub image: MdrTextureImage,
+}
+
+/// Where a texture's image data comes from.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum MdrTextureImage {
+  /// An image file, loaded with `load_texture()`.
+  File {
+    path: String,
+    color_type: MdrColorType,
+    sampler_mode: MdrSamplerMode,
+    #[serde(default = "default_filter")]
+    filter: MdrFilter,
+    #[serde(default = "default_mipmap_mode")]
+    mipmap_mode: MdrMipmapMode,
+    #[serde(default)]
+    anisotropy: Option<u32>,
+  },
+  /// A single color, created with `create_solid_texture()`.
+  Solid(MdrColor),
+}
+
+/// A material created with `create_material()`, referring to its textures by name.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MdrMaterialSource {
+  pub name: String,
+  pub diffuse: String,
+  pub roughness: String,
+  pub normal: String,
+  #[serde(default)]
+  pub metallic: Option<String>,
+  #[serde(default)]
+  pub occlusion: Option<String>,
+  #[serde(default)]
+  pub emissive: Option<String>,
+
+  #[serde(default)]
+  pub metallic_factor: f32,
+  #[serde(default = "default_roughness_factor")]
+  pub roughness_factor: f32,
+  #[serde(default = "default_emissive_factor")]
+  pub emissive_factor: MdrRgb,
+}
+
+/// An environment and the source of its images.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MdrEnvironmentSource {
+  pub name: String,
+  pub image: MdrEnvironmentImage,
+}
+
+/// Where an environment's image data comes from.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum MdrEnvironmentImage {
+  /// An equirectangular image file, loaded with `load_environment()`.
+  Equirect { path: String },
+  /// Six image files, one for each face of a cubemap, loaded with `load_environment_cube()`.
+  Cube { faces: [String; 6] },
+}
+
+/// A color lookup table loaded from a .cube file with `load_color_lut()`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MdrColorLutSource {
+  pub name: String,
+  pub path: String,
+}
+
+/// A custom post-processing effect loaded from a SPIR-V file with `load_post_effect()`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MdrPostEffectSource {
+  pub name: String,
+  pub path: String,
+}
+
+// Defaults of fields missing from older manifests
+// ///////////////////////////////////////////////
+
+/// Textures were always filtered linearly before the filter could be chosen.
+fn default_filter() -> MdrFilter {
+  MdrFilter::Linear
+}
+
+/// Textures had no mip chain before the mipmap mode could be chosen.
+fn default_mipmap_mode() -> MdrMipmapMode {
+  MdrMipmapMode::None
+}
+
+fn default_roughness_factor() -> f32 {
+  1.0
+}
+
+fn default_emissive_factor() -> MdrRgb {
+  MdrRgb {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+  }
+}
@@ -11,13 +11,23 @@ pub struct MdrMaterial {
   pub name: String,
 }
 
+/// The inputs of a physically based metal/roughness material. Single channel maps are read
+/// from their red channel, and each map is multiplied by its factor, if it has one. Maps which
+/// aren't given are treated as white, so their factor alone is used.
 pub struct MdrMaterialCreateInfo {
+  /// Base color map, the diffuse color of non-metals and the specular color of metals.
   pub diffuse: MdrTexture,
   pub roughness: MdrTexture,
   pub normal: MdrTexture,
+  pub metallic: Option<MdrTexture>,
+  /// Ambient occlusion map, darkening the ambient light reaching creases.
+  pub occlusion: Option<MdrTexture>,
+  /// Emitted light map.
+  pub emissive: Option<MdrTexture>,
 
-  pub specular_color: MdrRgb,
-  pub shininess: f32,
+  pub metallic_factor: f32,
+  pub roughness_factor: f32,
+  pub emissive_factor: MdrRgb,
 }
 
 #[derive(Clone)]
@@ -26,4 +36,7 @@ pub struct MdrGpuMaterialHandle {
   pub(crate) diffuse_map: MdrGpuTextureHandle,
   pub(crate) roughness_map: MdrGpuTextureHandle,
   pub(crate) normal_map: MdrGpuTextureHandle,
+  pub(crate) metallic_map: MdrGpuTextureHandle,
+  pub(crate) occlusion_map: MdrGpuTextureHandle,
+  pub(crate) emissive_map: MdrGpuTextureHandle,
 }
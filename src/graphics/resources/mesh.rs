@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use log::error;
+use log::{error, warn};
 use vulkano::{buffer::cpu_pool::CpuBufferPoolChunk, memory::pool::StdMemoryPool};
 
 use super::{vertex::MdrVertex_tan, MdrVertex_norm, MdrVertex_pos, MdrVertex_uv};
@@ -31,26 +31,82 @@ pub struct MdrGpuMeshHandle {
   pub(crate) index_count: u32,
 }
 
+/// A model loaded from an .obj file.
+pub struct MdrObjModel {
+  /// The name of the model's object or group in the file.
+  pub name: String,
+  pub mesh_data: MdrMeshData,
+  /// Index of the model's material in the file's material list, if it has one.
+  pub material: Option<usize>,
+}
+
+/// Loads the first model in the .obj file at `path`.
 pub fn open_obj(path: &str) -> Option<MdrMeshData> {
-  // Load data from disk
+  let (models, _) = load_obj_file(path)?;
+
+  // Take only the first model
+  let model = match models.first() {
+    Some(model) => model,
+    None => {
+      error!("Obj file contains no models: {}", path);
+      return None;
+    }
+  };
+
+  Some(convert_obj_mesh(&model.mesh))
+}
+
+/// Loads every model in the .obj file at `path`, along with the materials from its .mtl files.
+pub fn open_obj_models(path: &str) -> Option<(Vec<MdrObjModel>, Vec<tobj::Material>)> {
+  let (models, materials) = load_obj_file(path)?;
+
+  // A missing or broken .mtl file shouldn't prevent the geometry from loading
+  let materials = match materials {
+    Ok(materials) => materials,
+    Err(e) => {
+      warn!(
+        "Failed to load materials for obj file: {}, reason: {}",
+        path, e
+      );
+      Vec::new()
+    }
+  };
+
+  let models = models
+    .iter()
+    .map(|model| MdrObjModel {
+      name: model.name.clone(),
+      mesh_data: convert_obj_mesh(&model.mesh),
+      material: model.mesh.material_id,
+    })
+    .collect();
+
+  Some((models, materials))
+}
+
+/// The materials read from the .mtl files referenced by an .obj file.
+type ObjMaterials = Result<Vec<tobj::Material>, tobj::LoadError>;
+
+/// Reads an .obj file and any .mtl files it references from disk.
+fn load_obj_file(path: &str) -> Option<(Vec<tobj::Model>, ObjMaterials)> {
   let options = tobj::GPU_LOAD_OPTIONS;
-  let load_result = tobj::load_obj(path, &options);
-  let (models, _) = match load_result {
-    Ok(value) => value,
+  match tobj::load_obj(path, &options) {
+    Ok(value) => Some(value),
     Err(e) => {
       error!("Failed to load obj file: {}, reason: {}", path, e);
-      return None;
+      None
     }
-  };
+  }
+}
 
-  // Take only the first model
-  let model = &models[0];
-  let model_positions = &model.mesh.positions;
-  let model_normals = &model.mesh.normals;
-  let model_uvs = &model.mesh.texcoords;
+/// Converts the mesh of a model loaded by tobj into `MdrMeshData`.
+fn convert_obj_mesh(mesh: &tobj::Mesh) -> MdrMeshData {
+  let model_positions = &mesh.positions;
+  let model_normals = &mesh.normals;
+  let model_uvs = &mesh.texcoords;
 
   // Prepare data structures
-  let vertex_count = model.mesh.positions.len() / 3;
+  let vertex_count = mesh.positions.len() / 3;
   let mut mesh_positions = Vec::<MdrVertex_pos>::with_capacity(vertex_count);
   let mut mesh_normals = Vec::<MdrVertex_norm>::with_capacity(vertex_count);
   let mut mesh_uvs = Vec::<MdrVertex_uv>::with_capacity(vertex_count);
@@ -79,25 +135,25 @@ pub fn open_obj(path: &str) -> Option<MdrMeshData> {
     });
   }
 
-  let mesh_tangents = calculate_mesh_tangents(&mesh_positions, &mesh_uvs, &model.mesh.indices);
+  let mesh_tangents = calculate_mesh_tangents(&mesh_positions, &mesh_uvs, &mesh.indices);
 
-  Some(MdrMeshData {
+  MdrMeshData {
     positions: mesh_positions,
     normals: mesh_normals,
     uvs: mesh_uvs,
     tangents: mesh_tangents,
 
-    indices: model.mesh.indices.clone(),
-    index_count: model.mesh.indices.len() as u32,
-  })
+    indices: mesh.indices.clone(),
+    index_count: mesh.indices.len() as u32,
+  }
 }
 
 /// Calculates a matrix of tangent vector values from input vectors containing
 /// a mesh's positions, uvs, and indices.
-fn calculate_mesh_tangents(
-  mesh_positions: &Vec<MdrVertex_pos>,
-  mesh_uvs: &Vec<MdrVertex_uv>,
-  mesh_indices: &Vec<u32>,
+pub(crate) fn calculate_mesh_tangents(
+  mesh_positions: &[MdrVertex_pos],
+  mesh_uvs: &[MdrVertex_uv],
+  mesh_indices: &[u32],
 ) -> Vec<MdrVertex_tan> {
   // Allocate memory for averaging tangent values
   let mut tangent_vals = Vec::<Vec<MdrVertex_tan>>::with_capacity(mesh_positions.len());
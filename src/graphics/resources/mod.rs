@@ -1,36 +1,84 @@
 pub mod color;
+mod compressed_import;
+mod cube_import;
+pub mod environment;
+mod gltf_import;
+pub mod manifest;
 pub mod material;
 pub mod mesh;
+pub mod post_processing;
 pub mod texture;
 pub mod vertex;
 
 use fxhash::{FxBuildHasher, FxHashMap};
 use image::{io::Reader as ImageReader, DynamicImage, ImageBuffer, Rgb, Rgba};
 use log::{debug, error, warn};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
 use vulkano::{
-  buffer::{BufferUsage, CpuBufferPool},
-  command_buffer::{CommandBufferExecFuture, PrimaryAutoCommandBuffer},
+  buffer::{BufferUsage, CpuAccessibleBuffer, CpuBufferPool},
+  command_buffer::{
+    AutoCommandBufferBuilder, BufferImageCopy, CommandBufferExecFuture, CommandBufferUsage,
+    CopyBufferToImageInfo, PrimaryAutoCommandBuffer, PrimaryCommandBuffer,
+  },
+  descriptor_set::layout::DescriptorType,
   device::{Device, Queue},
   format::Format,
-  image::{view::ImageView, ImageDimensions, ImmutableImage, MipmapsCount},
-  sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+  image::{
+    view::ImageView, ImageAspects, ImageCreateFlags, ImageDimensions, ImageLayout,
+    ImageSubresourceLayers, ImageUsage, ImmutableImage, MipmapsCount,
+  },
+  pipeline::Pipeline,
+  sampler::{
+    Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode, LOD_CLAMP_NONE,
+  },
+  shader::ShaderModule,
   sync::{GpuFuture, NowFuture},
 };
 
 pub use color::{MdrColorType, MdrRgb, MdrRgba};
+pub use environment::{MdrEnvironment, MdrGpuEnvironmentHandle};
 pub use material::{
   MdrGpuMaterialHandle, MdrMaterial, MdrMaterialCreateInfo, MdrMaterialUniformData,
 };
 pub use mesh::{MdrGpuMeshHandle, MdrMesh, MdrMeshData};
+pub use post_processing::{
+  MdrColorLut, MdrGpuColorLutHandle, MdrGpuPostEffectHandle, MdrPostEffect,
+};
 pub use texture::{MdrGpuTextureHandle, MdrTexture};
 pub use vertex::{MdrVertex_norm, MdrVertex_pos, MdrVertex_uv};
 
 use self::{
   color::MdrColor,
-  texture::{MdrSamplerMode, MdrTextureCreateInfo},
+  compressed_import::{is_compressed_file, mip_extent, open_compressed_image, MdrCompressedImage},
+  cube_import::parse_cube_lut,
+  gltf_import::{encode_base_color, scale_base_color, MdrGltfMaterial, MdrGltfTexture},
+  manifest::{
+    MdrColorLutSource, MdrEnvironmentImage, MdrEnvironmentSource, MdrImportFormat, MdrImportSource,
+    MdrMaterialSource, MdrMeshSource, MdrPostEffectSource, MdrResourceManifest, MdrTextureImage,
+    MdrTextureSource,
+  },
+  texture::{
+    MdrFilter, MdrMipmapMode, MdrSamplerDescription, MdrSamplerMode, MdrTextureCreateInfo,
+  },
   vertex::MdrVertex_tan,
 };
+use crate::{
+  config::DEFAULT_TEXTURE_ANISOTROPY,
+  graphics::{
+    hdr::HDR_FORMAT,
+    pipeline::{MdrEnvironmentPipeline, MdrPostPipeline},
+    render_pass::MdrRenderPass,
+  },
+  scene::MdrRenderObject,
+};
+
+/// Radiance of the uniform environment which lights scenes that have no environment of their
+/// own, giving surfaces facing away from every light a little of their base color.
+const DEFAULT_ENVIRONMENT_RADIANCE: f32 = 0.03;
+
+/// Size in bytes of the `vec4` push constant custom post-processing effects receive their
+/// parameters in.
+const POST_EFFECT_PARAMETERS_SIZE: u32 = 16;
 
 /// Manages resources on the GPU by storing meshes, textures, and materials into libraries which
 /// can be accessed by key. Objects in the scene only store these keys rather than maintaining
@@ -50,8 +98,26 @@ pub struct MdrResourceManager {
   material_library: HashMap<String, MdrGpuMaterialHandle, FxBuildHasher>,
 
   texture_load_futures: Option<Box<dyn GpuFuture>>,
-  sampler_palette: HashMap<MdrSamplerMode, Arc<Sampler>, FxBuildHasher>,
+  sampler_palette: HashMap<MdrSamplerDescription, Arc<Sampler>, FxBuildHasher>,
   texture_library: HashMap<String, MdrGpuTextureHandle, FxBuildHasher>,
+  /// A white texture standing in for optional material maps which aren't given, created the
+  /// first time it is needed.
+  white_texture: Option<MdrGpuTextureHandle>,
+
+  environment_pipeline: MdrEnvironmentPipeline,
+  environment_library: HashMap<String, MdrGpuEnvironmentHandle, FxBuildHasher>,
+  default_environment: MdrGpuEnvironmentHandle,
+  /// The lookup table of the specular BRDF, shared by every environment.
+  brdf_lut: Arc<ImageView<ImmutableImage>>,
+
+  color_lut_library: HashMap<String, MdrGpuColorLutHandle, FxBuildHasher>,
+  /// A lookup table which leaves colors as they are, bound when a scene has no color grading.
+  identity_lut: MdrGpuColorLutHandle,
+  /// The render pass custom post-processing effects draw with, which draws into HDR images.
+  post_render_pass: MdrRenderPass,
+  post_effect_library: HashMap<String, MdrGpuPostEffectHandle, FxBuildHasher>,
+
+  manifest: MdrResourceManifest,
 }
 
 impl MdrResourceManager {
@@ -76,9 +142,44 @@ impl MdrResourceManager {
     );
     let material_library = FxHashMap::<String, MdrGpuMaterialHandle>::default();
 
-    let sampler_palette = FxHashMap::<MdrSamplerMode, Arc<Sampler>>::default();
+    let sampler_palette = FxHashMap::<MdrSamplerDescription, Arc<Sampler>>::default();
     let texture_library = FxHashMap::<String, MdrGpuTextureHandle>::default();
 
+    // Environment handler initialization, generating the BRDF lookup table and the default
+    // environment on the GPU
+    let environment_pipeline = MdrEnvironmentPipeline::new(&logical_device);
+    let environment_library = FxHashMap::<String, MdrGpuEnvironmentHandle>::default();
+    let radiance = DEFAULT_ENVIRONMENT_RADIANCE;
+    let (default_equirect_map, upload_future) = upload_environment_images(
+      &queue,
+      vec![DynamicImage::ImageRgba32F(ImageBuffer::from_pixel(
+        1,
+        1,
+        Rgba([radiance, radiance, radiance, 1.0]),
+      ))],
+    );
+    let mut builder = AutoCommandBufferBuilder::primary(
+      logical_device.clone(),
+      queue.family(),
+      CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    let brdf_lut = environment_pipeline.generate_brdf_lut(&mut builder, &queue);
+    let default_environment =
+      environment_pipeline.generate_from_equirect(&mut builder, &queue, default_equirect_map);
+    let environment_future = upload_future
+      .then_execute(queue.clone(), builder.build().unwrap())
+      .unwrap();
+
+    // Post-processing resource initialization, with red changing fastest in the identity table
+    let color_lut_library = FxHashMap::<String, MdrGpuColorLutHandle>::default();
+    let identity_texels: Vec<u8> = (0..8u8)
+      .flat_map(|index| [index & 1, (index >> 1) & 1, (index >> 2) & 1, 1].map(|bit| bit * 255))
+      .collect();
+    let (identity_lut, identity_future) = upload_color_lut(&queue, 2, identity_texels);
+    let post_render_pass = MdrRenderPass::new_color_only(&logical_device, HDR_FORMAT);
+    let post_effect_library = FxHashMap::<String, MdrGpuPostEffectHandle>::default();
+
     Self {
       logical_device,
       queue,
@@ -93,9 +194,22 @@ impl MdrResourceManager {
       material_buffer_pool,
       material_library,
 
-      texture_load_futures: None,
+      texture_load_futures: Some(environment_future.join(identity_future).boxed()),
       sampler_palette,
       texture_library,
+      white_texture: None,
+
+      environment_pipeline,
+      environment_library,
+      default_environment,
+      brdf_lut,
+
+      color_lut_library,
+      identity_lut,
+      post_render_pass,
+      post_effect_library,
+
+      manifest: MdrResourceManifest::default(),
     }
   }
 
@@ -106,11 +220,7 @@ impl MdrResourceManager {
   /// Load a mesh from an .obj file into the mesh library with a given name.
   /// `path` specifies a path to the .obj file.
   /// `name` is the name given to the mesh in the mesh library.
-  pub fn load_mesh_obj<'a>(
-    &mut self,
-    path: &str,
-    name: &'a str,
-  ) -> Result<MdrMesh, MdrResourceError> {
+  pub fn load_mesh_obj(&mut self, path: &str, name: &str) -> Result<MdrMesh, MdrResourceError> {
     // Check that the mesh name isn't already in use
     if self.mesh_library.contains_key(name) {
       error!("Mesh library already contains name: {}", name);
@@ -125,6 +235,10 @@ impl MdrResourceManager {
 
     let mesh_handle = self.upload_mesh_to_gpu(mesh_data);
     self.mesh_library.insert(String::from(name), mesh_handle);
+    self.manifest.meshes.push(MdrMeshSource {
+      name: String::from(name),
+      path: String::from(path),
+    });
     debug!("Added {} to mesh library", name);
 
     Ok(MdrMesh {
@@ -132,7 +246,7 @@ impl MdrResourceManager {
     })
   }
 
-  pub fn load_mesh<'a>(&mut self, path: &str, name: &'a str) -> Result<MdrMesh, MdrResourceError> {
+  pub fn load_mesh(&mut self, path: &str, name: &str) -> Result<MdrMesh, MdrResourceError> {
     // open_model_assimp
 
     // Check that the mesh name isn't already in use
@@ -149,6 +263,10 @@ impl MdrResourceManager {
 
     let mesh_handle = self.upload_mesh_to_gpu(mesh_data);
     self.mesh_library.insert(String::from(name), mesh_handle);
+    self.manifest.meshes.push(MdrMeshSource {
+      name: String::from(name),
+      path: String::from(path),
+    });
     debug!("Added {} to mesh library", name);
 
     Ok(MdrMesh {
@@ -156,6 +274,198 @@ impl MdrResourceManager {
     })
   }
 
+  /// Load every model in an .obj file into the mesh library, along with the materials from the
+  /// .mtl files it references. Texture paths in the .mtl files are resolved relative to the .obj
+  /// file. Each resource is named with the prefix `name`. Returns an `MdrRenderObject` for every
+  /// model, using its .mtl material or a plain white material if it has none.
+  pub fn load_obj_models(
+    &mut self,
+    path: &str,
+    name: &str,
+  ) -> Result<Vec<MdrRenderObject>, MdrResourceError> {
+    let (models, materials) = match mesh::open_obj_models(path) {
+      Some(value) => value,
+      None => return Err(MdrResourceError::ObjLoadError),
+    };
+    debug!("Loaded obj file: {}", path);
+
+    // Create materials and the textures they use
+    let directory = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let mut material_names = Vec::<String>::new();
+    for material in materials.iter() {
+      let material_name = format!("{}.{}", name, material.name);
+      self.create_obj_material(material, directory, name, &material_name)?;
+      material_names.push(material_name);
+    }
+    // Models without a material use a default white material
+    let default_material_name = format!("{}.material_default", name);
+    if models.iter().any(|model| model.material.is_none()) {
+      self.create_obj_material(
+        &tobj::Material {
+          diffuse: [1.0; 3],
+          ..Default::default()
+        },
+        directory,
+        name,
+        &default_material_name,
+      )?;
+    }
+
+    // Upload each model and create an object for it
+    let mut objects = Vec::<MdrRenderObject>::with_capacity(models.len());
+    for (model_index, model) in models.into_iter().enumerate() {
+      let mesh = self.add_mesh(
+        model.mesh_data,
+        &format!("{}.{}.{}", name, model_index, model.name),
+      )?;
+      let material_name = match model.material {
+        Some(index) => &material_names[index],
+        None => &default_material_name,
+      };
+
+      objects.push(MdrRenderObject::new(
+        mesh,
+        MdrMaterial {
+          name: material_name.clone(),
+        },
+      ));
+    }
+    self.manifest.imports.push(MdrImportSource {
+      name: String::from(name),
+      path: String::from(path),
+      format: MdrImportFormat::ObjModels,
+    });
+    debug!("Added {} to resource libraries", name);
+
+    Ok(objects)
+  }
+
+  /// Load the meshes, textures, and materials of a .gltf or .glb file into the resource
+  /// libraries, naming each resource with the prefix `name`. Returns an object for every mesh
+  /// primitive drawn by the file's default scene, which `MdrScene::add_imported_objects()` adds
+  /// with the scene's hierarchy. Each node's first primitive carries its name and transform, and
+  /// its other primitives are children of the first. Textures keep the wrapping and filtering
+  /// of their glTF samplers. Nothing is loaded if any of the resource names is already in use.
+  pub fn load_gltf(
+    &mut self,
+    path: &str,
+    name: &str,
+  ) -> Result<Vec<MdrImportedObject>, MdrResourceError> {
+    let gltf_data = match gltf_import::open_gltf(path) {
+      Some(data) => data,
+      None => return Err(MdrResourceError::GltfLoadError),
+    };
+    debug!("Loaded gltf file: {}", path);
+
+    // Name each mesh primitive, remembering its material index
+    let mesh_primitives: Vec<Vec<(String, Option<usize>)>> = gltf_data
+      .meshes
+      .iter()
+      .enumerate()
+      .map(|(mesh_index, primitives)| {
+        primitives
+          .iter()
+          .enumerate()
+          .map(|(primitive_index, primitive)| {
+            let mesh_name = format!("{}.mesh{}.{}", name, mesh_index, primitive_index);
+            (mesh_name, primitive.material)
+          })
+          .collect()
+      })
+      .collect();
+    let material_names: Vec<String> = (0..gltf_data.materials.len())
+      .map(|material_index| format!("{}.material{}", name, material_index))
+      .collect();
+    // Primitives without a material use the glTF default material
+    let default_material_name = format!("{}.material_default", name);
+    let uses_default_material = mesh_primitives
+      .iter()
+      .flatten()
+      .any(|(_, material)| material.is_none());
+
+    // Check every name is free before loading anything, so a failed import doesn't leave some
+    // of the file's resources in the libraries
+    if let Some((mesh_name, _)) = mesh_primitives
+      .iter()
+      .flatten()
+      .find(|(mesh_name, _)| self.mesh_library.contains_key(mesh_name))
+    {
+      error!("Mesh library already contains name: {}", mesh_name);
+      return Err(MdrResourceError::DuplicateMeshName);
+    }
+    let default_material = uses_default_material.then_some(&default_material_name);
+    if let Some(material_name) = material_names
+      .iter()
+      .chain(default_material)
+      .find(|material_name| self.material_library.contains_key(*material_name))
+    {
+      error!("Material library already contains name: {}", material_name);
+      return Err(MdrResourceError::DuplicateMaterialName);
+    }
+
+    // Upload each mesh primitive
+    let primitives = gltf_data.meshes.into_iter().flatten();
+    for (primitive, (mesh_name, _)) in primitives.zip(mesh_primitives.iter().flatten()) {
+      self.add_mesh(primitive.mesh_data, mesh_name)?;
+    }
+
+    // Create materials and the textures they use
+    for (material, material_name) in gltf_data.materials.iter().zip(material_names.iter()) {
+      self.create_gltf_material(material, &gltf_data.images, name, material_name)?;
+    }
+    if uses_default_material {
+      self.create_gltf_material(
+        &MdrGltfMaterial::default(),
+        &gltf_data.images,
+        name,
+        &default_material_name,
+      )?;
+    }
+
+    // Create an object for each primitive drawn by each node, remembering the object holding
+    // each node's transform so its children can be parented to it
+    let mut objects = Vec::<MdrImportedObject>::new();
+    let mut node_objects = Vec::<usize>::with_capacity(gltf_data.nodes.len());
+    for node in gltf_data.nodes.iter() {
+      let node_object = objects.len();
+      node_objects.push(node_object);
+
+      for (primitive_index, (mesh_name, material_index)) in
+        mesh_primitives[node.mesh].iter().enumerate()
+      {
+        let material_name = match material_index {
+          Some(index) => &material_names[*index],
+          None => &default_material_name,
+        };
+
+        let mut object = MdrRenderObject::new(
+          MdrMesh {
+            name: mesh_name.clone(),
+          },
+          MdrMaterial {
+            name: material_name.clone(),
+          },
+        );
+        let parent = if primitive_index == 0 {
+          object.transform = node.transform;
+          object.name = node.name.clone();
+          node.parent.map(|parent| node_objects[parent])
+        } else {
+          Some(node_object)
+        };
+        objects.push(MdrImportedObject { object, parent });
+      }
+    }
+    self.manifest.imports.push(MdrImportSource {
+      name: String::from(name),
+      path: String::from(path),
+      format: MdrImportFormat::Gltf,
+    });
+    debug!("Added {} to resource libraries", name);
+
+    Ok(objects)
+  }
+
   /// Returns an `MdrMesh` specified by `name` from the mesh library. If no match is found for the
   /// key, it returns `MdrResourceError::MeshNotFound`.
   pub fn retrieve_mesh(&self, name: &str) -> Result<MdrMesh, MdrResourceError> {
@@ -180,6 +490,7 @@ impl MdrResourceManager {
     }
 
     self.mesh_library.remove(&String::from(name));
+    self.manifest.remove(name);
   }
 
   // ////////////////
@@ -199,17 +510,38 @@ impl MdrResourceManager {
       return Err(MdrResourceError::DuplicateTextureName);
     }
 
-    // Load image data from disk
-    let image = match ImageReader::open(texture_create_info.source) {
-      Ok(reader) => reader.decode().unwrap(),
-      Err(_) => return Err(MdrResourceError::ImageLoadError),
+    let texture_source = MdrTextureSource {
+      name: String::from(name),
+      image: MdrTextureImage::File {
+        path: String::from(texture_create_info.source),
+        color_type: texture_create_info.color_type,
+        sampler_mode: texture_create_info.sampler_mode,
+        filter: texture_create_info.filter,
+        mipmap_mode: texture_create_info.mipmap_mode,
+        anisotropy: texture_create_info.anisotropy,
+      },
     };
 
-    // Upload to GPU and catalogue texture in library
-    let texture_handle = self.upload_image_to_gpu(image, texture_create_info);
+    // Load image data from disk and upload it to the GPU, compressed images keep their format
+    let path = Path::new(texture_create_info.source);
+    let texture_handle = if is_compressed_file(path) {
+      match open_compressed_image(path) {
+        Some(image) => self.upload_compressed_image_to_gpu(image, texture_create_info)?,
+        None => return Err(MdrResourceError::ImageLoadError),
+      }
+    } else {
+      let image = match ImageReader::open(path) {
+        Ok(reader) => reader.decode().unwrap(),
+        Err(_) => return Err(MdrResourceError::ImageLoadError),
+      };
+      self.upload_image_to_gpu(image, texture_create_info)
+    };
+
+    // Catalogue texture in library
     self
       .texture_library
       .insert(String::from(name), texture_handle);
+    self.manifest.textures.push(texture_source);
     debug!("Added {} to texture library", name);
 
     Ok(MdrTexture {
@@ -259,11 +591,18 @@ impl MdrResourceManager {
         source: "",
         color_type: MdrColorType::from(color),
         sampler_mode: MdrSamplerMode::ClampToEdge,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::None,
+        anisotropy: None,
       },
     );
     self
       .texture_library
       .insert(String::from(name), texture_handle);
+    self.manifest.textures.push(MdrTextureSource {
+      name: String::from(name),
+      image: MdrTextureImage::Solid(color),
+    });
     debug!("Added {} to texture library", name);
 
     Ok(MdrTexture {
@@ -295,6 +634,7 @@ impl MdrResourceManager {
     }
 
     self.texture_library.remove(&String::from(name));
+    self.manifest.remove(name);
   }
 
   // /////////////////
@@ -308,7 +648,542 @@ impl MdrResourceManager {
     material_create_info: MdrMaterialCreateInfo,
     name: &str,
   ) -> Result<MdrMaterial, MdrResourceError> {
-    // Check that the mesh name isn't already in use
+    let material_source = MdrMaterialSource {
+      name: String::from(name),
+      diffuse: material_create_info.diffuse.name.clone(),
+      roughness: material_create_info.roughness.name.clone(),
+      normal: material_create_info.normal.name.clone(),
+      metallic: material_create_info
+        .metallic
+        .as_ref()
+        .map(|t| t.name.clone()),
+      occlusion: material_create_info
+        .occlusion
+        .as_ref()
+        .map(|t| t.name.clone()),
+      emissive: material_create_info
+        .emissive
+        .as_ref()
+        .map(|t| t.name.clone()),
+
+      metallic_factor: material_create_info.metallic_factor,
+      roughness_factor: material_create_info.roughness_factor,
+      emissive_factor: material_create_info.emissive_factor,
+    };
+
+    let material = self.add_material(material_create_info, name)?;
+    self.manifest.materials.push(material_source);
+
+    Ok(material)
+  }
+
+  /// Returns an `MdrMaterial` specified by `name` from the material library. If no match is found for the
+  /// key, it returns `MdrResourceError::MaterialNotFound`.
+  pub fn retrieve_material(&self, name: &str) -> Result<MdrMaterial, MdrResourceError> {
+    if !self.material_library.contains_key(name) {
+      return Err(MdrResourceError::MaterialNotFound);
+    }
+
+    Ok(MdrMaterial {
+      name: String::from(name),
+    })
+  }
+
+  /// Removes the material specified by `name` from the material library and drops it, freeing it
+  /// from GPU memory. Doing this will effectively invalidate any existing `MdrMaterial` objects.
+  pub fn unload_material(&mut self, name: &str) {
+    if !self.material_library.contains_key(name) {
+      warn!(
+        "Cannot unload material {} because it is not in the library",
+        name
+      );
+      return;
+    }
+
+    self.material_library.remove(&String::from(name));
+    self.manifest.remove(name);
+  }
+
+  // ////////////////////
+  // Environment handling
+  // ////////////////////
+
+  /// Loads an equirectangular environment from the .hdr or .exr file at `path` and stores it in
+  /// the environment library under the key `name`. The environment is converted into a cubemap
+  /// and filtered into the maps used for image-based lighting on the GPU.
+  pub fn load_environment(
+    &mut self,
+    path: &str,
+    name: &str,
+  ) -> Result<MdrEnvironment, MdrResourceError> {
+    // Check that the environment name isn't already in use
+    if self.environment_library.contains_key(name) {
+      error!("Environment library already contains name: {}", name);
+      return Err(MdrResourceError::DuplicateEnvironmentName);
+    }
+
+    let image = match ImageReader::open(path).map(|reader| reader.decode()) {
+      Ok(Ok(image)) => image,
+      _ => {
+        error!("Failed to load environment file: {}", path);
+        return Err(MdrResourceError::ImageLoadError);
+      }
+    };
+
+    // Upload the image and generate the environment's maps from it
+    let (equirect_map, upload_future) = upload_environment_images(&self.queue, vec![image]);
+    let mut builder = AutoCommandBufferBuilder::primary(
+      self.logical_device.clone(),
+      self.queue.family(),
+      CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    let environment_handle =
+      self
+        .environment_pipeline
+        .generate_from_equirect(&mut builder, &self.queue, equirect_map);
+    let generate_future = upload_future
+      .then_execute(self.queue.clone(), builder.build().unwrap())
+      .unwrap();
+    self.join_texture_future(generate_future);
+
+    self.add_environment(
+      environment_handle,
+      MdrEnvironmentImage::Equirect {
+        path: String::from(path),
+      },
+      name,
+    )
+  }
+
+  /// Loads an environment from six .hdr, .exr, or LDR image files at `faces`, one for each face
+  /// of a cubemap, and stores it in the environment library under the key `name`. The faces are
+  /// in the order +x, -x, +y, -y, +z, -z, where -y is up, and must all be the same square size.
+  pub fn load_environment_cube(
+    &mut self,
+    faces: [&str; 6],
+    name: &str,
+  ) -> Result<MdrEnvironment, MdrResourceError> {
+    // Check that the environment name isn't already in use
+    if self.environment_library.contains_key(name) {
+      error!("Environment library already contains name: {}", name);
+      return Err(MdrResourceError::DuplicateEnvironmentName);
+    }
+
+    let mut images = Vec::with_capacity(faces.len());
+    for path in faces {
+      match ImageReader::open(path).map(|reader| reader.decode()) {
+        Ok(Ok(image)) => images.push(image),
+        _ => {
+          error!("Failed to load environment file: {}", path);
+          return Err(MdrResourceError::ImageLoadError);
+        }
+      }
+    }
+
+    // The faces are copied into the cubemap texel for texel
+    let size = images[0].width();
+    if images
+      .iter()
+      .any(|image| image.width() != size || image.height() != size)
+    {
+      error!("Environment faces of {} are not the same square size", name);
+      return Err(MdrResourceError::ImageLoadError);
+    }
+
+    // Upload the faces and generate the environment's maps from them
+    let (face_maps, upload_future) = upload_environment_images(&self.queue, images);
+    let mut builder = AutoCommandBufferBuilder::primary(
+      self.logical_device.clone(),
+      self.queue.family(),
+      CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    let environment_handle =
+      self
+        .environment_pipeline
+        .generate_from_faces(&mut builder, &self.queue, face_maps);
+    let generate_future = upload_future
+      .then_execute(self.queue.clone(), builder.build().unwrap())
+      .unwrap();
+    self.join_texture_future(generate_future);
+
+    self.add_environment(
+      environment_handle,
+      MdrEnvironmentImage::Cube {
+        faces: faces.map(String::from),
+      },
+      name,
+    )
+  }
+
+  /// Returns an `MdrEnvironment` specified by `name` from the environment library. If no match
+  /// is found for the key, it returns `MdrResourceError::EnvironmentNotFound`.
+  pub fn retrieve_environment(&self, name: &str) -> Result<MdrEnvironment, MdrResourceError> {
+    if !self.environment_library.contains_key(name) {
+      return Err(MdrResourceError::EnvironmentNotFound);
+    }
+
+    Ok(MdrEnvironment {
+      name: String::from(name),
+    })
+  }
+
+  /// Removes the environment specified by `name` from the environment library and drops it,
+  /// freeing it from GPU memory. Doing this will effectively invalidate any existing
+  /// `MdrEnvironment` objects.
+  pub fn unload_environment(&mut self, name: &str) {
+    if !self.environment_library.contains_key(name) {
+      warn!(
+        "Cannot unload environment {} because it is not in the library",
+        name
+      );
+      return;
+    }
+
+    self.environment_library.remove(&String::from(name));
+    self.manifest.remove(name);
+  }
+
+  // ////////////////////////
+  // Post-processing handling
+  // ////////////////////////
+
+  /// Loads a 3D color lookup table from the .cube file at `path` and stores it in the lookup
+  /// table library under the key `name`. The table maps sRGB encoded colors to sRGB encoded
+  /// colors, as exported by most color grading tools.
+  pub fn load_color_lut(
+    &mut self,
+    path: &str,
+    name: &str,
+  ) -> Result<MdrColorLut, MdrResourceError> {
+    // Check that the lookup table name isn't already in use
+    if self.color_lut_library.contains_key(name) {
+      error!("Color lookup table library already contains name: {}", name);
+      return Err(MdrResourceError::DuplicateColorLutName);
+    }
+
+    let cube_lut = match fs::read_to_string(path)
+      .ok()
+      .and_then(|s| parse_cube_lut(&s))
+    {
+      Some(cube_lut) => cube_lut,
+      None => {
+        error!("Failed to load color lookup table file: {}", path);
+        return Err(MdrResourceError::ColorLutLoadError);
+      }
+    };
+    debug!("Loaded cube file: {}", path);
+
+    // Upload to GPU and catalogue lookup table in library
+    let (lut_handle, upload_future) =
+      upload_color_lut(&self.queue, cube_lut.size, cube_lut.to_rgba8());
+    self.join_texture_future(upload_future);
+    self
+      .color_lut_library
+      .insert(String::from(name), lut_handle);
+    self.manifest.color_luts.push(MdrColorLutSource {
+      name: String::from(name),
+      path: String::from(path),
+    });
+    debug!("Added {} to color lookup table library", name);
+
+    Ok(MdrColorLut {
+      name: String::from(name),
+    })
+  }
+
+  /// Returns an `MdrColorLut` specified by `name` from the lookup table library. If no match is
+  /// found for the key, it returns `MdrResourceError::ColorLutNotFound`.
+  pub fn retrieve_color_lut(&self, name: &str) -> Result<MdrColorLut, MdrResourceError> {
+    if !self.color_lut_library.contains_key(name) {
+      return Err(MdrResourceError::ColorLutNotFound);
+    }
+
+    Ok(MdrColorLut {
+      name: String::from(name),
+    })
+  }
+
+  /// Removes the lookup table specified by `name` from the lookup table library and drops it,
+  /// freeing it from GPU memory. Doing this will effectively invalidate any existing
+  /// `MdrColorLut` objects.
+  pub fn unload_color_lut(&mut self, name: &str) {
+    if !self.color_lut_library.contains_key(name) {
+      warn!(
+        "Cannot unload color lookup table {} because it is not in the library",
+        name
+      );
+      return;
+    }
+
+    self.color_lut_library.remove(&String::from(name));
+    self.manifest.remove(name);
+  }
+
+  /// Loads a custom post-processing effect from the compiled SPIR-V fragment shader at `path`
+  /// and stores it in the post effect library under the key `name`. The shader's `main` entry
+  /// point reads the scene's linear HDR colors from a `sampler2D` at set 0, binding 0, at the
+  /// coordinates it receives as a `vec2` at location 0, and writes the new color to location 0.
+  /// It may declare a `vec4` push constant, which holds the effect's parameters.
+  pub fn load_post_effect(
+    &mut self,
+    path: &str,
+    name: &str,
+  ) -> Result<MdrPostEffect, MdrResourceError> {
+    // Check that the effect name isn't already in use
+    if self.post_effect_library.contains_key(name) {
+      error!("Post effect library already contains name: {}", name);
+      return Err(MdrResourceError::DuplicatePostEffectName);
+    }
+
+    let bytes = match fs::read(path) {
+      Ok(bytes) => bytes,
+      Err(_) => {
+        error!("Failed to read shader file: {}", path);
+        return Err(MdrResourceError::ShaderLoadError);
+      }
+    };
+    // Vulkano reflects the shader's interface from the SPIR-V, rejecting invalid modules
+    let shader = match unsafe { ShaderModule::from_bytes(self.logical_device.clone(), &bytes) } {
+      Ok(shader) => shader,
+      Err(e) => {
+        error!("Failed to create shader module from {}: {}", path, e);
+        return Err(MdrResourceError::ShaderLoadError);
+      }
+    };
+    if shader.entry_point("main").is_none() {
+      error!("Shader {} has no main entry point", path);
+      return Err(MdrResourceError::ShaderLoadError);
+    }
+
+    let pipeline = match MdrPostPipeline::new(&self.logical_device, &self.post_render_pass, &shader)
+    {
+      Ok(pipeline) => pipeline.graphics_pipeline,
+      Err(e) => {
+        error!("Failed to create post effect pipeline from {}: {}", path, e);
+        return Err(MdrResourceError::ShaderLoadError);
+      }
+    };
+
+    // Check that the shader reads only the source image, and at most the parameters
+    let set_layouts = pipeline.layout().set_layouts();
+    let reads_source_image = set_layouts.len() == 1
+      && set_layouts[0].bindings().len() == 1
+      && set_layouts[0]
+        .bindings()
+        .get(&0)
+        .is_some_and(|binding| binding.descriptor_type == DescriptorType::CombinedImageSampler);
+    if !reads_source_image {
+      error!(
+        "Shader {} must read a single sampler2D at set 0, binding 0",
+        path
+      );
+      return Err(MdrResourceError::ShaderLoadError);
+    }
+    let takes_parameters = match pipeline.layout().push_constant_ranges() {
+      [] => false,
+      [range] if range.offset == 0 && range.size == POST_EFFECT_PARAMETERS_SIZE => true,
+      _ => {
+        error!("Shader {} may only declare a vec4 push constant", path);
+        return Err(MdrResourceError::ShaderLoadError);
+      }
+    };
+
+    // Catalogue effect in library
+    self.post_effect_library.insert(
+      String::from(name),
+      MdrGpuPostEffectHandle {
+        graphics_pipeline: pipeline,
+        takes_parameters,
+      },
+    );
+    self.manifest.post_effects.push(MdrPostEffectSource {
+      name: String::from(name),
+      path: String::from(path),
+    });
+    debug!("Added {} to post effect library", name);
+
+    Ok(MdrPostEffect {
+      name: String::from(name),
+    })
+  }
+
+  /// Returns an `MdrPostEffect` specified by `name` from the post effect library. If no match is
+  /// found for the key, it returns `MdrResourceError::PostEffectNotFound`.
+  pub fn retrieve_post_effect(&self, name: &str) -> Result<MdrPostEffect, MdrResourceError> {
+    if !self.post_effect_library.contains_key(name) {
+      return Err(MdrResourceError::PostEffectNotFound);
+    }
+
+    Ok(MdrPostEffect {
+      name: String::from(name),
+    })
+  }
+
+  /// Removes the effect specified by `name` from the post effect library and drops it. Doing
+  /// this will effectively invalidate any existing `MdrPostEffect` objects.
+  pub fn unload_post_effect(&mut self, name: &str) {
+    if !self.post_effect_library.contains_key(name) {
+      warn!(
+        "Cannot unload post effect {} because it is not in the library",
+        name
+      );
+      return;
+    }
+
+    self.post_effect_library.remove(&String::from(name));
+    self.manifest.remove(name);
+  }
+
+  // //////////////////
+  // Resource manifests
+  // //////////////////
+
+  /// Returns the manifest recording how each resource loaded through the resource manager was
+  /// created. Resources created by imports are covered by the import's entry.
+  pub fn manifest(&self) -> &MdrResourceManifest {
+    &self.manifest
+  }
+
+  /// Creates every resource in `manifest` which isn't already present in the resource
+  /// libraries. Imports are loaded first, then meshes, textures, materials, environments, color
+  /// lookup tables, and finally post effects.
+  pub fn load_manifest(&mut self, manifest: &MdrResourceManifest) -> Result<(), MdrResourceError> {
+    for import in manifest.imports.iter() {
+      if self
+        .manifest
+        .imports
+        .iter()
+        .any(|loaded| loaded.name == import.name)
+      {
+        continue;
+      }
+
+      match import.format {
+        MdrImportFormat::Gltf => {
+          self.load_gltf(&import.path, &import.name)?;
+        }
+        MdrImportFormat::ObjModels => {
+          self.load_obj_models(&import.path, &import.name)?;
+        }
+      }
+    }
+
+    for mesh in manifest.meshes.iter() {
+      if !self.mesh_library.contains_key(&mesh.name) {
+        self.load_mesh(&mesh.path, &mesh.name)?;
+      }
+    }
+
+    for texture in manifest.textures.iter() {
+      if self.texture_library.contains_key(&texture.name) {
+        continue;
+      }
+
+      match &texture.image {
+        MdrTextureImage::File {
+          path,
+          color_type,
+          sampler_mode,
+          filter,
+          mipmap_mode,
+          anisotropy,
+        } => self.load_texture(
+          MdrTextureCreateInfo {
+            source: path,
+            color_type: *color_type,
+            sampler_mode: *sampler_mode,
+            filter: *filter,
+            mipmap_mode: *mipmap_mode,
+            anisotropy: *anisotropy,
+          },
+          &texture.name,
+        )?,
+        MdrTextureImage::Solid(color) => self.create_solid_texture(*color, &texture.name)?,
+      };
+    }
+
+    for material in manifest.materials.iter() {
+      if self.material_library.contains_key(&material.name) {
+        continue;
+      }
+
+      let retrieve_optional = |name: &Option<String>| {
+        name
+          .as_deref()
+          .map(|name| self.retrieve_texture(name))
+          .transpose()
+      };
+      let material_create_info = MdrMaterialCreateInfo {
+        diffuse: self.retrieve_texture(&material.diffuse)?,
+        roughness: self.retrieve_texture(&material.roughness)?,
+        normal: self.retrieve_texture(&material.normal)?,
+        metallic: retrieve_optional(&material.metallic)?,
+        occlusion: retrieve_optional(&material.occlusion)?,
+        emissive: retrieve_optional(&material.emissive)?,
+
+        metallic_factor: material.metallic_factor,
+        roughness_factor: material.roughness_factor,
+        emissive_factor: material.emissive_factor,
+      };
+      self.create_material(material_create_info, &material.name)?;
+    }
+
+    for environment in manifest.environments.iter() {
+      if self.environment_library.contains_key(&environment.name) {
+        continue;
+      }
+
+      match &environment.image {
+        MdrEnvironmentImage::Equirect { path } => {
+          self.load_environment(path, &environment.name)?;
+        }
+        MdrEnvironmentImage::Cube { faces } => {
+          let faces = [
+            faces[0].as_str(),
+            faces[1].as_str(),
+            faces[2].as_str(),
+            faces[3].as_str(),
+            faces[4].as_str(),
+            faces[5].as_str(),
+          ];
+          self.load_environment_cube(faces, &environment.name)?;
+        }
+      }
+    }
+
+    for color_lut in manifest.color_luts.iter() {
+      if !self.color_lut_library.contains_key(&color_lut.name) {
+        self.load_color_lut(&color_lut.path, &color_lut.name)?;
+      }
+    }
+
+    for post_effect in manifest.post_effects.iter() {
+      if !self.post_effect_library.contains_key(&post_effect.name) {
+        self.load_post_effect(&post_effect.path, &post_effect.name)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  // //////////////////
+  // Internal functions
+  // //////////////////
+
+  pub(crate) fn take_upload_futures(&mut self) -> Option<Box<dyn GpuFuture>> {
+    self.texture_load_futures.take()
+  }
+
+  /// Creates a material from `material_create_info` and stores it in the material library
+  /// under the key `name`, without recording it in the manifest.
+  fn add_material(
+    &mut self,
+    material_create_info: MdrMaterialCreateInfo,
+    name: &str,
+  ) -> Result<MdrMaterial, MdrResourceError> {
+    // Check that the material name isn't already in use
     if self.material_library.contains_key(name) {
       error!("Material library already contains name: {}", name);
       return Err(MdrResourceError::DuplicateMaterialName);
@@ -316,8 +1191,9 @@ impl MdrResourceManager {
 
     // Generate material uniform buffer contents from create info
     let material = MdrMaterialUniformData {
-      specular_color: material_create_info.specular_color.into(),
-      shininess: material_create_info.shininess,
+      emissive_factor: material_create_info.emissive_factor.into(),
+      metallic_factor: material_create_info.metallic_factor,
+      roughness_factor: material_create_info.roughness_factor,
     };
 
     // Get maps from texture library
@@ -342,10 +1218,20 @@ impl MdrResourceManager {
         return Err(MdrResourceError::TextureNotFound);
       }
     };
+    let metallic_map = self.get_optional_map(&material_create_info.metallic)?;
+    let occlusion_map = self.get_optional_map(&material_create_info.occlusion)?;
+    let emissive_map = self.get_optional_map(&material_create_info.emissive)?;
 
     // Push material to GPU and store in library
-    let material_handle =
-      self.upload_material_to_gpu(material, diffuse_map, roughness_map, normal_map);
+    let material_handle = MdrGpuMaterialHandle {
+      material_data: self.material_buffer_pool.chunk([material]).unwrap(),
+      diffuse_map,
+      roughness_map,
+      normal_map,
+      metallic_map,
+      occlusion_map,
+      emissive_map,
+    };
     self
       .material_library
       .insert(String::from(name), material_handle);
@@ -356,38 +1242,105 @@ impl MdrResourceManager {
     })
   }
 
-  /// Returns an `MdrMaterial` specified by `name` from the material library. If no match is found for the
-  /// key, it returns `MdrResourceError::MaterialNotFound`.
-  pub fn retrieve_material(&self, name: &str) -> Result<MdrMaterial, MdrResourceError> {
-    if !self.material_library.contains_key(name) {
-      return Err(MdrResourceError::MaterialNotFound);
+  /// Gets the texture handle of an optional material map from the texture library, or a white
+  /// texture if there is no map.
+  fn get_optional_map(
+    &mut self,
+    texture: &Option<MdrTexture>,
+  ) -> Result<MdrGpuTextureHandle, MdrResourceError> {
+    let texture = match texture {
+      Some(texture) => texture,
+      None => return Ok(self.get_white_texture()),
+    };
+
+    match self.texture_library.get(&texture.name) {
+      Some(texture) => Ok(texture.clone()),
+      None => Err(MdrResourceError::TextureNotFound),
     }
+  }
 
-    Ok(MdrMaterial {
-      name: String::from(name),
-    })
+  /// Gets the white texture used in place of missing material maps, creating it if needed.
+  fn get_white_texture(&mut self) -> MdrGpuTextureHandle {
+    if let Some(texture) = &self.white_texture {
+      return texture.clone();
+    }
+
+    let texture = self.upload_image_to_gpu(
+      solid_image([1.0; 4]),
+      MdrTextureCreateInfo {
+        source: "",
+        color_type: MdrColorType::NonColorData,
+        sampler_mode: MdrSamplerMode::Repeat,
+        filter: MdrFilter::Linear,
+        mipmap_mode: MdrMipmapMode::None,
+        anisotropy: None,
+      },
+    );
+    self.white_texture = Some(texture.clone());
+    texture
   }
 
-  /// Removes the material specified by `name` from the material library and drops it, freeing it
-  /// from GPU memory. Doing this will effectively invalidate any existing `MdrMaterial` objects.
-  pub fn unload_material(&mut self, name: &str) {
-    if !self.material_library.contains_key(name) {
-      warn!(
-        "Cannot unload material {} because it is not in the library",
-        name
-      );
-      return;
+  /// Gets a reference to the `MdrGpuEnvironmentHandle` that corresponds to the input
+  /// `MdrEnvironment`, or to the default environment if there is none.
+  pub(crate) fn get_environment_handle(
+    &self,
+    environment: Option<&MdrEnvironment>,
+  ) -> &MdrGpuEnvironmentHandle {
+    let environment = match environment {
+      Some(environment) => environment,
+      None => return &self.default_environment,
+    };
+
+    match self.environment_library.get(&environment.name) {
+      Some(handle) => handle,
+      None => {
+        panic!(
+          "Could not find environment {} in environment library",
+          environment.name
+        );
+      }
     }
+  }
 
-    self.material_library.remove(&String::from(name));
+  /// Gets the lookup table of the specular BRDF used with every environment.
+  pub(crate) fn get_brdf_lut(&self) -> &Arc<ImageView<ImmutableImage>> {
+    &self.brdf_lut
   }
 
-  // //////////////////
-  // Internal functions
-  // //////////////////
+  /// Gets a reference to the `MdrGpuColorLutHandle` that corresponds to the input
+  /// `MdrColorLut`, or to the identity lookup table if there is none.
+  pub(crate) fn get_color_lut_handle(
+    &self,
+    color_lut: Option<&MdrColorLut>,
+  ) -> &MdrGpuColorLutHandle {
+    let color_lut = match color_lut {
+      Some(color_lut) => color_lut,
+      None => return &self.identity_lut,
+    };
 
-  pub(crate) fn take_upload_futures(&mut self) -> Option<Box<dyn GpuFuture>> {
-    self.texture_load_futures.take()
+    match self.color_lut_library.get(&color_lut.name) {
+      Some(handle) => handle,
+      None => {
+        panic!(
+          "Could not find color lookup table {} in lookup table library",
+          color_lut.name
+        );
+      }
+    }
+  }
+
+  /// Gets a reference to the `MdrGpuPostEffectHandle` that corresponds to the input
+  /// `MdrPostEffect`.
+  pub(crate) fn get_post_effect_handle(&self, effect: &MdrPostEffect) -> &MdrGpuPostEffectHandle {
+    match self.post_effect_library.get(&effect.name) {
+      Some(handle) => handle,
+      None => {
+        panic!(
+          "Could not find post effect {} in post effect library",
+          effect.name
+        );
+      }
+    }
   }
 
   /// Gets a reference to the `MdrGpuMeshHandle` that corresponds to the input `MdrMesh`.
@@ -412,6 +1365,362 @@ impl MdrResourceManager {
     }
   }
 
+  /// Uploads `mesh_data` to the GPU and stores it in the mesh library under the key `name`.
+  fn add_mesh(&mut self, mesh_data: MdrMeshData, name: &str) -> Result<MdrMesh, MdrResourceError> {
+    // Check that the mesh name isn't already in use
+    if self.mesh_library.contains_key(name) {
+      error!("Mesh library already contains name: {}", name);
+      return Err(MdrResourceError::DuplicateMeshName);
+    }
+
+    let mesh_handle = self.upload_mesh_to_gpu(mesh_data);
+    self.mesh_library.insert(String::from(name), mesh_handle);
+    debug!("Added {} to mesh library", name);
+
+    Ok(MdrMesh {
+      name: String::from(name),
+    })
+  }
+
+  /// Stores a generated environment in the environment library under the key `name`,
+  /// recording `image` as its source in the manifest.
+  fn add_environment(
+    &mut self,
+    environment_handle: MdrGpuEnvironmentHandle,
+    image: MdrEnvironmentImage,
+    name: &str,
+  ) -> Result<MdrEnvironment, MdrResourceError> {
+    self
+      .environment_library
+      .insert(String::from(name), environment_handle);
+    self.manifest.environments.push(MdrEnvironmentSource {
+      name: String::from(name),
+      image,
+    });
+    debug!("Added {} to environment library", name);
+
+    Ok(MdrEnvironment {
+      name: String::from(name),
+    })
+  }
+
+  /// Creates a material named `material_name` from an imported glTF material. Textures are
+  /// created from `images` with names prefixed by `prefix` and shared between materials, while
+  /// maps the material lacks are replaced by solid textures made from its factors.
+  fn create_gltf_material(
+    &mut self,
+    material: &MdrGltfMaterial,
+    images: &[Option<DynamicImage>],
+    prefix: &str,
+    material_name: &str,
+  ) -> Result<MdrMaterial, MdrResourceError> {
+    let find_image = |texture: Option<MdrGltfTexture>| {
+      texture.and_then(|texture| images[texture.image].as_ref().map(|image| (texture, image)))
+    };
+
+    // Base color map, multiplied by the base color factor. The map is only shared between
+    // materials when the factor leaves it unchanged
+    let diffuse = match find_image(material.base_color_texture) {
+      Some((texture, image)) if material.base_color_factor == [1.0; 4] => self
+        .add_texture_from_image(
+          image.clone(),
+          gltf_texture_create_info(MdrColorType::SRGBA, &texture),
+          &format!("{}.texture{}.base_color", prefix, texture.index),
+        ),
+      Some((texture, image)) => self.add_texture_from_image(
+        scale_base_color(image, material.base_color_factor),
+        gltf_texture_create_info(MdrColorType::SRGBA, &texture),
+        &format!("{}.texture{}.base_color", material_name, texture.index),
+      ),
+      None => self.add_texture_from_image(
+        solid_image(encode_base_color(material.base_color_factor)),
+        imported_texture_create_info(MdrColorType::SRGBA),
+        &format!("{}.base_color", material_name),
+      ),
+    };
+
+    // Roughness map, glTF stores roughness in the green channel of the metallic-roughness map
+    let roughness = match find_image(material.metallic_roughness_texture) {
+      Some((texture, image)) => {
+        let mut roughness_image = image.to_rgba8();
+        for pixel in roughness_image.pixels_mut() {
+          let roughness = (pixel[1] as f32 * material.roughness_factor) as u8;
+          *pixel = Rgba([roughness, roughness, roughness, 255]);
+        }
+
+        self.add_texture_from_image(
+          DynamicImage::ImageRgba8(roughness_image),
+          gltf_texture_create_info(MdrColorType::NonColorData, &texture),
+          &format!("{}.texture{}.roughness", material_name, texture.index),
+        )
+      }
+      None => {
+        let roughness = material.roughness_factor;
+        self.add_texture_from_image(
+          solid_image([roughness, roughness, roughness, 1.0]),
+          imported_texture_create_info(MdrColorType::NonColorData),
+          &format!("{}.roughness", material_name),
+        )
+      }
+    };
+
+    // Metallic map, glTF stores metalness in the blue channel of the metallic-roughness map
+    let metallic = find_image(material.metallic_roughness_texture).map(|(texture, image)| {
+      let mut metallic_image = image.to_rgba8();
+      for pixel in metallic_image.pixels_mut() {
+        let metallic = pixel[2];
+        *pixel = Rgba([metallic, metallic, metallic, 255]);
+      }
+
+      self.add_texture_from_image(
+        DynamicImage::ImageRgba8(metallic_image),
+        gltf_texture_create_info(MdrColorType::NonColorData, &texture),
+        &format!("{}.texture{}.metallic", prefix, texture.index),
+      )
+    });
+
+    // Normal map, falling back to a flat normal pointing along the surface normal
+    let normal = match find_image(material.normal_texture) {
+      Some((texture, image)) => self.add_texture_from_image(
+        image.clone(),
+        gltf_texture_create_info(MdrColorType::NonColorData, &texture),
+        &format!("{}.texture{}.normal", prefix, texture.index),
+      ),
+      None => self.add_texture_from_image(
+        solid_image([0.5, 0.5, 1.0, 1.0]),
+        imported_texture_create_info(MdrColorType::NonColorData),
+        &format!("{}.normal", material_name),
+      ),
+    };
+
+    // Occlusion map, glTF stores occlusion in the red channel, which is the one sampled
+    let occlusion = find_image(material.occlusion_texture).map(|(texture, image)| {
+      self.add_texture_from_image(
+        image.clone(),
+        gltf_texture_create_info(MdrColorType::NonColorData, &texture),
+        &format!("{}.texture{}.occlusion", prefix, texture.index),
+      )
+    });
+
+    // Emissive map
+    let emissive = find_image(material.emissive_texture).map(|(texture, image)| {
+      self.add_texture_from_image(
+        image.clone(),
+        gltf_texture_create_info(MdrColorType::SRGBA, &texture),
+        &format!("{}.texture{}.emissive", prefix, texture.index),
+      )
+    });
+
+    self.add_material(
+      MdrMaterialCreateInfo {
+        diffuse,
+        roughness,
+        normal,
+        metallic,
+        occlusion,
+        emissive,
+
+        metallic_factor: material.metallic_factor,
+        // The roughness factor is already applied to the roughness map
+        roughness_factor: 1.0,
+        emissive_factor: material.emissive_factor.into(),
+      },
+      material_name,
+    )
+  }
+
+  /// Creates a material named `material_name` from a material loaded from an .mtl file.
+  /// Texture paths are resolved relative to `directory` and the textures are shared between
+  /// materials, with names prefixed by `prefix`. Maps the material lacks are replaced by solid
+  /// textures made from its colors.
+  fn create_obj_material(
+    &mut self,
+    material: &tobj::Material,
+    directory: &Path,
+    prefix: &str,
+    material_name: &str,
+  ) -> Result<MdrMaterial, MdrResourceError> {
+    // Base color map
+    let diffuse = if material.diffuse_texture.is_empty() {
+      let [r, g, b] = material.diffuse;
+      self.add_texture_from_image(
+        solid_image([r, g, b, 1.0]),
+        imported_texture_create_info(MdrColorType::SRGBA),
+        &format!("{}.diffuse", material_name),
+      )
+    } else {
+      self.add_texture_from_file(
+        &directory.join(&material.diffuse_texture),
+        MdrColorType::SRGBA,
+        &format!("{}.{}", prefix, material.diffuse_texture),
+      )?
+    };
+
+    // Roughness map, from the PBR extension's map_Pr or the specular exponent map
+    let roughness_texture = match material.unknown_param.get("map_Pr") {
+      Some(texture) => texture,
+      None => &material.shininess_texture,
+    };
+    let roughness = if roughness_texture.is_empty() {
+      self.add_texture_from_image(
+        solid_image([1.0; 4]),
+        imported_texture_create_info(MdrColorType::NonColorData),
+        &format!("{}.roughness", material_name),
+      )
+    } else {
+      self.add_texture_from_file(
+        &directory.join(roughness_texture),
+        MdrColorType::NonColorData,
+        &format!("{}.{}", prefix, roughness_texture),
+      )?
+    };
+
+    // Normal map, falling back to a flat normal pointing along the surface normal
+    let normal = if material.normal_texture.is_empty() {
+      self.add_texture_from_image(
+        solid_image([0.5, 0.5, 1.0, 1.0]),
+        imported_texture_create_info(MdrColorType::NonColorData),
+        &format!("{}.normal", material_name),
+      )
+    } else {
+      self.add_texture_from_file(
+        &directory.join(&material.normal_texture),
+        MdrColorType::NonColorData,
+        &format!("{}.{}", prefix, material.normal_texture),
+      )?
+    };
+
+    // Metallic and emissive maps from the PBR extension
+    let metallic = match material.unknown_param.get("map_Pm") {
+      Some(texture) => Some(self.add_texture_from_file(
+        &directory.join(texture),
+        MdrColorType::NonColorData,
+        &format!("{}.{}", prefix, texture),
+      )?),
+      None => None,
+    };
+    let emissive = match material.unknown_param.get("map_Ke") {
+      Some(texture) => Some(self.add_texture_from_file(
+        &directory.join(texture),
+        MdrColorType::SRGBA,
+        &format!("{}.{}", prefix, texture),
+      )?),
+      None => None,
+    };
+
+    // Factors from the PBR extension, falling back to a roughness matching the Blinn-Phong
+    // specular exponent when there is no roughness map either
+    let parse_param = |key: &str| {
+      material
+        .unknown_param
+        .get(key)
+        .and_then(|value| value.trim().parse::<f32>().ok())
+    };
+    let roughness_factor = match parse_param("Pr") {
+      Some(roughness) => roughness,
+      None if roughness_texture.is_empty() && material.shininess > 0.0 => {
+        (2.0 / (material.shininess + 2.0)).sqrt()
+      }
+      None => 1.0,
+    };
+    let emissive_factor = match material.unknown_param.get("Ke") {
+      Some(value) => {
+        let channels: Vec<f32> = value
+          .split_whitespace()
+          .filter_map(|channel| channel.parse().ok())
+          .collect();
+        match channels[..] {
+          [r, g, b] => MdrRgb { r, g, b },
+          _ => MdrRgb::black(),
+        }
+      }
+      // Emissive maps are used as they are when there is no emissive color
+      None if emissive.is_some() => MdrRgb::white(),
+      None => MdrRgb::black(),
+    };
+
+    self.add_material(
+      MdrMaterialCreateInfo {
+        diffuse,
+        roughness,
+        normal,
+        metallic,
+        occlusion: None,
+        emissive,
+
+        metallic_factor: parse_param("Pm").unwrap_or(0.0),
+        roughness_factor,
+        emissive_factor,
+      },
+      material_name,
+    )
+  }
+
+  /// Loads the image at `path` and stores it in the texture library under the key `name`. If
+  /// the library already contains `name`, the existing texture is used instead.
+  fn add_texture_from_file(
+    &mut self,
+    path: &Path,
+    color_type: MdrColorType,
+    name: &str,
+  ) -> Result<MdrTexture, MdrResourceError> {
+    if self.texture_library.contains_key(name) {
+      return Ok(MdrTexture {
+        name: String::from(name),
+      });
+    }
+
+    // Compressed images are uploaded as they are
+    if is_compressed_file(path) {
+      let image = match open_compressed_image(path) {
+        Some(image) => image,
+        None => return Err(MdrResourceError::ImageLoadError),
+      };
+      let texture_handle =
+        self.upload_compressed_image_to_gpu(image, imported_texture_create_info(color_type))?;
+      self
+        .texture_library
+        .insert(String::from(name), texture_handle);
+      debug!("Added {} to texture library", name);
+
+      return Ok(MdrTexture {
+        name: String::from(name),
+      });
+    }
+
+    let image = match ImageReader::open(path).map(|reader| reader.decode()) {
+      Ok(Ok(image)) => image,
+      _ => {
+        error!("Failed to load image file: {}", path.display());
+        return Err(MdrResourceError::ImageLoadError);
+      }
+    };
+
+    Ok(self.add_texture_from_image(image, imported_texture_create_info(color_type), name))
+  }
+
+  /// Uploads `image` to the GPU with settings defined by the `texture_create_info` and stores it
+  /// in the texture library under the key `name`. If the library already contains `name`, the
+  /// existing texture is used instead.
+  fn add_texture_from_image(
+    &mut self,
+    image: DynamicImage,
+    texture_create_info: MdrTextureCreateInfo,
+    name: &str,
+  ) -> MdrTexture {
+    if !self.texture_library.contains_key(name) {
+      let texture_handle = self.upload_image_to_gpu(image, texture_create_info);
+      self
+        .texture_library
+        .insert(String::from(name), texture_handle);
+      debug!("Added {} to texture library", name);
+    }
+
+    MdrTexture {
+      name: String::from(name),
+    }
+  }
+
   /// Uploads input `MdrMeshdata` to the GPU and returns an `MdrGpuMeshHandle` containing the
   /// vertex buffer, index buffer, and index count for the input data.
   fn upload_mesh_to_gpu(&mut self, mesh: MdrMeshData) -> MdrGpuMeshHandle {
@@ -442,45 +1751,31 @@ impl MdrResourceManager {
     };
 
     // Handle intended color use types provided by user
-    let (immutable_image, upload_future) = match texture_create_info.color_type {
+    let (pixels, format) = match texture_create_info.color_type {
       // SRGBA images are in standard (gamma-corrected) color space.
       // They are used for images that will be shown to the user
-      MdrColorType::SRGBA => ImmutableImage::from_iter(
-        image.to_rgba8().into_raw(),
-        dimensions,
-        MipmapsCount::One,
-        Format::R8G8B8A8_SRGB,
-        self.queue.clone(),
-      )
-      .unwrap(),
+      MdrColorType::SRGBA => (image.to_rgba8().into_raw(), Format::R8G8B8A8_SRGB),
 
       // SRGB images are in standard color space, too, but with just the R, G, and B channels.
-      // They are also used for images that will be shown to the user
-      MdrColorType::SRGB => ImmutableImage::from_iter(
-        image.to_rgb8().into_raw(),
-        dimensions,
-        MipmapsCount::One,
-        Format::R8G8B8_SRGB,
-        self.queue.clone(),
-      )
-      .unwrap(),
+      // They are also used for images that will be shown to the user. Few devices can blit
+      // three-channel images to generate mip levels, so they're given an opaque alpha channel
+      MdrColorType::SRGB => (image.to_rgba8().into_raw(), Format::R8G8B8A8_SRGB),
 
       // NonColorData images are in linear color space, and their values are read as data, not rgb.
       // They are used for images that inform shading algorithms (normal maps, roughness maps, etc.)
-      // TODO: Currently we're super wasteful of memory because we use RGBA even when there isn't
-      // a meaningful alpha channel. We should start using texture compression, which will fix this.
-      MdrColorType::NonColorData => ImmutableImage::from_iter(
-        image.to_rgba8().into_raw(),
-        dimensions,
-        MipmapsCount::One,
-        Format::R8G8B8A8_UNORM,
-        self.queue.clone(),
-      )
-      .unwrap(),
+      // These are wasteful of memory because we use RGBA even when there isn't a meaningful alpha
+      // channel, so large textures should be loaded from compressed .ktx2 or .dds files instead.
+      MdrColorType::NonColorData => (image.to_rgba8().into_raw(), Format::R8G8B8A8_UNORM),
     };
 
+    // Mip levels are generated from the full size image with blits once it's uploaded
+    let mip_levels = self.mip_levels(format, texture_create_info.mipmap_mode);
+    let (immutable_image, upload_future) =
+      ImmutableImage::from_iter(pixels, dimensions, mip_levels, format, self.queue.clone())
+        .unwrap();
+
     let image_view = ImageView::new_default(immutable_image).unwrap();
-    let sampler = self.get_sampler(texture_create_info.sampler_mode);
+    let sampler = self.get_sampler(texture_create_info.sampler_description());
     self.join_texture_future(upload_future);
 
     MdrGpuTextureHandle {
@@ -489,59 +1784,210 @@ impl MdrResourceManager {
     }
   }
 
-  /// Uploads an input `MdrMaterialUniformData` to the GPU .
-  /// Returns an `MdrGpuMaterialHandle` containing the resulting buffer.
-  fn upload_material_to_gpu(
+  /// Uploads a block-compressed image and its mip levels to the GPU as they are, with sampling
+  /// settings defined by the `texture_create_info`. Devices which can't sample the image's format
+  /// are given the decompressed image instead. Returns an `MdrGpuTextureHandle` containing the
+  /// resulting image view and sampler.
+  fn upload_compressed_image_to_gpu(
     &mut self,
-    material_uniforms: MdrMaterialUniformData,
-    diffuse_map: MdrGpuTextureHandle,
-    roughness_map: MdrGpuTextureHandle,
-    normal_map: MdrGpuTextureHandle,
-  ) -> MdrGpuMaterialHandle {
-    MdrGpuMaterialHandle {
-      material_data: self
-        .material_buffer_pool
-        .chunk([material_uniforms])
-        .unwrap(),
-      diffuse_map,
-      roughness_map,
-      normal_map,
+    image: MdrCompressedImage,
+    texture_create_info: MdrTextureCreateInfo,
+  ) -> Result<MdrGpuTextureHandle, MdrResourceError> {
+    let features = self
+      .logical_device
+      .physical_device()
+      .format_properties(image.format)
+      .optimal_tiling_features;
+    if !features.sampled_image || !features.sampled_image_filter_linear {
+      debug!(
+        "Decompressing {:?} image, which the device cannot sample",
+        image.format
+      );
+      let decompressed = match image.decompress() {
+        Some(decompressed) => decompressed,
+        None => return Err(MdrResourceError::ImageLoadError),
+      };
+      let color_type = if image.is_srgb() {
+        MdrColorType::SRGBA
+      } else {
+        MdrColorType::NonColorData
+      };
+
+      return Ok(self.upload_image_to_gpu(
+        decompressed,
+        MdrTextureCreateInfo {
+          color_type,
+          ..texture_create_info
+        },
+      ));
+    }
+
+    // Compressed images can't be blitted, so only the file's own mip levels are used
+    let mut levels = image.levels;
+    if texture_create_info.mipmap_mode == MdrMipmapMode::None {
+      levels.truncate(1);
+    }
+
+    let (immutable_image, initializer) = ImmutableImage::uninitialized(
+      self.logical_device.clone(),
+      ImageDimensions::Dim2d {
+        width: image.width,
+        height: image.height,
+        array_layers: 1,
+      },
+      image.format,
+      MipmapsCount::Specific(levels.len() as u32),
+      ImageUsage {
+        transfer_dst: true,
+        sampled: true,
+        ..ImageUsage::none()
+      },
+      ImageCreateFlags::none(),
+      ImageLayout::ShaderReadOnlyOptimal,
+      [self.queue.family()],
+    )
+    .unwrap();
+
+    // Copy every mip level out of one staging buffer
+    let mut regions = Vec::with_capacity(levels.len());
+    let mut buffer_offset = 0;
+    for (level, data) in levels.iter().enumerate() {
+      let level = level as u32;
+      regions.push(BufferImageCopy {
+        buffer_offset,
+        image_subresource: ImageSubresourceLayers {
+          aspects: ImageAspects {
+            color: true,
+            ..ImageAspects::none()
+          },
+          mip_level: level,
+          array_layers: 0..1,
+        },
+        image_extent: [
+          mip_extent(image.width, level),
+          mip_extent(image.height, level),
+          1,
+        ],
+        ..Default::default()
+      });
+      buffer_offset += data.len() as u64;
     }
+    let staging_buffer = CpuAccessibleBuffer::from_iter(
+      self.logical_device.clone(),
+      BufferUsage::transfer_src(),
+      false,
+      levels.into_iter().flatten().collect::<Vec<u8>>(),
+    )
+    .unwrap();
+
+    let mut builder = AutoCommandBufferBuilder::primary(
+      self.logical_device.clone(),
+      self.queue.family(),
+      CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    builder
+      .copy_buffer_to_image(CopyBufferToImageInfo {
+        regions: regions.into(),
+        ..CopyBufferToImageInfo::buffer_image(staging_buffer, initializer)
+      })
+      .unwrap();
+    let upload_future = builder
+      .build()
+      .unwrap()
+      .execute(self.queue.clone())
+      .unwrap();
+    self.join_texture_future(upload_future);
+
+    Ok(MdrGpuTextureHandle {
+      image_view: ImageView::new_default(immutable_image).unwrap(),
+      sampler: self.get_sampler(texture_create_info.sampler_description()),
+    })
   }
 
-  /// Gets a sampler with the input `MdrSamplerMode` by either grabbing a reference off the
-  /// sampler palette or, if none is available, creating a new one.
-  fn get_sampler(&mut self, sampler_mode: MdrSamplerMode) -> Arc<Sampler> {
+  /// Returns how many mip levels a texture of `format` sampled with `mipmap_mode` should have.
+  /// Textures whose format can't be blitted with linear filtering on this device have none.
+  fn mip_levels(&self, format: Format, mipmap_mode: MdrMipmapMode) -> MipmapsCount {
+    if mipmap_mode == MdrMipmapMode::None {
+      return MipmapsCount::One;
+    }
+
+    let features = self
+      .logical_device
+      .physical_device()
+      .format_properties(format)
+      .optimal_tiling_features;
+    if features.blit_src && features.blit_dst && features.sampled_image_filter_linear {
+      MipmapsCount::Log2
+    } else {
+      warn!(
+        "Cannot generate mip levels for format {:?} on this device",
+        format
+      );
+      MipmapsCount::One
+    }
+  }
+
+  /// Gets a sampler matching the input `MdrSamplerDescription` by either grabbing a reference
+  /// off the sampler palette or, if none is available, creating a new one.
+  fn get_sampler(&mut self, description: MdrSamplerDescription) -> Arc<Sampler> {
     // If we've already got that sampler, return it
-    if let Some((_, sampler)) = self.sampler_palette.get_key_value(&sampler_mode) {
+    if let Some((_, sampler)) = self.sampler_palette.get_key_value(&description) {
       return sampler.clone();
     }
 
     // If not, we need to create one
     // TODO We should probably put this in its own resource
+    // Anisotropic filtering is an optional device feature with a device-specific limit
+    let anisotropy = match description.anisotropy {
+      Some(anisotropy) if self.logical_device.enabled_features().sampler_anisotropy => {
+        let max_anisotropy = self
+          .logical_device
+          .physical_device()
+          .properties()
+          .max_sampler_anisotropy;
+        Some((anisotropy as f32).clamp(1.0, max_anisotropy))
+      }
+      Some(_) => {
+        warn!("Anisotropic filtering is not supported by this device");
+        None
+      }
+      None => None,
+    };
+
+    let filter = match description.filter {
+      MdrFilter::Nearest => Filter::Nearest,
+      MdrFilter::Linear => Filter::Linear,
+    };
+    let (mipmap_mode, lod) = match description.mipmap_mode {
+      MdrMipmapMode::None => (SamplerMipmapMode::Nearest, 0.0..=0.0),
+      MdrMipmapMode::Nearest => (SamplerMipmapMode::Nearest, 0.0..=LOD_CLAMP_NONE),
+      MdrMipmapMode::Linear => (SamplerMipmapMode::Linear, 0.0..=LOD_CLAMP_NONE),
+    };
     let sampler = Sampler::new(
       self.logical_device.clone(),
       SamplerCreateInfo {
-        mag_filter: Filter::Linear,
-        min_filter: Filter::Linear,
-        address_mode: match sampler_mode {
+        mag_filter: filter,
+        min_filter: filter,
+        mipmap_mode,
+        address_mode: match description.sampler_mode {
           MdrSamplerMode::Repeat => [SamplerAddressMode::Repeat; 3],
           MdrSamplerMode::ClampToEdge => [SamplerAddressMode::ClampToEdge; 3],
+          MdrSamplerMode::MirroredRepeat => [SamplerAddressMode::MirroredRepeat; 3],
         },
+        anisotropy,
+        lod,
         ..Default::default()
       },
     )
     .unwrap();
 
     // Map the new sampler and return it
-    self.sampler_palette.insert(sampler_mode, sampler.clone());
+    self.sampler_palette.insert(description, sampler.clone());
     sampler
   }
 
-  fn join_texture_future(
-    &mut self,
-    texture_future: CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>,
-  ) {
+  fn join_texture_future(&mut self, texture_future: impl GpuFuture + 'static) {
     let new_future = match self.texture_load_futures.take() {
       Some(future) => future.join(texture_future).boxed(),
       None => texture_future.boxed(),
@@ -551,6 +1997,115 @@ impl MdrResourceManager {
   }
 }
 
+/// Uploads environment `images` of the same size to the GPU as the layers of one image, as
+/// 32-bit floats so that HDR values are kept. Returns a view of the image and the future of
+/// its upload.
+fn upload_environment_images(
+  queue: &Arc<Queue>,
+  images: Vec<DynamicImage>,
+) -> (
+  Arc<ImageView<ImmutableImage>>,
+  CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>,
+) {
+  let dimensions = ImageDimensions::Dim2d {
+    width: images[0].width(),
+    height: images[0].height(),
+    array_layers: images.len() as u32,
+  };
+
+  let (immutable_image, upload_future) = ImmutableImage::from_iter(
+    images
+      .into_iter()
+      .flat_map(|image| image.to_rgba32f().into_raw())
+      .collect::<Vec<f32>>(),
+    dimensions,
+    MipmapsCount::One,
+    Format::R32G32B32A32_SFLOAT,
+    queue.clone(),
+  )
+  .unwrap();
+
+  (
+    ImageView::new_default(immutable_image).unwrap(),
+    upload_future,
+  )
+}
+
+/// Uploads a color lookup table with `size` entries along each axis to the GPU as a 3D image.
+/// `texels` holds the 8-bit RGBA output of each entry, with red changing fastest and blue
+/// slowest. Returns a handle to the table and the future of its upload.
+fn upload_color_lut(
+  queue: &Arc<Queue>,
+  size: u32,
+  texels: Vec<u8>,
+) -> (
+  MdrGpuColorLutHandle,
+  CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>,
+) {
+  let (immutable_image, upload_future) = ImmutableImage::from_iter(
+    texels,
+    ImageDimensions::Dim3d {
+      width: size,
+      height: size,
+      depth: size,
+    },
+    MipmapsCount::One,
+    Format::R8G8B8A8_UNORM,
+    queue.clone(),
+  )
+  .unwrap();
+
+  (
+    MdrGpuColorLutHandle {
+      image_view: ImageView::new_default(immutable_image).unwrap(),
+    },
+    upload_future,
+  )
+}
+
+/// Returns the settings used for textures which are created by imports rather than
+/// `load_texture()`.
+fn imported_texture_create_info(color_type: MdrColorType) -> MdrTextureCreateInfo<'static> {
+  MdrTextureCreateInfo {
+    source: "",
+    color_type,
+    sampler_mode: MdrSamplerMode::Repeat,
+    filter: MdrFilter::Linear,
+    mipmap_mode: MdrMipmapMode::Linear,
+    anisotropy: Some(DEFAULT_TEXTURE_ANISOTROPY),
+  }
+}
+
+/// Returns the settings used for a texture imported from a glTF file, which are taken from its
+/// sampler.
+fn gltf_texture_create_info(
+  color_type: MdrColorType,
+  texture: &MdrGltfTexture,
+) -> MdrTextureCreateInfo<'static> {
+  MdrTextureCreateInfo {
+    source: "",
+    color_type,
+    sampler_mode: texture.sampler_mode,
+    filter: texture.filter,
+    mipmap_mode: texture.mipmap_mode,
+    anisotropy: Some(DEFAULT_TEXTURE_ANISOTROPY),
+  }
+}
+
+/// Creates a single-pixel image with the input linear `[r, g, b, a]` values.
+fn solid_image(rgba: [f32; 4]) -> DynamicImage {
+  let rgba_u8 = rgba.map(|channel| (channel * 255.0) as u8);
+  DynamicImage::ImageRgba8(ImageBuffer::from_fn(1, 1, |_, _| Rgba(rgba_u8)))
+}
+
+/// An object created by importing a file, along with its place in the file's hierarchy.
+pub struct MdrImportedObject {
+  pub object: MdrRenderObject,
+  /// Index of the object's parent in the list of imported objects, which always comes before
+  /// it. `None` for root objects.
+  pub parent: Option<usize>,
+}
+
 #[derive(Debug)]
 /// Error emitted by `MdrResourceManager`.
 pub enum MdrResourceError {
@@ -558,6 +2113,8 @@ pub enum MdrResourceError {
   ObjLoadError,
   /// Emitted when the resource manager fails to load assets with Assimp.
   AssimpLoadError,
+  /// Emitted when the resource manager fails to load a .gltf or .glb file.
+  GltfLoadError,
   /// Emitted when the resource manager fails to load an image file.
   ImageLoadError,
 
@@ -581,4 +2138,30 @@ pub enum MdrResourceError {
   /// Emitted when the resource manager attempts to add a texture with a name that is
   /// already present in the texture library.
   DuplicateTextureName,
+
+  /// Emitted when the resource manager cannot find an environment with a given name in its
+  /// environment library.
+  EnvironmentNotFound,
+  /// Emitted when the resource manager attempts to add an environment with a name that is
+  /// already present in the environment library.
+  DuplicateEnvironmentName,
+
+  /// Emitted when the resource manager fails to load a .cube color lookup table file.
+  ColorLutLoadError,
+  /// Emitted when the resource manager cannot find a color lookup table with a given name in
+  /// its lookup table library.
+  ColorLutNotFound,
+  /// Emitted when the resource manager attempts to add a color lookup table with a name that is
+  /// already present in the lookup table library.
+  DuplicateColorLutName,
+
+  /// Emitted when the resource manager fails to load a post effect's shader, or the shader
+  /// doesn't match the interface of post effects.
+  ShaderLoadError,
+  /// Emitted when the resource manager cannot find a post effect with a given name in its
+  /// post effect library.
+  PostEffectNotFound,
+  /// Emitted when the resource manager attempts to add a post effect with a name that is
+  /// already present in the post effect library.
+  DuplicatePostEffectName,
 }
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use vulkano::{
+  image::{view::ImageView, ImmutableImage},
+  pipeline::GraphicsPipeline,
+};
+
+/// A 3D color lookup table in the resource manager's lookup table library, which grades the
+/// scene's colors when set as its `post_processing.color_grading.lut`.
+#[derive(Debug)]
+pub struct MdrColorLut {
+  pub name: String,
+}
+
+/// A custom post-processing effect in the resource manager's post effect library, which is
+/// applied to the scene when added to its `post_processing.custom_effects`.
+#[derive(Debug)]
+pub struct MdrPostEffect {
+  pub name: String,
+}
+
+/// A color lookup table stored as a 3D image, indexed by red, green, and blue along its x, y,
+/// and z axes.
+#[derive(Clone)]
+pub struct MdrGpuColorLutHandle {
+  pub(crate) image_view: Arc<ImageView<ImmutableImage>>,
+}
+
+/// The pipeline drawing a custom post-processing effect over the whole screen.
+#[derive(Clone)]
+pub struct MdrGpuPostEffectHandle {
+  pub(crate) graphics_pipeline: Arc<GraphicsPipeline>,
+  /// Whether the effect's shader declares the push constant its parameters are passed in.
+  pub(crate) takes_parameters: bool,
+}
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use vulkano::{
   image::{view::ImageView, ImmutableImage},
   sampler::Sampler,
@@ -16,6 +17,26 @@ pub struct MdrTextureCreateInfo<'a> {
   pub source: &'a str,
   pub color_type: MdrColorType,
   pub sampler_mode: MdrSamplerMode,
+  /// How texels are filtered when the texture is magnified or minified.
+  pub filter: MdrFilter,
+  /// How the texture's mip levels are blended. A full mip chain is generated on upload unless
+  /// this is `MdrMipmapMode::None`.
+  pub mipmap_mode: MdrMipmapMode,
+  /// The most texels sampled along the direction the texture is stretched in when viewed at a
+  /// grazing angle, or `None` to disable anisotropic filtering. Clamped to the device's limit.
+  pub anisotropy: Option<u32>,
+}
+
+impl MdrTextureCreateInfo<'_> {
+  /// Returns the sampler which reads the texture.
+  pub(crate) fn sampler_description(&self) -> MdrSamplerDescription {
+    MdrSamplerDescription {
+      sampler_mode: self.sampler_mode,
+      filter: self.filter,
+      mipmap_mode: self.mipmap_mode,
+      anisotropy: self.anisotropy,
+    }
+  }
 }
 
 #[derive(Clone)]
@@ -25,11 +46,47 @@ pub struct MdrGpuTextureHandle {
 }
 
 /// Refers to various texture sampling options supported by the engine.
-#[derive(Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum MdrSamplerMode {
   /// The texture will repeat when u, v, w > 1.0
   Repeat,
 
   /// The texture will use the edge pixel at u, v, w > 1.0.
   ClampToEdge,
+
+  /// The texture will repeat when u, v, w > 1.0, flipping on every other repeat.
+  MirroredRepeat,
+}
+
+/// Refers to the texel filtering options supported by the engine.
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum MdrFilter {
+  /// The nearest texel is used, giving a blocky look when magnified.
+  Nearest,
+
+  /// The four nearest texels are blended.
+  Linear,
+}
+
+/// Refers to the ways of sampling a texture's mip levels supported by the engine.
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum MdrMipmapMode {
+  /// The texture has no mip chain, only the full size image is sampled.
+  None,
+
+  /// The mip level closest to the texture's on-screen size is sampled.
+  Nearest,
+
+  /// The two mip levels either side of the texture's on-screen size are sampled and blended,
+  /// which gives trilinear filtering along with `MdrFilter::Linear`.
+  Linear,
+}
+
+/// Everything which determines how a texture is sampled, used to share samplers between textures.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub(crate) struct MdrSamplerDescription {
+  pub sampler_mode: MdrSamplerMode,
+  pub filter: MdrFilter,
+  pub mipmap_mode: MdrMipmapMode,
+  pub anisotropy: Option<u32>,
 }
@@ -1,3 +1,6 @@
+// The `Pod` derive generates layout checks which are never called
+#![allow(dead_code)]
+
 use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
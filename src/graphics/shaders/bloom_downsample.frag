@@ -0,0 +1,72 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+// Input buffer objects
+// ////////////////////
+
+// The image being halved in size, either the scene or the previous bloom level
+layout(set = 0, binding = 0) uniform sampler2D source_image;
+
+// Push constant data containing the bloom settings
+layout(push_constant) uniform MdrBloomDownsamplePushConstants
+{
+  // Brightness above which colors glow, and the width of the range below it they fade in over
+  float threshold;
+  float knee;
+  // Whether to keep only the colors above the threshold, set when reading the scene
+  uint apply_threshold;
+} settings;
+
+// Helpers
+// ///////
+
+// Scales `color` so that only the part of its brightness above the threshold remains, fading
+// in smoothly over the knee
+vec3 threshold(vec3 color) {
+  float brightness = max(color.r, max(color.g, color.b));
+  float knee = settings.threshold * settings.knee;
+
+  float soft = clamp(brightness - settings.threshold + knee, 0.0, 2.0 * knee);
+  soft = soft * soft / (4.0 * knee + 0.0001);
+  float contribution = max(soft, brightness - settings.threshold) / max(brightness, 0.0001);
+
+  return color * contribution;
+}
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  vec2 texel = 1.0 / vec2(textureSize(source_image, 0));
+
+  // Thirteen samples in overlapping boxes around the output texel, which avoids the flickering
+  // of a plain box filter as bright spots move
+  vec3 a = texture(source_image, v_uv + texel * vec2(-2.0, -2.0)).rgb;
+  vec3 b = texture(source_image, v_uv + texel * vec2(0.0, -2.0)).rgb;
+  vec3 c = texture(source_image, v_uv + texel * vec2(2.0, -2.0)).rgb;
+  vec3 d = texture(source_image, v_uv + texel * vec2(-2.0, 0.0)).rgb;
+  vec3 e = texture(source_image, v_uv).rgb;
+  vec3 f = texture(source_image, v_uv + texel * vec2(2.0, 0.0)).rgb;
+  vec3 g = texture(source_image, v_uv + texel * vec2(-2.0, 2.0)).rgb;
+  vec3 h = texture(source_image, v_uv + texel * vec2(0.0, 2.0)).rgb;
+  vec3 i = texture(source_image, v_uv + texel * vec2(2.0, 2.0)).rgb;
+  vec3 j = texture(source_image, v_uv + texel * vec2(-1.0, -1.0)).rgb;
+  vec3 k = texture(source_image, v_uv + texel * vec2(1.0, -1.0)).rgb;
+  vec3 l = texture(source_image, v_uv + texel * vec2(-1.0, 1.0)).rgb;
+  vec3 m = texture(source_image, v_uv + texel * vec2(1.0, 1.0)).rgb;
+
+  vec3 color = e * 0.125;
+  color += (a + c + g + i) * 0.03125;
+  color += (b + d + f + h) * 0.0625;
+  color += (j + k + l + m) * 0.125;
+
+  if (settings.apply_threshold != 0u) {
+    color = threshold(max(color, vec3(0.0)));
+  }
+
+  f_color = vec4(color, 1.0);
+}
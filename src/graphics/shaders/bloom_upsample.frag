@@ -0,0 +1,41 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+// Input buffer objects
+// ////////////////////
+
+// The smaller, already upsampled bloom level being doubled in size
+layout(set = 0, binding = 0) uniform sampler2D source_image;
+// The downsampled bloom level of the size being drawn, which the upsampled glow is added to
+layout(set = 0, binding = 1) uniform sampler2D current_image;
+
+// Push constant data containing the bloom settings
+layout(push_constant) uniform MdrBloomUpsamplePushConstants
+{
+  // Distance in source texels between the filter's samples
+  float radius;
+} settings;
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  vec2 offset = settings.radius / vec2(textureSize(source_image, 0));
+
+  // A 3x3 tent filter, which spreads the glow further at each level
+  vec3 color = texture(source_image, v_uv).rgb * 4.0;
+  color += texture(source_image, v_uv + offset * vec2(0.0, -1.0)).rgb * 2.0;
+  color += texture(source_image, v_uv + offset * vec2(-1.0, 0.0)).rgb * 2.0;
+  color += texture(source_image, v_uv + offset * vec2(1.0, 0.0)).rgb * 2.0;
+  color += texture(source_image, v_uv + offset * vec2(0.0, 1.0)).rgb * 2.0;
+  color += texture(source_image, v_uv + offset * vec2(-1.0, -1.0)).rgb;
+  color += texture(source_image, v_uv + offset * vec2(1.0, -1.0)).rgb;
+  color += texture(source_image, v_uv + offset * vec2(-1.0, 1.0)).rgb;
+  color += texture(source_image, v_uv + offset * vec2(1.0, 1.0)).rgb;
+
+  f_color = vec4(texture(current_image, v_uv).rgb + color / 16.0, 1.0);
+}
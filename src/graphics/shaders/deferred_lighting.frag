@@ -0,0 +1,68 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec2 v_uv;
+
+// Added onto the emitted light and skybox already in the HDR image
+layout(location = 0) out vec4 f_color;
+
+// Input buffer objects
+// ////////////////////
+
+#include "scene_data.glsl"
+#include "shading.glsl"
+
+// The G-buffer written by the geometry pass, in the layout written by `gbuffer.frag`
+layout(set = 1, binding = 0) uniform sampler2D gbuffer_albedo;
+layout(set = 1, binding = 1) uniform sampler2D gbuffer_normal;
+layout(set = 1, binding = 2) uniform sampler2D gbuffer_material;
+layout(set = 1, binding = 3) uniform sampler2D gbuffer_depth;
+
+// Push constant data containing the camera's transforms
+layout(push_constant) uniform MdrDeferredLightingPushConstants
+{
+  // The inverse of the camera's view and projection, mapping clip space into world space
+  mat4 inverse_view_projection;
+} camera;
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  ivec2 texel = ivec2(gl_FragCoord.xy);
+
+  // Pixels no object was drawn over keep the clear color or skybox
+  float depth = texelFetch(gbuffer_depth, texel, 0).r;
+  if (depth >= 1.0) {
+    discard;
+  }
+
+  // Reconstruct the surface's position from its depth
+  vec4 world_position = camera.inverse_view_projection * vec4(v_uv * 2.0 - 1.0, depth, 1.0);
+  vec3 position = world_position.xyz / world_position.w;
+  float view_depth = -(scene_data.camera.view * vec4(position, 1.0)).z;
+
+  // Material inputs
+  vec4 albedo = texelFetch(gbuffer_albedo, texel, 0);
+  vec4 normal = texelFetch(gbuffer_normal, texel, 0);
+  vec2 material = texelFetch(gbuffer_material, texel, 0).rg;
+
+  vec3 result = shade_surface(
+    position,
+    gl_FragCoord.xy,
+    view_depth,
+    normalize(normal.xyz),
+    albedo.rgb,
+    material.g,
+    material.r,
+    albedo.a,
+    normal.w > 0.5
+  );
+
+  // Tint each directional light shadow cascade a different color
+  if (scene_data.debug_view == DEBUG_VIEW_SHADOW_CASCADES) {
+    result = mix(result, cascade_debug_color(view_depth), 0.5);
+  }
+
+  f_color = vec4(result, 1.0);
+}
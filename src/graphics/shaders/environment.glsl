@@ -0,0 +1,47 @@
+// Helpers shared by the compute shaders which convert and filter environment maps.
+
+#define PI 3.14159265359
+
+// Returns the world space direction through `uv` (from 0 to 1) on cube face `face`, in the order
+// +x, -x, +y, -y, +z, -z used by Vulkan cube images.
+vec3 cube_direction(uint face, vec2 uv) {
+  vec2 st = uv * 2.0 - 1.0;
+  vec3 direction;
+  switch (face) {
+    case 0u: direction = vec3(1.0, -st.y, -st.x); break;
+    case 1u: direction = vec3(-1.0, -st.y, st.x); break;
+    case 2u: direction = vec3(st.x, 1.0, st.y); break;
+    case 3u: direction = vec3(st.x, -1.0, -st.y); break;
+    case 4u: direction = vec3(st.x, -st.y, 1.0); break;
+    default: direction = vec3(-st.x, -st.y, -1.0); break;
+  }
+  return normalize(direction);
+}
+
+// Returns the `i`th of `count` points of the Hammersley sequence, spread evenly over the unit
+// square.
+vec2 hammersley(uint i, uint count) {
+  uint bits = i;
+  bits = (bits << 16u) | (bits >> 16u);
+  bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+  bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+  bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+  bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+  return vec2(float(i) / float(count), float(bits) * 2.3283064365386963e-10);
+}
+
+// Returns a half vector around the normal `N` drawn from the GGX distribution of a surface with
+// `roughness`, for the point `xi` on the unit square.
+vec3 importance_sample_ggx(vec2 xi, vec3 N, float roughness) {
+  float alpha = roughness * roughness;
+  float phi = 2.0 * PI * xi.x;
+  float cos_theta = sqrt((1.0 - xi.y) / (1.0 + (alpha * alpha - 1.0) * xi.y));
+  float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+  vec3 H = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+
+  // Rotate from tangent space around the normal into world space
+  vec3 up = abs(N.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+  vec3 tangent = normalize(cross(up, N));
+  vec3 bitangent = cross(N, tangent);
+  return normalize(tangent * H.x + bitangent * H.y + N * H.z);
+}
@@ -0,0 +1,15 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+
+// Where the fragment lies on the screen, from 0 at the top left to 1 at the bottom right
+layout(location = 0) out vec2 v_uv;
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  // One triangle covering the whole screen, with no vertex buffer
+  v_uv = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+  gl_Position = vec4(v_uv * 2.0 - 1.0, 0.0, 1.0);
+}
@@ -0,0 +1,100 @@
+#version 450
+
+// Configuration
+// /////////////
+// Edges with less contrast than this are left alone, absolutely and relative to the brightest
+// of the samples
+#define EDGE_THRESHOLD_MIN (1.0 / 32.0)
+#define EDGE_THRESHOLD (1.0 / 8.0)
+// Furthest distance in texels that is blurred along an edge
+#define SPAN_MAX 8.0
+// Keep the blur direction stable in dark and flat areas
+#define REDUCE_MUL (1.0 / 8.0)
+#define REDUCE_MIN (1.0 / 128.0)
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+// Input buffer objects
+// ////////////////////
+
+// The tone mapped frame, which is read back as linear colors
+layout(set = 0, binding = 0) uniform sampler2D source_image;
+
+// Push constant data containing the output settings
+layout(push_constant) uniform MdrFxaaPushConstants
+{
+  // Whether the output must be gamma encoded here, as the target's format is not sRGB
+  uint encode_srgb;
+} settings;
+
+// Helpers
+// ///////
+
+// Returns the perceived brightness of a linear color, approximately gamma encoded as edges are
+// judged by how they look
+float luma(vec3 color) {
+  return sqrt(dot(color, vec3(0.299, 0.587, 0.114)));
+}
+
+vec3 sample_offset(vec2 offset) {
+  return textureLod(source_image, v_uv + offset, 0.0).rgb;
+}
+
+// Encodes a linear color with the sRGB transfer function
+vec3 linear_to_srgb(vec3 color) {
+  vec3 low = color * 12.92;
+  vec3 high = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+
+  return mix(high, low, lessThanEqual(color, vec3(0.0031308)));
+}
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  vec2 texel = 1.0 / vec2(textureSize(source_image, 0));
+
+  vec3 color_m = sample_offset(vec2(0.0));
+  float luma_nw = luma(sample_offset(texel * vec2(-1.0, -1.0)));
+  float luma_ne = luma(sample_offset(texel * vec2(1.0, -1.0)));
+  float luma_sw = luma(sample_offset(texel * vec2(-1.0, 1.0)));
+  float luma_se = luma(sample_offset(texel * vec2(1.0, 1.0)));
+  float luma_m = luma(color_m);
+
+  float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+  float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+  vec3 color = color_m;
+  // Only blur pixels on a noticeable edge
+  if (luma_max - luma_min >= max(EDGE_THRESHOLD_MIN, luma_max * EDGE_THRESHOLD)) {
+    // The edge runs perpendicular to the direction the brightness changes in
+    vec2 direction = vec2(
+      -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+      (luma_nw + luma_sw) - (luma_ne + luma_se)
+    );
+    float direction_reduce =
+      max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float inverse_direction_min = 1.0 / (min(abs(direction.x), abs(direction.y)) + direction_reduce);
+    direction = clamp(direction * inverse_direction_min, -SPAN_MAX, SPAN_MAX) * texel;
+
+    // Average along the edge, falling back to a shorter span if the longer one crosses another
+    // edge
+    vec3 color_near = 0.5 * (
+      sample_offset(direction * (1.0 / 3.0 - 0.5)) + sample_offset(direction * (2.0 / 3.0 - 0.5))
+    );
+    vec3 color_far = color_near * 0.5 + 0.25 * (
+      sample_offset(direction * -0.5) + sample_offset(direction * 0.5)
+    );
+    float luma_far = luma(color_far);
+    color = (luma_far < luma_min || luma_far > luma_max) ? color_near : color_far;
+  }
+
+  if (settings.encode_srgb != 0u) {
+    color = linear_to_srgb(color);
+  }
+
+  f_color = vec4(color, 1.0);
+}
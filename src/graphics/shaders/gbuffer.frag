@@ -0,0 +1,48 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec3 v_position;
+layout(location = 1) in vec2 v_uv;
+layout(location = 2) in mat3 v_TBN;
+layout(location = 5) flat in uint v_receives_shadows;
+
+// Light emitted by the surface, written straight into the HDR image
+layout(location = 0) out vec4 f_emissive;
+// Base color, with ambient occlusion in alpha
+layout(location = 1) out vec4 f_albedo;
+// World space normal, with whether the surface receives shadows in w
+layout(location = 2) out vec4 f_normal;
+// Roughness in red and metalness in green
+layout(location = 3) out vec4 f_material;
+
+// Input buffer objects
+// ////////////////////
+
+#include "material.glsl"
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  // Material inputs
+  vec3 base_color = texture(diffuse_map, v_uv).rgb;
+  float metallic = clamp(texture(metallic_map, v_uv).r * material.metallic_factor, 0.0, 1.0);
+  float roughness = clamp(
+    texture(roughness_map, v_uv).r * material.roughness_factor,
+    MIN_ROUGHNESS,
+    1.0
+  );
+  float occlusion = texture(occlusion_map, v_uv).r;
+  vec3 emissive = texture(emissive_map, v_uv).rgb * material.emissive_factor;
+
+  // Surface normal from normal map and TBN
+  vec3 N = texture(normal_map, v_uv).xyz;
+  N = N * 2.0 - 1.0;
+  N = normalize(v_TBN * N);
+
+  // Lighting is left to the lighting pass, which reads the surface back from the G-buffer
+  f_emissive = vec4(emissive, 1.0);
+  f_albedo = vec4(base_color, occlusion);
+  f_normal = vec4(N, float(v_receives_shadows));
+  f_material = vec4(roughness, metallic, 0.0, 0.0);
+}
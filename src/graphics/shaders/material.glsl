@@ -0,0 +1,33 @@
+// Material data shared by the shaders which sample an object's material.
+
+// Configuration
+// /////////////
+// The smoothest surface shaded, as perfectly smooth surfaces have infinitely small highlights
+#define MIN_ROUGHNESS 0.04
+
+// Input buffer objects
+// ////////////////////
+
+// Data representing a material
+layout(set = 1, binding = 0) uniform MdrMaterialUniformData {
+  // Multiplies the emissive map
+  vec3 emissive_factor;
+  // Multiplies the metallic map
+  float metallic_factor;
+  // Multiplies the roughness map
+  float roughness_factor;
+} material;
+
+// Material texture maps
+// Base color of material
+layout(set = 1, binding = 1) uniform sampler2D diffuse_map;
+// Roughness map for material
+layout(set = 1, binding = 2) uniform sampler2D roughness_map;
+// Normal map for material
+layout(set = 1, binding = 3) uniform sampler2D normal_map;
+// Metalness map for material
+layout(set = 1, binding = 4) uniform sampler2D metallic_map;
+// Ambient occlusion map for material
+layout(set = 1, binding = 5) uniform sampler2D occlusion_map;
+// Emitted light map for material
+layout(set = 1, binding = 6) uniform sampler2D emissive_map;
@@ -1,9 +1,5 @@
 #version 450
 
-// Configuration
-// /////////////
-#define MAX_POINT_LIGHTS 10
-
 // Inputs/Ouputs
 // /////////////
 layout(location = 0) in vec3 a_position;
@@ -14,45 +10,20 @@ layout(location = 3) in vec3 a_tangent;
 layout(location = 0) out vec3 v_position;
 layout(location = 1) out vec2 v_uv;
 layout(location = 2) out mat3 v_TBN;
+layout(location = 5) flat out uint v_receives_shadows;
 
 // Input buffer objects
 // ////////////////////
 
-// Data representing a camera in the scene
-struct CameraData {
-  // Camera's position in world space
-  vec3 position;
-  // View transformation matrix
-  mat4 view;
-  // Perspective projection matrix
-  mat4 proj;
-};
-
-// Data representing a point light
-struct PointLightData {
-  // The RGB color of the light
-  vec3 color;
-  // The position of the light in world space
-  vec3 position;
-  // The brightness factor of the light
-  float brightness;
-};
-
-// Data representing the scene
-layout(set = 0, binding = 0) buffer MdrSceneData {
-  // The camera being used to render the scene
-  CameraData camera;
-  // Up to MAX_POINT_LIGHTS point light values
-  PointLightData point_lights[MAX_POINT_LIGHTS];
-  // Maximum point_light index with a valid value
-  uint point_light_count;
-} scene_data;
+#include "scene_data.glsl"
 
 // Push constant data containing an object's world transforms
 layout(push_constant) uniform MdrPushConstants
 {
   // The translation/rotation/scale of the current object
 	mat4 transformation_matrix;
+  // Whether shadows are drawn onto the object, 0 or 1
+  uint receives_shadows;
 } object;
 
 // Shader Entry Point
@@ -73,6 +44,7 @@ void main() {
   
   // Write output UVs
   v_uv = a_uv;
+  v_receives_shadows = object.receives_shadows;
   
   // Write output of vertex position
   v_position = world_position.xyz;
@@ -1,3 +1,6 @@
+// The code generated by `vulkano_shaders::shader!` wraps its results in `Ok(...?)`
+#![allow(clippy::needless_question_mark)]
+
 use std::sync::Arc;
 
 use vulkano::{
@@ -5,8 +8,11 @@ use vulkano::{
   shader::{ShaderCreationError, ShaderModule},
 };
 
+// Defines `shader_with_config!`, which build.rs generates from `config.rs`
+include!(concat!(env!("OUT_DIR"), "/shader_config.rs"));
+
 pub mod mesh_vertex_shader {
-  vulkano_shaders::shader! {
+  shader_with_config! {
     ty: "vertex",
     path: "src/graphics/shaders/mesh.vert",
     types_meta: {
@@ -18,7 +24,7 @@ pub mod mesh_vertex_shader {
 }
 
 pub mod mesh_fragment_shader {
-  vulkano_shaders::shader! {
+  shader_with_config! {
     ty: "fragment",
     path: "src/graphics/shaders/mesh.frag",
     types_meta: {
@@ -38,8 +44,54 @@ pub fn load_mesh_shaders(logical_device: &Arc<Device>) -> (Arc<ShaderModule>, Ar
   (vs, fs)
 }
 
-pub mod light_vertex_shader {
+pub mod gbuffer_fragment_shader {
   vulkano_shaders::shader! {
+    ty: "fragment",
+    path: "src/graphics/shaders/gbuffer.frag",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub fn load_gbuffer_shaders(
+  logical_device: &Arc<Device>,
+) -> (Arc<ShaderModule>, Arc<ShaderModule>) {
+  // Vertex shader
+  let vs = validate_load_result(mesh_vertex_shader::load(logical_device.clone()));
+  // Fragment shader
+  let fs = validate_load_result(gbuffer_fragment_shader::load(logical_device.clone()));
+
+  (vs, fs)
+}
+
+pub mod deferred_lighting_shader {
+  shader_with_config! {
+    ty: "fragment",
+    path: "src/graphics/shaders/deferred_lighting.frag",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub fn load_deferred_lighting_shaders(
+  logical_device: &Arc<Device>,
+) -> (Arc<ShaderModule>, Arc<ShaderModule>) {
+  // Vertex shader
+  let vs = load_fullscreen_shader(logical_device);
+  // Fragment shader
+  let fs = validate_load_result(deferred_lighting_shader::load(logical_device.clone()));
+
+  (vs, fs)
+}
+
+pub mod light_vertex_shader {
+  shader_with_config! {
     ty: "vertex",
     path: "src/graphics/shaders/light.vert",
     types_meta: {
@@ -57,6 +109,7 @@ pub mod light_fragment_shader {
   }
 }
 
+#[allow(dead_code)]
 pub fn load_light_shaders(logical_device: &Arc<Device>) -> (Arc<ShaderModule>, Arc<ShaderModule>) {
   // Vertex shader
   let vs = validate_load_result(light_vertex_shader::load(logical_device.clone()));
@@ -66,6 +119,263 @@ pub fn load_light_shaders(logical_device: &Arc<Device>) -> (Arc<ShaderModule>, A
   (vs, fs)
 }
 
+pub mod shadow_vertex_shader {
+  vulkano_shaders::shader! {
+    ty: "vertex",
+    path: "src/graphics/shaders/shadow.vert",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub mod shadow_fragment_shader {
+  vulkano_shaders::shader! {
+    ty: "fragment",
+    path: "src/graphics/shaders/shadow.frag",
+  }
+}
+
+pub fn load_shadow_shaders(logical_device: &Arc<Device>) -> (Arc<ShaderModule>, Arc<ShaderModule>) {
+  // Vertex shader
+  let vs = validate_load_result(shadow_vertex_shader::load(logical_device.clone()));
+  // Fragment shader
+  let fs = validate_load_result(shadow_fragment_shader::load(logical_device.clone()));
+
+  (vs, fs)
+}
+
+pub mod skybox_vertex_shader {
+  vulkano_shaders::shader! {
+    ty: "vertex",
+    path: "src/graphics/shaders/skybox.vert",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub mod skybox_fragment_shader {
+  vulkano_shaders::shader! {
+    ty: "fragment",
+    path: "src/graphics/shaders/skybox.frag",
+  }
+}
+
+pub fn load_skybox_shaders(logical_device: &Arc<Device>) -> (Arc<ShaderModule>, Arc<ShaderModule>) {
+  // Vertex shader
+  let vs = validate_load_result(skybox_vertex_shader::load(logical_device.clone()));
+  // Fragment shader
+  let fs = validate_load_result(skybox_fragment_shader::load(logical_device.clone()));
+
+  (vs, fs)
+}
+
+pub mod fullscreen_vertex_shader {
+  vulkano_shaders::shader! {
+    ty: "vertex",
+    path: "src/graphics/shaders/fullscreen.vert",
+  }
+}
+
+pub fn load_fullscreen_shader(logical_device: &Arc<Device>) -> Arc<ShaderModule> {
+  validate_load_result(fullscreen_vertex_shader::load(logical_device.clone()))
+}
+
+pub mod tonemap_fragment_shader {
+  vulkano_shaders::shader! {
+    ty: "fragment",
+    path: "src/graphics/shaders/tonemap.frag",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub mod bloom_downsample_shader {
+  vulkano_shaders::shader! {
+    ty: "fragment",
+    path: "src/graphics/shaders/bloom_downsample.frag",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub mod bloom_upsample_shader {
+  vulkano_shaders::shader! {
+    ty: "fragment",
+    path: "src/graphics/shaders/bloom_upsample.frag",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub mod fxaa_shader {
+  vulkano_shaders::shader! {
+    ty: "fragment",
+    path: "src/graphics/shaders/fxaa.frag",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub mod ssao_shader {
+  shader_with_config! {
+    ty: "fragment",
+    path: "src/graphics/shaders/ssao.frag",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub mod ssao_multisampled_shader {
+  shader_with_config! {
+    ty: "fragment",
+    path: "src/graphics/shaders/ssao.frag",
+    define: [("MULTISAMPLED_DEPTH", "1")],
+  }
+}
+
+pub mod ssao_blur_shader {
+  vulkano_shaders::shader! {
+    ty: "fragment",
+    path: "src/graphics/shaders/ssao_blur.frag",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub fn load_ssao_shaders(
+  logical_device: &Arc<Device>,
+  multisampled_depth: bool,
+) -> (Arc<ShaderModule>, Arc<ShaderModule>) {
+  // Occlusion shader, reading the first sample of multisampled depth buffers
+  let ssao = if multisampled_depth {
+    validate_load_result(ssao_multisampled_shader::load(logical_device.clone()))
+  } else {
+    validate_load_result(ssao_shader::load(logical_device.clone()))
+  };
+  // Blur shader, which also darkens the scene
+  let ssao_blur = validate_load_result(ssao_blur_shader::load(logical_device.clone()));
+
+  (ssao, ssao_blur)
+}
+
+pub fn load_post_processing_shaders(
+  logical_device: &Arc<Device>,
+) -> (
+  Arc<ShaderModule>,
+  Arc<ShaderModule>,
+  Arc<ShaderModule>,
+  Arc<ShaderModule>,
+) {
+  // Exposure, tone mapping, and color grading shader
+  let tonemap = validate_load_result(tonemap_fragment_shader::load(logical_device.clone()));
+  // Bloom threshold and downsampling shader
+  let bloom_downsample =
+    validate_load_result(bloom_downsample_shader::load(logical_device.clone()));
+  // Bloom upsampling shader
+  let bloom_upsample = validate_load_result(bloom_upsample_shader::load(logical_device.clone()));
+  // Anti-aliasing shader
+  let fxaa = validate_load_result(fxaa_shader::load(logical_device.clone()));
+
+  (tonemap, bloom_downsample, bloom_upsample, fxaa)
+}
+
+pub mod light_culling_shader {
+  shader_with_config! {
+    ty: "compute",
+    path: "src/graphics/shaders/light_culling.comp",
+  }
+}
+
+pub fn load_light_culling_shader(logical_device: &Arc<Device>) -> Arc<ShaderModule> {
+  validate_load_result(light_culling_shader::load(logical_device.clone()))
+}
+
+pub mod equirect_to_cube_shader {
+  vulkano_shaders::shader! {
+    ty: "compute",
+    path: "src/graphics/shaders/equirect_to_cube.comp",
+  }
+}
+
+pub mod faces_to_cube_shader {
+  vulkano_shaders::shader! {
+    ty: "compute",
+    path: "src/graphics/shaders/faces_to_cube.comp",
+  }
+}
+
+pub mod irradiance_shader {
+  vulkano_shaders::shader! {
+    ty: "compute",
+    path: "src/graphics/shaders/irradiance.comp",
+  }
+}
+
+pub mod prefilter_shader {
+  vulkano_shaders::shader! {
+    ty: "compute",
+    path: "src/graphics/shaders/prefilter.comp",
+    types_meta: {
+      use bytemuck::{Pod, Zeroable};
+
+      #[derive(Clone, Copy, Zeroable, Pod)]
+    },
+  }
+}
+
+pub mod brdf_lut_shader {
+  vulkano_shaders::shader! {
+    ty: "compute",
+    path: "src/graphics/shaders/brdf_lut.comp",
+  }
+}
+
+pub fn load_environment_shaders(logical_device: &Arc<Device>) -> [Arc<ShaderModule>; 5] {
+  // Equirectangular to cubemap conversion shader
+  let equirect_to_cube =
+    validate_load_result(equirect_to_cube_shader::load(logical_device.clone()));
+  // Cubemap face copying shader
+  let faces_to_cube = validate_load_result(faces_to_cube_shader::load(logical_device.clone()));
+  // Diffuse irradiance convolution shader
+  let irradiance = validate_load_result(irradiance_shader::load(logical_device.clone()));
+  // Specular prefiltering shader
+  let prefilter = validate_load_result(prefilter_shader::load(logical_device.clone()));
+  // BRDF lookup table shader
+  let brdf_lut = validate_load_result(brdf_lut_shader::load(logical_device.clone()));
+
+  [
+    equirect_to_cube,
+    faces_to_cube,
+    irradiance,
+    prefilter,
+    brdf_lut,
+  ]
+}
+
 fn validate_load_result(
   output: Result<Arc<ShaderModule>, ShaderCreationError>,
 ) -> Arc<ShaderModule> {
@@ -0,0 +1,120 @@
+// Scene data shared by every shader which reads the camera or the lights. CLUSTER_GRID_*,
+// MAX_LIGHTS_PER_CLUSTER, and SHADOW_CASCADE_COUNT are defined from `config.rs` by
+// `shader_with_config!`. Define WRITE_CLUSTERS before including this to make the cluster buffer
+// writable.
+
+// Configuration
+// /////////////
+#define CLUSTER_COUNT (CLUSTER_GRID_X * CLUSTER_GRID_Y * CLUSTER_GRID_Z)
+
+#define LIGHT_KIND_POINT 0u
+#define LIGHT_KIND_DIRECTIONAL 1u
+#define LIGHT_KIND_SPOT 2u
+
+#define DEBUG_VIEW_NONE 0u
+#define DEBUG_VIEW_SHADOW_CASCADES 1u
+
+// Input buffer objects
+// ////////////////////
+
+// Data representing a camera in the scene
+struct CameraData {
+  // Camera's position in world space
+  vec3 position;
+  // View transformation matrix
+  mat4 view;
+  // Projection matrix
+  mat4 proj;
+};
+
+// Data representing a light
+struct LightData {
+  // The RGB color of the light
+  vec3 color;
+  // The type of light, one of the LIGHT_KIND_* values
+  uint kind;
+  // The position of the light in world space, unused by directional lights
+  vec3 position;
+  // The brightness factor of the light
+  float brightness;
+  // The normalized direction the light shines in world space, unused by point lights
+  vec3 direction;
+  // Cosine of the angle from the direction within which a spot light is at full brightness
+  float cos_inner_cone;
+  // Cosine of the angle from the direction beyond which a spot light has no effect
+  float cos_outer_cone;
+  // Distance at which point and spot lights fade out completely, may be infinite
+  float range;
+  // Index of the light's first matrix in the shadow data, or -1 if it casts no shadows. Point
+  // lights have six consecutive shadow maps, one for each face of a cube, and directional
+  // lights have one for each cascade
+  int shadow_index;
+  // Pads the struct to a multiple of 16 bytes
+  float _padding;
+};
+
+// Data representing the scene
+layout(set = 0, binding = 0) readonly buffer MdrSceneData {
+  // The camera being used to render the scene
+  CameraData camera;
+  // The view space depth at which each directional light shadow cascade ends
+  vec4 cascade_splits;
+  // Size of the render target in pixels
+  vec2 screen_size;
+  // Distances to the camera's clipping planes, which bound the light clusters
+  float near_plane;
+  float far_plane;
+  // Number of values in the light buffer
+  uint light_count;
+  // What to draw instead of the shaded scene, one of the DEBUG_VIEW_* values
+  uint debug_view;
+} scene_data;
+
+// Every light in the scene
+layout(set = 0, binding = 1) readonly buffer MdrLightBuffer {
+  LightData lights[];
+} light_buffer;
+
+// The lights affecting each cluster. The first CLUSTER_COUNT values are the number of lights
+// in each cluster, followed by MAX_LIGHTS_PER_CLUSTER light indices for each cluster.
+layout(set = 0, binding = 2)
+#ifndef WRITE_CLUSTERS
+readonly
+#endif
+buffer MdrClusterLights {
+  uint cluster_lights[];
+} cluster_data;
+
+// Cluster helpers
+// ///////////////
+
+// Returns the view space depth at which cluster slice `slice` begins. Slices are spaced
+// exponentially so that clusters near the camera are as deep as they are wide.
+float cluster_slice_depth(uint slice) {
+  float depth_ratio = scene_data.far_plane / scene_data.near_plane;
+  return scene_data.near_plane * pow(depth_ratio, float(slice) / float(CLUSTER_GRID_Z));
+}
+
+// Returns the index of the cluster containing a fragment at `frag_coord` in pixels and
+// `view_depth` in front of the camera.
+uint cluster_index(vec2 frag_coord, float view_depth) {
+  uvec2 tile = uvec2(frag_coord / scene_data.screen_size * vec2(CLUSTER_GRID_X, CLUSTER_GRID_Y));
+  tile = min(tile, uvec2(CLUSTER_GRID_X - 1, CLUSTER_GRID_Y - 1));
+
+  float depth_ratio = scene_data.far_plane / scene_data.near_plane;
+  float slice = log(max(view_depth, scene_data.near_plane) / scene_data.near_plane)
+    / log(depth_ratio) * float(CLUSTER_GRID_Z);
+  uint z = min(uint(slice), CLUSTER_GRID_Z - 1);
+
+  return tile.x + tile.y * CLUSTER_GRID_X + z * CLUSTER_GRID_X * CLUSTER_GRID_Y;
+}
+
+// Returns the number of lights affecting the cluster `cluster`.
+uint cluster_light_count(uint cluster) {
+  return min(cluster_data.cluster_lights[cluster], MAX_LIGHTS_PER_CLUSTER);
+}
+
+// Returns the index into the light buffer of the `i`th light affecting cluster `cluster`.
+uint cluster_light_index(uint cluster, uint i) {
+  return cluster_data.cluster_lights[CLUSTER_COUNT + cluster * MAX_LIGHTS_PER_CLUSTER + i];
+}
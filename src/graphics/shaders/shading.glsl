@@ -0,0 +1,277 @@
+// Lighting shared by the shaders which shade surfaces, from the scene's lights, their shadows,
+// and its environment. Include `scene_data.glsl` before this.
+
+// Configuration
+// /////////////
+#define PI 3.14159265359
+// Reflectance at normal incidence of non-metals
+#define DIELECTRIC_F0 0.04
+// World space distance surfaces are pushed along their normal before shadow lookups at grazing
+// angles, which stops them from shadowing themselves
+#define SHADOW_NORMAL_OFFSET 0.02
+// The same offset for directional light cascades, in texels of the cascade's shadow map
+#define CASCADE_NORMAL_OFFSET_TEXELS 1.5
+// Fraction of each cascade at its far end which blends into the next cascade
+#define CASCADE_BLEND_FRACTION 0.1
+// Shadow map texels sampled in each direction for percentage-closer filtering
+#define SHADOW_PCF_RADIUS 1
+
+// Input buffer objects
+// ////////////////////
+
+// Depth maps rendered from each shadow-casting light
+layout(set = 0, binding = 3) uniform sampler2DArrayShadow shadow_maps;
+
+// The view and projection of each shadow map, mapping world space into its clip space
+layout(set = 0, binding = 4) readonly buffer MdrShadowData {
+  mat4 matrices[];
+} shadow_data;
+
+// Image-based lighting maps of the scene's environment
+// The light reaching a surface facing each direction, divided by PI
+layout(set = 0, binding = 5) uniform samplerCube irradiance_map;
+// The environment reflected by surfaces of increasing roughness in each mip level
+layout(set = 0, binding = 6) uniform samplerCube prefiltered_map;
+// The scale and bias applied to F0 by the specular BRDF, by view angle and roughness
+layout(set = 0, binding = 7) uniform sampler2D brdf_lut;
+
+// Returns the fraction of a light with `range` reaching a surface at `distance`, fading
+// smoothly to zero at the range.
+float range_attenuation(float distance, float range) {
+  float ratio = distance / range;
+  float window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+  return window * window;
+}
+
+// Returns the GGX (Trowbridge-Reitz) normal distribution, the fraction of microfacets of a
+// surface with `roughness` which face along the half vector `H`.
+float distribution_ggx(vec3 N, vec3 H, float roughness) {
+  float alpha = roughness * roughness;
+  float alpha_squared = alpha * alpha;
+  float n_dot_h = max(dot(N, H), 0.0);
+  float denominator = n_dot_h * n_dot_h * (alpha_squared - 1.0) + 1.0;
+  return alpha_squared / (PI * denominator * denominator);
+}
+
+// Returns the Schlick-GGX approximation of the fraction of microfacets visible along a
+// direction at cosine `n_dot_x` to the normal.
+float geometry_schlick_ggx(float n_dot_x, float roughness) {
+  float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+  return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+// Returns the Smith geometry term, the fraction of microfacets which are neither shadowed from
+// the light nor masked from the viewer.
+float geometry_smith(float n_dot_v, float n_dot_l, float roughness) {
+  return geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+}
+
+// Returns the Schlick approximation of the fraction of light reflected by a surface with
+// normal incidence reflectance `F0`, at cosine `cos_theta` between the view and half vectors.
+vec3 fresnel_schlick(float cos_theta, vec3 F0) {
+  return F0 + (1.0 - F0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// Returns the Schlick approximation of Fresnel reflectance averaged over the microfacets of a
+// surface with `roughness`, for light from the whole environment rather than one direction.
+vec3 fresnel_schlick_roughness(float cos_theta, vec3 F0, float roughness) {
+  vec3 max_reflectance = max(vec3(1.0 - roughness), F0);
+  return F0 + (max_reflectance - F0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// Returns the light reflected towards the viewer along `V` from the scene's environment, by a
+// surface with normal `N`.
+vec3 environment_lighting(vec3 N, vec3 V, float n_dot_v, vec3 base_color, float metallic,
+  float roughness, vec3 F0) {
+  vec3 F = fresnel_schlick_roughness(n_dot_v, F0, roughness);
+
+  // Diffuse reflection of the light arriving from the hemisphere around the normal
+  vec3 diffuse = (1.0 - F) * (1.0 - metallic) * texture(irradiance_map, N).rgb * base_color;
+
+  // Specular reflection, split into the environment blurred by the surface's roughness and the
+  // response of the BRDF
+  vec3 R = reflect(-V, N);
+  float lod = roughness * float(textureQueryLevels(prefiltered_map) - 1);
+  vec3 prefiltered = textureLod(prefiltered_map, R, lod).rgb;
+  vec2 brdf = texture(brdf_lut, vec2(n_dot_v, roughness)).rg;
+  vec3 specular = prefiltered * (F * brdf.x + brdf.y);
+
+  return diffuse + specular;
+}
+
+// Returns which of a point light's six shadow maps covers `offset` from the light, in the order
+// +x, -x, +y, -y, +z, -z.
+uint cube_face(vec3 offset) {
+  vec3 magnitude = abs(offset);
+  if (magnitude.x >= magnitude.y && magnitude.x >= magnitude.z) {
+    return offset.x >= 0.0 ? 0u : 1u;
+  } else if (magnitude.y >= magnitude.z) {
+    return offset.y >= 0.0 ? 2u : 3u;
+  }
+  return offset.z >= 0.0 ? 4u : 5u;
+}
+
+// Returns which directional light shadow cascade covers `view_depth`, or SHADOW_CASCADE_COUNT
+// if it is beyond all of them.
+uint shadow_cascade(float view_depth) {
+  for (uint i = 0; i < SHADOW_CASCADE_COUNT; i++) {
+    if (view_depth < scene_data.cascade_splits[i]) {
+      return i;
+    }
+  }
+  return SHADOW_CASCADE_COUNT;
+}
+
+// Returns the world space size of a texel of the orthographic shadow map `layer`.
+float cascade_texel_size(uint layer) {
+  mat4 matrix = shadow_data.matrices[layer];
+  float world_to_clip = length(vec3(matrix[0][0], matrix[1][0], matrix[2][0]));
+  return 2.0 / (world_to_clip * float(textureSize(shadow_maps, 0).x));
+}
+
+// Returns the fraction of the surface at `position` which is lit in shadow map `layer`, for a
+// surface with normal `N` facing the light along `L`, pushed `normal_offset` along its normal.
+float sample_shadow_map(uint layer, vec3 position, vec3 N, vec3 L, float normal_offset) {
+  // Project the surface into the shadow map
+  normal_offset *= 1.0 - max(dot(N, L), 0.0);
+  vec4 shadow_position = shadow_data.matrices[layer] * vec4(position + N * normal_offset, 1.0);
+  vec3 shadow_coord = shadow_position.xyz / shadow_position.w;
+  // Surfaces beyond the far plane of the shadow map can't be shadowed
+  if (shadow_coord.z > 1.0) {
+    return 1.0;
+  }
+  vec2 uv = shadow_coord.xy * 0.5 + 0.5;
+
+  // Average the depth comparisons of the surrounding texels to soften the shadow's edges
+  vec2 texel_size = 1.0 / vec2(textureSize(shadow_maps, 0).xy);
+  float lit = 0.0;
+  for (int x = -SHADOW_PCF_RADIUS; x <= SHADOW_PCF_RADIUS; x++) {
+    for (int y = -SHADOW_PCF_RADIUS; y <= SHADOW_PCF_RADIUS; y++) {
+      vec2 sample_uv = uv + vec2(x, y) * texel_size;
+      lit += texture(shadow_maps, vec4(sample_uv, float(layer), shadow_coord.z));
+    }
+  }
+  float sample_count = float((2 * SHADOW_PCF_RADIUS + 1) * (2 * SHADOW_PCF_RADIUS + 1));
+  return lit / sample_count;
+}
+
+// Returns the fraction of `light` which isn't blocked by shadow casters, for a surface at
+// `position`, `view_depth` in front of the camera, with normal `N` facing the light along `L`.
+float shadow_factor(LightData light, vec3 position, vec3 N, vec3 L, float view_depth) {
+  if (light.shadow_index < 0) {
+    return 1.0;
+  }
+  uint layer = uint(light.shadow_index);
+
+  if (light.kind == LIGHT_KIND_POINT) {
+    layer += cube_face(position - light.position);
+    return sample_shadow_map(layer, position, N, L, SHADOW_NORMAL_OFFSET);
+  } else if (light.kind == LIGHT_KIND_SPOT) {
+    return sample_shadow_map(layer, position, N, L, SHADOW_NORMAL_OFFSET);
+  }
+
+  // Directional lights have a cascade for each range of depths
+  uint cascade = shadow_cascade(view_depth);
+  if (cascade >= SHADOW_CASCADE_COUNT) {
+    return 1.0;
+  }
+  float normal_offset = CASCADE_NORMAL_OFFSET_TEXELS * cascade_texel_size(layer + cascade);
+  float lit = sample_shadow_map(layer + cascade, position, N, L, normal_offset);
+
+  // Fade into the next cascade towards the end of this one, hiding the seam between them
+  float cascade_start = cascade == 0u
+    ? scene_data.near_plane
+    : scene_data.cascade_splits[cascade - 1u];
+  float cascade_end = scene_data.cascade_splits[cascade];
+  float blend_start = mix(cascade_end, cascade_start, CASCADE_BLEND_FRACTION);
+  if (cascade + 1u < SHADOW_CASCADE_COUNT && view_depth > blend_start) {
+    uint next_layer = layer + cascade + 1u;
+    float next_offset = CASCADE_NORMAL_OFFSET_TEXELS * cascade_texel_size(next_layer);
+    float next_lit = sample_shadow_map(next_layer, position, N, L, next_offset);
+    lit = mix(lit, next_lit, (view_depth - blend_start) / (cascade_end - blend_start));
+  }
+
+  return lit;
+}
+
+// Returns the color the debug view of the shadow cascades tints the cascade covering
+// `view_depth`. Cascades get hues spread evenly around the color wheel.
+vec3 cascade_debug_color(float view_depth) {
+  uint cascade = shadow_cascade(view_depth);
+  // Beyond every cascade
+  if (cascade >= SHADOW_CASCADE_COUNT) {
+    return vec3(0.5, 0.5, 0.5);
+  }
+
+  float hue = float(cascade) / float(SHADOW_CASCADE_COUNT);
+  vec3 rgb = clamp(abs(fract(hue + vec3(0.0, 2.0, 1.0) / 3.0) * 6.0 - 3.0) - 1.0, 0.0, 1.0);
+  return mix(vec3(0.2), vec3(1.0), rgb);
+}
+
+// Returns the light reflected towards the camera by the surface at world space `position` and
+// pixel `frag_coord`, `view_depth` in front of the camera, from the lights of its cluster and
+// the environment. Emitted light isn't included.
+vec3 shade_surface(vec3 position, vec2 frag_coord, float view_depth, vec3 N, vec3 base_color,
+  float metallic, float roughness, float occlusion, bool receives_shadows) {
+  vec3 V = normalize(scene_data.camera.position - position);
+  float n_dot_v = max(dot(N, V), 1e-4);
+
+  // Metals reflect their base color and have no diffuse reflection
+  vec3 F0 = mix(vec3(DIELECTRIC_F0), base_color, metallic);
+
+  // Find the cluster this surface is in, which lists the lights which can reach it
+  uint cluster = cluster_index(frag_coord, view_depth);
+
+  // Loop over the cluster's lights, accumulating the result
+  vec3 result = vec3(0.0);
+  uint cluster_light_total = cluster_light_count(cluster);
+  for (uint i = 0; i < cluster_light_total; i++) {
+    LightData light = light_buffer.lights[cluster_light_index(cluster, i)];
+
+    // Direction towards the light, and how much of the light reaches this surface
+    vec3 L;
+    float attenuation = 1.0;
+    float intensity = 1.0;
+    if (light.kind == LIGHT_KIND_DIRECTIONAL) {
+      L = -light.direction;
+    } else {
+      vec3 to_light = light.position - position;
+      L = normalize(to_light);
+      attenuation = range_attenuation(length(to_light), light.range);
+    }
+    if (light.kind == LIGHT_KIND_SPOT) {
+      // Fade from full brightness at the inner cone to nothing at the outer cone
+      float cos_angle = dot(-L, light.direction);
+      intensity = smoothstep(light.cos_outer_cone, light.cos_inner_cone, cos_angle);
+    }
+
+    float n_dot_l = max(dot(N, L), 0.0);
+    if (n_dot_l <= 0.0) {
+      continue;
+    }
+
+    // Shadows block the light's direct contribution
+    if (receives_shadows) {
+      intensity *= shadow_factor(light, position, N, L, view_depth);
+    }
+
+    // Cook-Torrance specular reflection
+    vec3 H = normalize(L + V);
+    vec3 F = fresnel_schlick(max(dot(H, V), 0.0), F0);
+    float D = distribution_ggx(N, H, roughness);
+    float G = geometry_smith(n_dot_v, n_dot_l, roughness);
+    vec3 specular = D * G * F / (4.0 * n_dot_v * n_dot_l);
+
+    // Light which isn't reflected is diffused, except by metals
+    vec3 diffuse = (1.0 - F) * (1.0 - metallic) * base_color / PI;
+
+    // Scaled by PI so that a light of brightness 1 lights a white surface facing it fully
+    vec3 radiance = light.color * light.brightness * attenuation * intensity * PI;
+    result += (diffuse + specular) * radiance * n_dot_l;
+  }
+
+  // Light from the environment reaches creases less easily
+  result += environment_lighting(N, V, n_dot_v, base_color, metallic, roughness, F0) * occlusion;
+
+  return result;
+}
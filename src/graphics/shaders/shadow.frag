@@ -0,0 +1,8 @@
+#version 450
+
+// Shadow maps only store depth, which is written by the fixed function pipeline
+
+// Shader Entry Point
+// //////////////////
+void main() {
+}
@@ -0,0 +1,21 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec3 a_position;
+
+// Input buffer objects
+// ////////////////////
+
+// Push constant data containing an object's transform into the shadow map
+layout(push_constant) uniform MdrShadowPushConstants
+{
+  // The object's world transform followed by the light's view and projection
+  mat4 light_transformation_matrix;
+} object;
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  gl_Position = object.light_transformation_matrix * vec4(a_position, 1.0);
+}
@@ -0,0 +1,21 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec4 v_direction;
+
+layout(location = 0) out vec4 f_color;
+
+// Input buffer objects
+// ////////////////////
+
+// The environment drawn as the skybox
+layout(set = 0, binding = 0) uniform samplerCube environment_map;
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  vec3 direction = v_direction.xyz / v_direction.w;
+  vec3 color = textureLod(environment_map, direction, 0.0).rgb;
+  f_color = vec4(color, 1.0);
+}
@@ -0,0 +1,28 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+
+// The homogeneous world space direction each corner of the screen looks in
+layout(location = 0) out vec4 v_direction;
+
+// Input buffer objects
+// ////////////////////
+
+// Push constant data containing the camera's transforms
+layout(push_constant) uniform MdrSkyboxPushConstants
+{
+  // The inverse of the camera's view and projection, without the view's translation
+  mat4 inverse_view_projection;
+} camera;
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  // One triangle covering the whole screen, with no vertex buffer
+  vec2 position = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2) * 2.0 - 1.0;
+
+  // The skybox lies on the far plane, so it is only drawn where no object has been
+  gl_Position = vec4(position, 1.0, 1.0);
+  v_direction = camera.inverse_view_projection * vec4(position, 1.0, 1.0);
+}
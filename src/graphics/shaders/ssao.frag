@@ -0,0 +1,153 @@
+#version 450
+
+// Configuration
+// /////////////
+#define PI 3.14159265359
+// SSAO_KERNEL_SIZE is defined from `config.rs` by `shader_with_config!`
+// Define MULTISAMPLED_DEPTH when the depth buffer has more than one sample per pixel, in which
+// case the first sample of each pixel is read
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec2 v_uv;
+
+// The fraction of the ambient light reaching each pixel, in red
+layout(location = 0) out vec4 f_occlusion;
+
+// Input buffer objects
+// ////////////////////
+
+// The depth buffer the scene was drawn with
+#ifdef MULTISAMPLED_DEPTH
+layout(set = 0, binding = 0) uniform sampler2DMS depth_image;
+#else
+layout(set = 0, binding = 0) uniform sampler2D depth_image;
+#endif
+// World space normals in xyz, only read when `use_normals` is set
+layout(set = 0, binding = 1) uniform sampler2D normal_image;
+
+// The camera's transforms and the occlusion settings
+layout(set = 0, binding = 2) uniform MdrSsaoData {
+  // The camera's projection, and its inverse mapping clip space into view space
+  mat4 projection;
+  mat4 inverse_projection;
+  // The camera's view, for moving world space normals into view space
+  mat4 view;
+  // Offsets within the unit hemisphere around +z, denser towards the center
+  vec4 kernel[SSAO_KERNEL_SIZE];
+  // View space distance searched for occluders
+  float radius;
+  // Depth difference below which geometry doesn't occlude
+  float bias;
+  // Whether `normal_image` holds the scene's normals, otherwise they are found from the depths
+  uint use_normals;
+} ssao;
+
+// Helpers
+// ///////
+
+// Returns the size of the depth buffer in texels.
+ivec2 depth_size() {
+#ifdef MULTISAMPLED_DEPTH
+  return textureSize(depth_image);
+#else
+  return textureSize(depth_image, 0);
+#endif
+}
+
+// Returns the view space position of the surface drawn at `uv`, at `depth` in the depth buffer.
+vec3 view_position(vec2 uv, float depth) {
+  vec4 position = ssao.inverse_projection * vec4(uv * 2.0 - 1.0, depth, 1.0);
+  return position.xyz / position.w;
+}
+
+// Returns the view space position of the surface drawn at `texel`.
+vec3 texel_position(ivec2 texel) {
+  vec2 uv = (vec2(texel) + 0.5) / vec2(depth_size());
+  return view_position(uv, texelFetch(depth_image, texel, 0).r);
+}
+
+// Returns the view space normal of the surface at `position`, drawn at `texel`, from the slope
+// of its neighbors' depths. The neighbor closest in depth is used on each axis, so that normals
+// don't bend across the edges of objects.
+vec3 reconstruct_normal(ivec2 texel, vec3 position) {
+  ivec2 last_texel = depth_size() - 1;
+  vec3 right = texel_position(min(texel + ivec2(1, 0), last_texel));
+  vec3 left = texel_position(max(texel - ivec2(1, 0), ivec2(0)));
+  vec3 below = texel_position(min(texel + ivec2(0, 1), last_texel));
+  vec3 above = texel_position(max(texel - ivec2(0, 1), ivec2(0)));
+
+  vec3 dx = abs(right.z - position.z) < abs(position.z - left.z)
+    ? right - position
+    : position - left;
+  vec3 dy = abs(below.z - position.z) < abs(position.z - above.z)
+    ? below - position
+    : position - above;
+
+  // Face the normal towards the camera, which is at the origin
+  vec3 normal = normalize(cross(dx, dy));
+  return dot(normal, position) > 0.0 ? -normal : normal;
+}
+
+// Returns a value from 0 to 1 which repeats every 4x4 pixels, visiting every sixteenth of the
+// range once in an order which spreads neighboring values apart.
+float noise(ivec2 texel) {
+  const float bayer[16] = float[](
+    0.0, 8.0, 2.0, 10.0,
+    12.0, 4.0, 14.0, 6.0,
+    3.0, 11.0, 1.0, 9.0,
+    15.0, 7.0, 13.0, 5.0
+  );
+  ivec2 cell = texel & 3;
+  return bayer[cell.y * 4 + cell.x] / 16.0;
+}
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  ivec2 texel = ivec2(gl_FragCoord.xy);
+
+  // Nothing occludes the background
+  float depth = texelFetch(depth_image, texel, 0).r;
+  if (depth >= 1.0) {
+    f_occlusion = vec4(1.0);
+    return;
+  }
+  vec3 position = view_position(v_uv, depth);
+
+  vec3 N;
+  if (ssao.use_normals != 0u) {
+    N = normalize(mat3(ssao.view) * texelFetch(normal_image, texel, 0).xyz);
+  } else {
+    N = reconstruct_normal(texel, position);
+  }
+
+  // Turn the kernel around the normal by an angle which repeats every 4x4 pixels, trading
+  // banding for noise which the blur pass averages away
+  float angle = noise(texel) * 2.0 * PI;
+  vec3 rotation = vec3(cos(angle), sin(angle), 0.0);
+  vec3 T = normalize(rotation - N * dot(rotation, N));
+  vec3 B = cross(N, T);
+  mat3 TBN = mat3(T, B, N);
+
+  // Count the kernel's samples which lie behind the surface drawn where they land
+  float occlusion = 0.0;
+  for (int i = 0; i < SSAO_KERNEL_SIZE; i++) {
+    vec3 sample_position = position + TBN * ssao.kernel[i].xyz * ssao.radius;
+    vec4 sample_clip = ssao.projection * vec4(sample_position, 1.0);
+    vec2 sample_uv = sample_clip.xy / sample_clip.w * 0.5 + 0.5;
+    if (any(lessThan(sample_uv, vec2(0.0))) || any(greaterThan(sample_uv, vec2(1.0)))) {
+      continue;
+    }
+
+    ivec2 sample_texel = min(ivec2(sample_uv * vec2(depth_size())), depth_size() - 1);
+    float surface_z = texel_position(sample_texel).z;
+
+    // The camera looks along -z, so nearer surfaces have greater z. Surfaces much nearer than
+    // the radius are separate objects in front, and fade out of the count
+    float in_range = smoothstep(0.0, 1.0, ssao.radius / abs(position.z - surface_z));
+    occlusion += (surface_z >= sample_position.z + ssao.bias ? 1.0 : 0.0) * in_range;
+  }
+
+  f_occlusion = vec4(1.0 - occlusion / float(SSAO_KERNEL_SIZE));
+}
@@ -0,0 +1,42 @@
+#version 450
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+// Input buffer objects
+// ////////////////////
+
+// The scene's linear, unbounded colors
+layout(set = 0, binding = 0) uniform sampler2D hdr_image;
+// The noisy ambient occlusion written by `ssao.frag`
+layout(set = 0, binding = 1) uniform sampler2D occlusion_image;
+
+// Push constant data containing the occlusion settings
+layout(push_constant) uniform MdrSsaoBlurPushConstants
+{
+  // How much the occlusion darkens the scene, from 0 to 1
+  float intensity;
+} settings;
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  ivec2 texel = ivec2(gl_FragCoord.xy);
+  ivec2 last_texel = textureSize(occlusion_image, 0) - 1;
+
+  // Average a 4x4 block, which covers each rotation of the occlusion kernel exactly once
+  float occlusion = 0.0;
+  for (int x = -2; x < 2; x++) {
+    for (int y = -2; y < 2; y++) {
+      ivec2 sample_texel = clamp(texel + ivec2(x, y), ivec2(0), last_texel);
+      occlusion += texelFetch(occlusion_image, sample_texel, 0).r;
+    }
+  }
+  occlusion /= 16.0;
+
+  vec3 color = texelFetch(hdr_image, texel, 0).rgb;
+  f_color = vec4(color * mix(1.0, occlusion, settings.intensity), 1.0);
+}
@@ -0,0 +1,189 @@
+#version 450
+
+// Configuration
+// /////////////
+// Must match the `TONEMAPPER_*` values in `post_processing.rs`
+#define TONEMAPPER_ACES 0u
+#define TONEMAPPER_REINHARD 1u
+#define TONEMAPPER_AGX 2u
+
+// Inputs/Ouputs
+// /////////////
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+// Input buffer objects
+// ////////////////////
+
+// The scene's linear, unbounded colors
+layout(set = 0, binding = 0) uniform sampler2D hdr_image;
+// The glow around the scene's brightest parts, at half the size of the scene
+layout(set = 0, binding = 1) uniform sampler2D bloom_image;
+// The color grading lookup table, indexed by sRGB encoded colors
+layout(set = 0, binding = 2) uniform sampler3D color_lut;
+
+// Push constant data containing the camera's exposure settings and the effects applied with
+// tone mapping
+layout(push_constant) uniform MdrTonemapPushConstants
+{
+  // Factor the scene's colors are multiplied by before tone mapping
+  float exposure;
+  // The curve mapping colors into the displayable range, one of the TONEMAPPER_* values
+  uint tonemapper;
+  // Whether the output must be gamma encoded here, as the target's format is not sRGB
+  uint encode_srgb;
+  // Factor the bloom is multiplied by before it is added, 0 when bloom is off
+  float bloom_intensity;
+  // How much of the graded color is used, 0 when there is no lookup table
+  float lut_intensity;
+  // How dark the corners become, 0 when the vignette is off
+  float vignette_intensity;
+  // Distance from the center at which the vignette begins, and over which it fades in
+  float vignette_radius;
+  float vignette_smoothness;
+} settings;
+
+// Tone mapping curves
+// ///////////////////
+
+// Stephen Hill's fit of the ACES reference rendering and output transforms. The matrices
+// convert between linear sRGB and the space the fit is made in
+vec3 tonemap_aces(vec3 color) {
+  const mat3 input_matrix = mat3(
+    0.59719, 0.07600, 0.02840,
+    0.35458, 0.90834, 0.13383,
+    0.04823, 0.01566, 0.83777
+  );
+  const mat3 output_matrix = mat3(
+    1.60475, -0.10208, -0.00327,
+    -0.53108, 1.10813, -0.07276,
+    -0.07367, -0.00605, 1.07602
+  );
+
+  color = input_matrix * color;
+  vec3 a = color * (color + 0.0245786) - 0.000090537;
+  vec3 b = color * (0.983729 * color + 0.4329510) + 0.238081;
+  color = output_matrix * (a / b);
+
+  return clamp(color, 0.0, 1.0);
+}
+
+// Reinhard's curve, which compresses each channel towards 1 without ever reaching it
+vec3 tonemap_reinhard(vec3 color) {
+  return color / (1.0 + color);
+}
+
+// Polynomial fit of the AgX base contrast curve, for values in the range 0 to 1
+vec3 agx_contrast(vec3 x) {
+  vec3 x2 = x * x;
+  vec3 x4 = x2 * x2;
+
+  return 15.5 * x4 * x2
+    - 40.14 * x4 * x
+    + 31.96 * x4
+    - 6.868 * x2 * x
+    + 0.4298 * x2
+    + 0.1191 * x
+    - 0.00232;
+}
+
+// Troy Sobotka's AgX, which desaturates bright colors towards white rather than letting them
+// skew in hue
+vec3 tonemap_agx(vec3 color) {
+  const mat3 inset_matrix = mat3(
+    0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+    0.0784335999999992, 0.878468636469772, 0.0784336,
+    0.0792237451477643, 0.0791661274605434, 0.879142973793104
+  );
+  const mat3 outset_matrix = mat3(
+    1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+    -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+    -0.0990297440797205, -0.0989611768448433, 1.15107367264116
+  );
+  // The range of exposures around middle grey which the curve covers
+  const float min_ev = -12.47393;
+  const float max_ev = 4.026069;
+
+  color = inset_matrix * color;
+  color = clamp(log2(max(color, vec3(1e-10))), min_ev, max_ev);
+  color = agx_contrast((color - min_ev) / (max_ev - min_ev));
+  color = outset_matrix * color;
+
+  // The curve produces display encoded colors, which are decoded as the target encodes them again
+  return pow(clamp(color, 0.0, 1.0), vec3(2.2));
+}
+
+// sRGB transfer functions
+// //////////////////////
+
+// Encodes a linear color with the sRGB transfer function
+vec3 linear_to_srgb(vec3 color) {
+  vec3 low = color * 12.92;
+  vec3 high = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+
+  return mix(high, low, lessThanEqual(color, vec3(0.0031308)));
+}
+
+// Decodes an sRGB encoded color into a linear one
+vec3 srgb_to_linear(vec3 color) {
+  vec3 low = color / 12.92;
+  vec3 high = pow((color + 0.055) / 1.055, vec3(2.4));
+
+  return mix(high, low, lessThanEqual(color, vec3(0.04045)));
+}
+
+// Effects
+// ///////
+
+// Looks up a tone mapped color in the color grading table, which like most grading tools works
+// on sRGB encoded colors
+vec3 grade(vec3 color) {
+  float lut_size = float(textureSize(color_lut, 0).x);
+  // Sample the centers of the first and last texels at 0 and 1
+  vec3 lut_coordinates = linear_to_srgb(color) * (lut_size - 1.0) / lut_size + 0.5 / lut_size;
+  vec3 graded = srgb_to_linear(texture(color_lut, lut_coordinates).rgb);
+
+  return mix(color, graded, settings.lut_intensity);
+}
+
+// Darkens the color towards the edges of the screen
+vec3 vignette(vec3 color) {
+  // 1 at the middle of each edge, further into the corners
+  float distance_from_center = length(v_uv * 2.0 - 1.0);
+  float darkening = smoothstep(
+    settings.vignette_radius,
+    settings.vignette_radius + settings.vignette_smoothness,
+    distance_from_center
+  );
+
+  return color * (1.0 - darkening * settings.vignette_intensity);
+}
+
+// Shader Entry Point
+// //////////////////
+void main() {
+  // Bloom is gathered from the unexposed scene, so it is exposed along with it
+  vec3 color = texture(hdr_image, v_uv).rgb;
+  color += texture(bloom_image, v_uv).rgb * settings.bloom_intensity;
+  color *= settings.exposure;
+
+  if (settings.tonemapper == TONEMAPPER_REINHARD) {
+    color = tonemap_reinhard(color);
+  } else if (settings.tonemapper == TONEMAPPER_AGX) {
+    color = tonemap_agx(color);
+  } else {
+    color = tonemap_aces(color);
+  }
+
+  if (settings.lut_intensity > 0.0) {
+    color = grade(color);
+  }
+  color = vignette(color);
+
+  if (settings.encode_srgb != 0u) {
+    color = linear_to_srgb(color);
+  }
+
+  f_color = vec4(color, 1.0);
+}
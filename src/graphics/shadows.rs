@@ -0,0 +1,378 @@
+use std::{f32::consts::FRAC_PI_2, sync::Arc};
+
+use log::trace;
+use nalgebra::{Matrix4, Orthographic3, Perspective3, Point3, Vector3};
+use vulkano::{
+  device::{Device, Queue},
+  format::Format,
+  image::{
+    view::{ImageView, ImageViewCreateInfo},
+    ImageAspects, ImageCreateFlags, ImageDimensions, ImageSubresourceRange, ImageUsage,
+    StorageImage,
+  },
+  pipeline::graphics::depth_stencil::CompareOp,
+  render_pass::{Framebuffer, FramebufferCreateInfo},
+  sampler::{BorderColor, Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+};
+
+use crate::{
+  config::{MAX_SHADOW_MAPS, SHADOW_CASCADE_COUNT, SHADOW_CASCADE_SPLIT_LAMBDA, SHADOW_MAP_SIZE},
+  scene::{vulkan_depth_range, MdrLight, MdrLightKind, MdrProjection, MdrScene},
+};
+
+use super::render_pass::MdrRenderPass;
+
+/// Format of the shadow maps, which every device supports for depth attachments and sampling.
+pub(crate) const SHADOW_MAP_FORMAT: Format = Format::D16_UNORM;
+
+/// Distance from point and spot lights to the near plane of their shadow maps.
+const SHADOW_NEAR_PLANE: f32 = 0.05;
+
+/// The widest spot light cone that can be covered by one shadow map, in radians.
+const MAX_SPOT_SHADOW_ANGLE: f32 = 1.48;
+
+/// Distance behind each cascade that objects still cast shadows into it from, as objects behind
+/// the camera can shadow what it sees.
+const CASCADE_CASTER_DISTANCE: f32 = 100.0;
+
+/// The depth images shadow-casting lights are rendered into, stored as the layers of one image
+/// so the mesh shader can sample any of them.
+pub(crate) struct MdrShadowMaps {
+  render_pass: MdrRenderPass,
+  /// One framebuffer for each layer of the image.
+  pub framebuffers: Vec<Arc<Framebuffer>>,
+  /// A view of every layer, for sampling in the mesh shader.
+  pub image_view: Arc<ImageView<StorageImage>>,
+  /// Compares depths rather than returning them, for `sampler2DArrayShadow`.
+  pub sampler: Arc<Sampler>,
+}
+
+impl MdrShadowMaps {
+  pub fn new(logical_device: &Arc<Device>, queue: &Arc<Queue>) -> Self {
+    let render_pass = MdrRenderPass::new_depth_only(logical_device, SHADOW_MAP_FORMAT);
+
+    let image = StorageImage::with_usage(
+      logical_device.clone(),
+      ImageDimensions::Dim2d {
+        width: SHADOW_MAP_SIZE,
+        height: SHADOW_MAP_SIZE,
+        array_layers: MAX_SHADOW_MAPS,
+      },
+      SHADOW_MAP_FORMAT,
+      ImageUsage {
+        depth_stencil_attachment: true,
+        sampled: true,
+        ..ImageUsage::none()
+      },
+      ImageCreateFlags::none(),
+      [queue.family()],
+    )
+    .unwrap();
+
+    // Each shadow map is drawn into through a view of its own layer
+    let framebuffers = (0..MAX_SHADOW_MAPS)
+      .map(|layer| {
+        let layer_view = ImageView::new(
+          image.clone(),
+          ImageViewCreateInfo {
+            subresource_range: ImageSubresourceRange {
+              aspects: ImageAspects {
+                depth: true,
+                ..ImageAspects::none()
+              },
+              mip_levels: 0..1,
+              array_layers: layer..layer + 1,
+            },
+            ..ImageViewCreateInfo::from_image(&image)
+          },
+        )
+        .unwrap();
+
+        Framebuffer::new(
+          render_pass.get_pass(),
+          FramebufferCreateInfo {
+            attachments: vec![layer_view],
+            ..Default::default()
+          },
+        )
+        .unwrap()
+      })
+      .collect();
+
+    let image_view = ImageView::new_default(image).unwrap();
+
+    // Everything outside a shadow map is lit, and filtering is done in the shader
+    let sampler = Sampler::new(
+      logical_device.clone(),
+      SamplerCreateInfo {
+        mag_filter: Filter::Nearest,
+        min_filter: Filter::Nearest,
+        address_mode: [SamplerAddressMode::ClampToBorder; 3],
+        border_color: BorderColor::FloatOpaqueWhite,
+        compare: Some(CompareOp::LessOrEqual),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    Self {
+      render_pass,
+      framebuffers,
+      image_view,
+      sampler,
+    }
+  }
+
+  /// Returns the render pass used to draw into the shadow maps.
+  pub fn render_pass(&self) -> &MdrRenderPass {
+    &self.render_pass
+  }
+}
+
+/// The shadow maps to render for a frame, assigned to the scene's shadow-casting lights.
+pub(crate) struct MdrShadowViews {
+  /// The first shadow map of each light in the scene's light set, `None` if it casts no shadows.
+  pub light_indices: Vec<Option<u32>>,
+  /// The view and projection of each shadow map, mapping world space into its clip space.
+  pub matrices: Vec<Matrix4<f32>>,
+  /// The view space depth at which each directional light shadow cascade ends.
+  pub cascade_splits: [f32; SHADOW_CASCADE_COUNT],
+}
+
+impl MdrShadowViews {
+  /// Assigns shadow maps to the lights in `scene` which cast shadows, in order, until they run
+  /// out.
+  pub fn new(scene: &MdrScene) -> Self {
+    let mut light_indices = Vec::new();
+    let mut matrices = Vec::new();
+    let cascade_splits = Self::cascade_splits(scene);
+
+    for light in scene.lights.iter() {
+      let light_matrices = if light.casts_shadows {
+        Self::light_matrices(scene, light, &cascade_splits)
+      } else {
+        Vec::new()
+      };
+
+      if light_matrices.is_empty() {
+        light_indices.push(None);
+      } else if matrices.len() + light_matrices.len() > MAX_SHADOW_MAPS as usize {
+        trace!("Out of shadow maps, light will not cast shadows");
+        light_indices.push(None);
+      } else {
+        light_indices.push(Some(matrices.len() as u32));
+        matrices.extend(light_matrices);
+      }
+    }
+
+    Self {
+      light_indices,
+      matrices,
+      cascade_splits,
+    }
+  }
+
+  /// Returns the depths at which each cascade ends, blending between evenly and logarithmically
+  /// spaced splits of the camera's view.
+  fn cascade_splits(scene: &MdrScene) -> [f32; SHADOW_CASCADE_COUNT] {
+    Self::split_depths(
+      scene.camera.projection.near_plane(),
+      scene.camera.projection.far_plane(),
+      SHADOW_CASCADE_SPLIT_LAMBDA,
+    )
+  }
+
+  /// Returns the depths at which each cascade between `near_plane` and `far_plane` ends, from
+  /// evenly spaced at a `lambda` of 0 to logarithmically spaced at 1.
+  fn split_depths(near_plane: f32, far_plane: f32, lambda: f32) -> [f32; SHADOW_CASCADE_COUNT] {
+    let mut splits = [far_plane; SHADOW_CASCADE_COUNT];
+    for (i, split) in splits.iter_mut().enumerate() {
+      let fraction = (i + 1) as f32 / SHADOW_CASCADE_COUNT as f32;
+      let logarithmic_split = near_plane * (far_plane / near_plane).powf(fraction);
+      let uniform_split = near_plane + (far_plane - near_plane) * fraction;
+      *split = lambda * logarithmic_split + (1.0 - lambda) * uniform_split;
+    }
+
+    splits
+  }
+
+  /// Returns the matrices of the shadow maps `light` needs, six for point lights, one for each
+  /// of the cascades ending at `cascade_splits` for directional lights, and one for spot lights.
+  fn light_matrices(
+    scene: &MdrScene,
+    light: &MdrLight,
+    cascade_splits: &[f32; SHADOW_CASCADE_COUNT],
+  ) -> Vec<Matrix4<f32>> {
+    let position = Point3::from(scene.light_world_position(light));
+    let direction = scene
+      .light_world_direction(light)
+      .unwrap_or_else(Vector3::z);
+    // Lights with an infinite range are limited to what the camera can see
+    let far_plane = light
+      .range
+      .min(scene.camera.projection.far_plane())
+      .max(SHADOW_NEAR_PLANE * 2.0);
+
+    match light.kind {
+      MdrLightKind::Point => {
+        // One square view covering each face of a cube around the light
+        let projection = Perspective3::new(1.0, FRAC_PI_2, SHADOW_NEAR_PLANE, far_plane);
+        let faces = [
+          (Vector3::x(), Vector3::y()),
+          (-Vector3::x(), Vector3::y()),
+          (Vector3::y(), Vector3::z()),
+          (-Vector3::y(), Vector3::z()),
+          (Vector3::z(), Vector3::y()),
+          (-Vector3::z(), Vector3::y()),
+        ];
+
+        faces
+          .iter()
+          .map(|(face_direction, up)| {
+            let view = Matrix4::look_at_rh(&position, &(position + face_direction), up);
+            vulkan_depth_range(&projection.to_homogeneous()) * view
+          })
+          .collect()
+      }
+      MdrLightKind::Spot {
+        outer_cone_angle, ..
+      } => {
+        let field_of_view = (outer_cone_angle * 2.0).min(MAX_SPOT_SHADOW_ANGLE * 2.0);
+        let projection = Perspective3::new(1.0, field_of_view, SHADOW_NEAR_PLANE, far_plane);
+        let view = Matrix4::look_at_rh(&position, &(position + direction), &up_for(&direction));
+
+        vec![vulkan_depth_range(&projection.to_homogeneous()) * view]
+      }
+      MdrLightKind::Directional { .. } => {
+        // Each cascade starts where the previous one ends
+        let mut cascade_start = scene.camera.projection.near_plane();
+        cascade_splits
+          .iter()
+          .map(|&cascade_end| {
+            let matrix = Self::cascade_matrix(scene, &direction, cascade_start, cascade_end);
+            cascade_start = cascade_end;
+            matrix
+          })
+          .collect()
+      }
+    }
+  }
+
+  /// Returns the matrix of a directional light shadow cascade covering the camera's view between
+  /// `near_depth` and `far_depth`, for light shining along `direction`.
+  fn cascade_matrix(
+    scene: &MdrScene,
+    direction: &Vector3<f32>,
+    near_depth: f32,
+    far_depth: f32,
+  ) -> Matrix4<f32> {
+    // Cover a sphere around this part of the camera's view. Its size doesn't change as the
+    // camera turns, so the shadow map's texels stay the same size
+    let corners = scene.camera.frustum_corners(near_depth, far_depth);
+    let center = corners.iter().sum::<Vector3<f32>>() / corners.len() as f32;
+    let radius = corners
+      .iter()
+      .map(|corner| (corner - center).norm())
+      .fold(0.0, f32::max);
+
+    // Move the center in whole texels across the light's view, so that shadow edges don't
+    // shimmer as the camera moves
+    let up = up_for(direction);
+    let light_rotation = Matrix4::look_at_rh(&Point3::origin(), &Point3::from(*direction), &up);
+    let texel_size = radius * 2.0 / SHADOW_MAP_SIZE as f32;
+    let mut light_center = light_rotation.transform_point(&Point3::from(center));
+    light_center.x = (light_center.x / texel_size).floor() * texel_size;
+    light_center.y = (light_center.y / texel_size).floor() * texel_size;
+    let center = light_rotation
+      .try_inverse()
+      .unwrap_or_else(Matrix4::identity)
+      .transform_point(&light_center);
+
+    // Objects behind the cascade can still cast shadows into it, so start the shadow map well
+    // before the sphere
+    let caster_distance = radius + CASCADE_CASTER_DISTANCE;
+    let eye = center - direction * caster_distance;
+    let view = Matrix4::look_at_rh(&eye, &center, &up);
+    let projection = Orthographic3::new(
+      -radius,
+      radius,
+      -radius,
+      radius,
+      0.0,
+      caster_distance + radius,
+    );
+
+    vulkan_depth_range(&projection.to_homogeneous()) * view
+  }
+}
+
+/// Returns an up vector for a view looking along `direction` which isn't parallel to it.
+fn up_for(direction: &Vector3<f32>) -> Vector3<f32> {
+  if direction.y.abs() > 0.99 {
+    Vector3::z()
+  } else {
+    Vector3::y()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const NEAR_PLANE: f32 = 0.1;
+  const FAR_PLANE: f32 = 100.0;
+
+  fn assert_near(actual: f32, expected: f32) {
+    assert!(
+      (actual - expected).abs() <= expected.abs() * 1e-5,
+      "expected {}, got {}",
+      expected,
+      actual
+    );
+  }
+
+  #[test]
+  fn uniform_splits_are_evenly_spaced() {
+    let splits = MdrShadowViews::split_depths(NEAR_PLANE, FAR_PLANE, 0.0);
+
+    let step = (FAR_PLANE - NEAR_PLANE) / SHADOW_CASCADE_COUNT as f32;
+    for (i, split) in splits.iter().enumerate() {
+      assert_near(*split, NEAR_PLANE + step * (i + 1) as f32);
+    }
+  }
+
+  #[test]
+  fn logarithmic_splits_have_equal_ratios() {
+    let splits = MdrShadowViews::split_depths(NEAR_PLANE, FAR_PLANE, 1.0);
+
+    let ratio = (FAR_PLANE / NEAR_PLANE).powf(1.0 / SHADOW_CASCADE_COUNT as f32);
+    let mut previous = NEAR_PLANE;
+    for split in splits.iter() {
+      assert_near(split / previous, ratio);
+      previous = *split;
+    }
+  }
+
+  #[test]
+  fn configured_splits_cover_the_view() {
+    let splits = MdrShadowViews::split_depths(NEAR_PLANE, FAR_PLANE, SHADOW_CASCADE_SPLIT_LAMBDA);
+    let uniform_splits = MdrShadowViews::split_depths(NEAR_PLANE, FAR_PLANE, 0.0);
+    let logarithmic_splits = MdrShadowViews::split_depths(NEAR_PLANE, FAR_PLANE, 1.0);
+
+    // The last cascade ends at the far plane, and the others lie between the two spacings
+    assert_near(splits[SHADOW_CASCADE_COUNT - 1], FAR_PLANE);
+    let mut previous = NEAR_PLANE;
+    let bounds = uniform_splits.iter().zip(logarithmic_splits.iter());
+    for (split, (uniform_split, logarithmic_split)) in splits.iter().zip(bounds) {
+      assert!(*split > previous);
+      assert!(*split <= uniform_split * (1.0 + 1e-5));
+      assert!(*split >= logarithmic_split * (1.0 - 1e-5));
+      previous = *split;
+    }
+  }
+
+  #[test]
+  fn split_lambda_is_a_blend() {
+    assert!((0.0..=1.0).contains(&SHADOW_CASCADE_SPLIT_LAMBDA));
+  }
+}
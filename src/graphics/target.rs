@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use vulkano::{
+  format::Format,
+  image::{
+    view::{ImageView, ImageViewAbstract},
+    AttachmentImage, ImageAccess, SwapchainImage,
+  },
+  pipeline::graphics::viewport::Viewport,
+  swapchain::Swapchain,
+};
+use winit::window::Window;
+
+use super::window::MdrWindow;
+
+/// The color images a graphics context renders into.
+pub(crate) enum MdrRenderTarget {
+  /// Rendering into a window's swapchain images, which are presented to the screen.
+  Window {
+    window: Arc<MdrWindow>,
+    swapchain: Arc<Swapchain<Window>>,
+    images: Vec<Arc<SwapchainImage<Window>>>,
+  },
+
+  /// Rendering into a single offscreen image, without a window, surface, or swapchain.
+  Offscreen { image: Arc<AttachmentImage> },
+}
+
+impl MdrRenderTarget {
+  /// Returns the `[width, height]` of the target's images in pixels.
+  pub fn dimensions(&self) -> [u32; 2] {
+    match self {
+      Self::Window { images, .. } => images[0].dimensions().width_height(),
+      Self::Offscreen { image } => image.dimensions().width_height(),
+    }
+  }
+
+  /// Returns the format of the target's color images.
+  pub fn image_format(&self) -> Format {
+    match self {
+      Self::Window { swapchain, .. } => swapchain.image_format(),
+      Self::Offscreen { image } => image.format(),
+    }
+  }
+
+  /// Returns an image view for each of the target's color images.
+  pub fn image_views(&self) -> Vec<Arc<dyn ImageViewAbstract>> {
+    match self {
+      Self::Window { images, .. } => images
+        .iter()
+        .map(|image| ImageView::new_default(image.clone()).unwrap() as Arc<dyn ImageViewAbstract>)
+        .collect(),
+      Self::Offscreen { image } => {
+        vec![ImageView::new_default(image.clone()).unwrap() as Arc<dyn ImageViewAbstract>]
+      }
+    }
+  }
+
+  /// Returns whether the target's color images can be copied out of, which swapchain images
+  /// only allow when the surface supports it.
+  pub fn allows_transfer_src(&self) -> bool {
+    match self {
+      Self::Window { images, .. } => images[0].usage().transfer_src,
+      Self::Offscreen { image } => image.usage().transfer_src,
+    }
+  }
+
+  /// Returns a viewport covering the whole target.
+  pub fn create_viewport(&self) -> Viewport {
+    let dimensions = self.dimensions();
+    Viewport {
+      origin: [0.0, 0.0],
+      dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+      depth_range: 0.0..1.0,
+    }
+  }
+
+  /// Returns whether or not the target has no visible drawing surface.
+  pub fn is_minimized(&self) -> bool {
+    match self {
+      Self::Window { window, .. } => window.is_minimized(),
+      Self::Offscreen { .. } => false,
+    }
+  }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use vulkano::{instance::Instance, pipeline::graphics::viewport::Viewport, swapchain::Surface};
+use vulkano::{instance::Instance, swapchain::Surface};
 use vulkano_win::VkSurfaceBuild;
 
 use winit::{
@@ -40,17 +40,9 @@ impl MdrWindow {
     Arc::new(Self { surface })
   }
 
-  pub fn create_viewport(&self) -> Viewport {
-    Viewport {
-      origin: [0.0, 0.0],
-      dimensions: self.dimensions().into(),
-      depth_range: 0.0..1.0,
-    }
-  }
-
   /// Returns the dimensions of the window.
   pub fn dimensions(&self) -> PhysicalSize<u32> {
-    return self.surface.window().inner_size();
+    self.surface.window().inner_size()
   }
 
   /// Returns whether or not the window has no visible drawing surface.
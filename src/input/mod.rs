@@ -1,9 +1,12 @@
 use log::trace;
 use winit::{
   dpi::PhysicalPosition,
-  event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode},
+  event::{ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode},
 };
 
+/// Number of pixels of precise (e.g. touchpad) scrolling treated as one line of wheel scrolling.
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
 pub struct MdrInputState {
   pub left: bool,
   pub up: bool,
@@ -14,11 +17,18 @@ pub struct MdrInputState {
   pub a: bool,
   pub s: bool,
   pub d: bool,
+  pub q: bool,
+  pub e: bool,
+  pub space: bool,
+  pub shift: bool,
 
   pub mouse_position: [f32; 2],
   pub mouse_left: bool,
   pub mouse_right: bool,
+  /// The distance the mouse moved since the last update in pixels.
   pub mouse_delta: [f32; 2],
+  /// The distance scrolled since the last update in lines, positive when scrolling up.
+  pub scroll_delta: f32,
 }
 
 pub struct MdrInputContext {
@@ -38,11 +48,16 @@ impl MdrInputContext {
         a: false,
         s: false,
         d: false,
+        q: false,
+        e: false,
+        space: false,
+        shift: false,
 
         mouse_position: [0.0, 0.0],
         mouse_left: false,
         mouse_right: false,
         mouse_delta: [0.0, 0.0],
+        scroll_delta: 0.0,
       },
     }
   }
@@ -64,6 +79,10 @@ impl MdrInputContext {
         VirtualKeyCode::A => self.state.a = true,
         VirtualKeyCode::S => self.state.s = true,
         VirtualKeyCode::D => self.state.d = true,
+        VirtualKeyCode::Q => self.state.q = true,
+        VirtualKeyCode::E => self.state.e = true,
+        VirtualKeyCode::Space => self.state.space = true,
+        VirtualKeyCode::LShift | VirtualKeyCode::RShift => self.state.shift = true,
 
         _ => (),
       },
@@ -77,6 +96,10 @@ impl MdrInputContext {
         VirtualKeyCode::A => self.state.a = false,
         VirtualKeyCode::S => self.state.s = false,
         VirtualKeyCode::D => self.state.d = false,
+        VirtualKeyCode::Q => self.state.q = false,
+        VirtualKeyCode::E => self.state.e = false,
+        VirtualKeyCode::Space => self.state.space = false,
+        VirtualKeyCode::LShift | VirtualKeyCode::RShift => self.state.shift = false,
 
         _ => (),
       },
@@ -105,15 +128,23 @@ impl MdrInputContext {
   pub fn mouse_moved_input(&mut self, position: PhysicalPosition<f64>) {
     trace!("Mouse moved event");
     let new_position = [position.x as f32, position.y as f32];
-    self.state.mouse_delta = [
-      new_position[0] - self.state.mouse_position[0],
-      new_position[1] - self.state.mouse_position[1],
-    ];
+    // Several move events can arrive between updates, so accumulate them
+    self.state.mouse_delta[0] += new_position[0] - self.state.mouse_position[0];
+    self.state.mouse_delta[1] += new_position[1] - self.state.mouse_position[1];
     self.state.mouse_position = new_position;
   }
 
+  pub fn mouse_wheel_input(&mut self, delta: &MouseScrollDelta) {
+    trace!("Mouse wheel event");
+    self.state.scroll_delta += match delta {
+      MouseScrollDelta::LineDelta(_, lines) => *lines,
+      MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_SCROLL_LINE,
+    };
+  }
+
   pub fn cleanup_after_update(&mut self) {
-    // Zero mouse delta in case mouse has stopped moving
+    // Zero mouse and scroll deltas in case they have stopped moving
     self.state.mouse_delta = [0.0, 0.0];
+    self.state.scroll_delta = 0.0;
   }
 }
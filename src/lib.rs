@@ -8,3 +8,5 @@ mod update;
 pub mod logger;
 pub use engine::{MdrEngine, MdrEngineOptions};
 pub use graphics::resources;
+pub use graphics::{MdrCaptureError, MdrRenderPath};
+pub use input::MdrInputState;
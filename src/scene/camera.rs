@@ -1,15 +1,35 @@
-use nalgebra::{Matrix4, Perspective3, Vector3, Vector4};
+use nalgebra::{Matrix4, Point3, Vector3, Vector4};
+use serde::{Deserialize, Serialize};
 
-use super::transform::MdrTransform;
+use super::{
+  projection::{MdrCameraProjection, MdrProjection},
+  transform::{MdrRotation, MdrTransform},
+};
 
-// TODO Reimplement as trait and split out ortho/perspective cameras
 pub struct MdrCamera {
   pub transform: MdrTransform,
 
-  pub field_of_view: f32,
+  /// How the camera projects the scene onto the screen, which can be changed at any time.
+  pub projection: MdrCameraProjection,
   pub aspect_ratio: f32,
-  pub near_plane: f32,
-  pub far_plane: f32,
+
+  /// Exposure compensation in stops. The scene is made twice as bright for each stop above 0
+  /// before it is tone mapped.
+  pub exposure: f32,
+  /// The curve mapping the scene's unbounded colors into the range the screen can show.
+  pub tonemapper: MdrTonemapper,
+}
+
+/// Curves used to tone map a scene's colors into the range the screen can show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MdrTonemapper {
+  /// A fit of the ACES filmic curve, which has strong contrast and saturates bright colors.
+  #[default]
+  Aces,
+  /// Reinhard's curve, which is gentle and keeps dark colors close to the original.
+  Reinhard,
+  /// The AgX curve, which desaturates bright colors towards white instead of shifting their hue.
+  AgX,
 }
 
 impl MdrCamera {
@@ -21,13 +41,50 @@ impl MdrCamera {
   }
 
   pub fn get_projection_matrix(&self) -> Matrix4<f32> {
-    Perspective3::new(
-      self.aspect_ratio,
-      self.field_of_view,
-      self.near_plane,
-      self.far_plane,
-    )
-    .to_homogeneous()
+    self.projection.projection_matrix(self.aspect_ratio)
+  }
+
+  /// Returns the world space corners of the part of the view frustum between `near_depth` and
+  /// `far_depth` in front of the camera. The four near corners come first, then the four far
+  /// corners in the same order.
+  pub fn frustum_corners(&self, near_depth: f32, far_depth: f32) -> [Vector3<f32>; 8] {
+    let inverse_view = self
+      .get_view_matrix()
+      .try_inverse()
+      .unwrap_or_else(Matrix4::identity);
+    let inverse_projection = self
+      .get_projection_matrix()
+      .try_inverse()
+      .unwrap_or_else(Matrix4::identity);
+
+    let mut corners = [Vector3::zeros(); 8];
+    for (i, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+      .into_iter()
+      .enumerate()
+    {
+      // The ray through this corner of the screen in view space, between the clipping planes
+      let ray_start = inverse_projection.transform_point(&Point3::new(x, y, 0.0));
+      let ray_end = inverse_projection.transform_point(&Point3::new(x, y, 1.0));
+
+      // The camera looks down negative z, so points at a depth have z equal to its negation
+      for (j, depth) in [near_depth, far_depth].into_iter().enumerate() {
+        let t = (-depth - ray_start.z) / (ray_end.z - ray_start.z);
+        let view_point = ray_start + (ray_end - ray_start) * t;
+        corners[j * 4 + i] = inverse_view.transform_point(&view_point).coords;
+      }
+    }
+
+    corners
+  }
+
+  /// Rotates the camera to face `target`, keeping its y axis as close to `up` as possible.
+  pub fn look_at(&mut self, target: &Vector3<f32>, up: &Vector3<f32>) {
+    // The camera's transform is applied to the world, so its translation is the negated
+    // camera position and its rotation is the inverse of the camera's orientation
+    let translation = &self.transform.translation;
+    let position = -Vector3::new(translation.x, translation.y, translation.z);
+
+    self.transform.rotation = MdrRotation::look_at(&(target - position), up).inverse();
   }
 
   pub fn get_forward_vector(&self) -> Vector3<f32> {
@@ -60,10 +117,11 @@ impl Default for MdrCamera {
     Self {
       transform: MdrTransform::identity(),
 
-      field_of_view: std::f32::consts::FRAC_PI_2,
-      near_plane: 0.01,
+      projection: MdrCameraProjection::default(),
       aspect_ratio: 1.0,
-      far_plane: 1000.0,
+
+      exposure: 0.0,
+      tonemapper: MdrTonemapper::default(),
     }
   }
 }
@@ -0,0 +1,280 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use nalgebra::{UnitQuaternion, Vector3};
+
+use crate::input::MdrInputState;
+
+use super::{projection::MdrCameraProjection, transform::MdrRotation, MdrCamera};
+
+/// How close the orbit controller may pitch to straight up or down, in radians. Keeps the
+/// camera from flipping over the poles.
+const ORBIT_POLE_MARGIN: f32 = 0.01;
+
+/// Orbits a camera around a target point. Dragging with the right mouse button held rotates
+/// around the target and scrolling moves towards or away from it.
+pub struct MdrOrbitController {
+  /// The point the camera orbits around and looks at.
+  pub target: Vector3<f32>,
+  /// The world's up direction, which the camera yaws around. The engine's projection puts
+  /// negative y at the top of the screen, so this defaults to negative y.
+  pub up: Vector3<f32>,
+
+  /// Radians rotated per pixel the mouse moves.
+  pub rotation_speed: f32,
+  /// How quickly scrolling changes the distance to the target, per line scrolled.
+  pub zoom_speed: f32,
+  /// The closest the camera gets to the target, must be greater than zero.
+  pub min_distance: f32,
+  pub max_distance: f32,
+  /// Roughly the time in seconds the camera takes to catch up with the input, 0 disables
+  /// smoothing.
+  pub smoothing: f32,
+
+  yaw: f32,
+  pitch: f32,
+  distance: f32,
+
+  current_yaw: f32,
+  current_pitch: f32,
+  current_distance: f32,
+}
+
+impl MdrOrbitController {
+  /// Creates a controller orbiting `target` at `distance`, clamped between the default
+  /// `min_distance` and `max_distance`.
+  pub fn new(target: Vector3<f32>, distance: f32) -> Self {
+    let min_distance = 0.1;
+    let max_distance = 1000.0;
+    let distance = distance.clamp(min_distance, max_distance);
+
+    Self {
+      target,
+      up: -Vector3::y(),
+
+      rotation_speed: 0.01,
+      zoom_speed: 0.1,
+      min_distance,
+      max_distance,
+      smoothing: 0.05,
+
+      yaw: 0.0,
+      pitch: 0.0,
+      distance,
+
+      current_yaw: 0.0,
+      current_pitch: 0.0,
+      current_distance: distance,
+    }
+  }
+
+  /// Moves the camera to `yaw` and `pitch` radians around the target at `distance`, without
+  /// smoothing.
+  pub fn set_orbit(&mut self, yaw: f32, pitch: f32, distance: f32) {
+    self.yaw = yaw;
+    self.pitch = pitch.clamp(
+      -FRAC_PI_2 + ORBIT_POLE_MARGIN,
+      FRAC_PI_2 - ORBIT_POLE_MARGIN,
+    );
+    self.distance = distance.clamp(self.min_distance, self.max_distance);
+
+    self.current_yaw = self.yaw;
+    self.current_pitch = self.pitch;
+    self.current_distance = self.distance;
+  }
+
+  /// Updates the orbit from `input` and moves `camera` to match. Call this from the update
+  /// function each frame.
+  pub fn update(&mut self, camera: &mut MdrCamera, input: &MdrInputState, dt: f32) {
+    if input.mouse_right {
+      self.yaw -= input.mouse_delta[0] * self.rotation_speed;
+      self.pitch = (self.pitch + input.mouse_delta[1] * self.rotation_speed).clamp(
+        -FRAC_PI_2 + ORBIT_POLE_MARGIN,
+        FRAC_PI_2 - ORBIT_POLE_MARGIN,
+      );
+    }
+    self.distance = (self.distance * zoom_factor(-input.scroll_delta, self.zoom_speed))
+      .clamp(self.min_distance, self.max_distance);
+
+    // Ease towards the new orbit
+    let blend = smoothing_blend(self.smoothing, dt);
+    let previous_distance = self.current_distance;
+    self.current_yaw += (self.yaw - self.current_yaw) * blend;
+    self.current_pitch += (self.pitch - self.current_pitch) * blend;
+    // The limits may have changed since the last update, and the orthographic height is
+    // scaled by the change in distance so it must never reach zero
+    self.current_distance = (self.current_distance
+      + (self.distance - self.current_distance) * blend)
+      .clamp(self.min_distance, self.max_distance);
+
+    // Moving an orthographic camera doesn't change the size of anything, so scale its view
+    if let MdrCameraProjection::Orthographic(orthographic) = &mut camera.projection {
+      orthographic.height *= self.current_distance / previous_distance;
+    }
+
+    // The camera looks down its negative z axis, so it sits along positive z from the target
+    let orientation = MdrRotation::from_quaternion(up_alignment(&self.up))
+      * MdrRotation::from_axis_angle(&Vector3::y(), self.current_yaw)
+      * MdrRotation::from_axis_angle(&Vector3::x(), self.current_pitch);
+    let position =
+      self.target + orientation.rotate_vector(&Vector3::new(0.0, 0.0, self.current_distance));
+
+    set_camera_pose(camera, &position, &orientation);
+  }
+}
+
+/// Flies a camera freely in all six degrees of freedom. Dragging with the right mouse button
+/// held turns the camera around its own axes, W/A/S/D move forward, left, back, and right,
+/// space and shift move up and down, and Q and E roll. Scrolling zooms the projection.
+pub struct MdrFlyController {
+  /// Units moved per second.
+  pub movement_speed: f32,
+  /// Radians rotated per pixel the mouse moves.
+  pub rotation_speed: f32,
+  /// Radians rolled per second.
+  pub roll_speed: f32,
+  /// How quickly scrolling zooms, per line scrolled.
+  pub zoom_speed: f32,
+  /// The smallest and largest magnification relative to the camera's projection when the
+  /// controller was created. Zooming scales whatever projection the camera has, so changes
+  /// made to it between updates are kept.
+  pub min_zoom: f32,
+  pub max_zoom: f32,
+  /// Roughly the time in seconds the camera takes to catch up with the input, 0 disables
+  /// smoothing.
+  pub smoothing: f32,
+
+  position: Vector3<f32>,
+  orientation: MdrRotation,
+  zoom: f32,
+
+  current_position: Vector3<f32>,
+  current_orientation: MdrRotation,
+  current_zoom: f32,
+}
+
+impl MdrFlyController {
+  /// Creates a controller starting from `camera`'s current position and rotation.
+  pub fn new(camera: &MdrCamera) -> Self {
+    let (position, orientation) = camera_pose(camera);
+
+    Self {
+      movement_speed: 1.0,
+      rotation_speed: 0.01,
+      roll_speed: 1.0,
+      zoom_speed: 0.1,
+      min_zoom: 0.5,
+      max_zoom: 10.0,
+      smoothing: 0.05,
+
+      position,
+      orientation,
+      zoom: 1.0,
+
+      current_position: position,
+      current_orientation: orientation,
+      current_zoom: 1.0,
+    }
+  }
+
+  /// Updates the camera's flight from `input` and moves `camera` to match. Call this from the
+  /// update function each frame.
+  pub fn update(&mut self, camera: &mut MdrCamera, input: &MdrInputState, dt: f32) {
+    if input.mouse_right {
+      // Turning about the camera's own axes, rather than the world's, avoids gimbal lock
+      let [delta_x, delta_y] = input.mouse_delta;
+      self
+        .orientation
+        .rotate_local(&Vector3::y(), -delta_x * self.rotation_speed);
+      self
+        .orientation
+        .rotate_local(&Vector3::x(), delta_y * self.rotation_speed);
+    }
+    let roll = axis_input(input.q, input.e) * self.roll_speed * dt;
+    self.orientation.rotate_local(&Vector3::z(), roll);
+
+    // The camera looks down negative z and negative y is the top of the screen
+    let local_direction = Vector3::new(
+      axis_input(input.d, input.a),
+      axis_input(input.shift, input.space),
+      axis_input(input.s, input.w),
+    );
+    if let Some(local_direction) = local_direction.try_normalize(f32::EPSILON) {
+      self.position += self.orientation.rotate_vector(&local_direction) * self.movement_speed * dt;
+    }
+    self.zoom = (self.zoom * zoom_factor(input.scroll_delta, self.zoom_speed))
+      .clamp(self.min_zoom, self.max_zoom);
+
+    // Ease towards the new pose
+    let blend = smoothing_blend(self.smoothing, dt);
+    let previous_zoom = self.current_zoom;
+    self.current_position += (self.position - self.current_position) * blend;
+    self.current_orientation = self.current_orientation.slerp(&self.orientation, blend);
+    self.current_zoom += (self.zoom - self.current_zoom) * blend;
+
+    // Zoom by the change since the last update, keeping any other changes to the projection
+    camera.projection = zoomed_projection(&camera.projection, self.current_zoom / previous_zoom);
+    set_camera_pose(camera, &self.current_position, &self.current_orientation);
+  }
+}
+
+/// Returns `1.0`, `-1.0`, or `0.0` depending on which of two opposing inputs are held.
+fn axis_input(positive: bool, negative: bool) -> f32 {
+  (positive as i32 - negative as i32) as f32
+}
+
+/// Returns the factor to scale by for `scroll_delta` lines of scrolling, so that scrolling
+/// the same amount in and out cancels out.
+fn zoom_factor(scroll_delta: f32, zoom_speed: f32) -> f32 {
+  (scroll_delta * zoom_speed).exp()
+}
+
+/// Returns how far to move from the current value towards the goal value this frame, for a
+/// smoothing time constant of `smoothing` seconds.
+fn smoothing_blend(smoothing: f32, dt: f32) -> f32 {
+  if smoothing <= 0.0 {
+    return 1.0;
+  }
+
+  1.0 - (-dt / smoothing).exp()
+}
+
+/// Returns `projection` magnified by `zoom`.
+fn zoomed_projection(projection: &MdrCameraProjection, zoom: f32) -> MdrCameraProjection {
+  match *projection {
+    MdrCameraProjection::Perspective(mut perspective) => {
+      perspective.field_of_view = 2.0 * ((perspective.field_of_view / 2.0).tan() / zoom).atan();
+      MdrCameraProjection::Perspective(perspective)
+    }
+    MdrCameraProjection::Orthographic(mut orthographic) => {
+      orthographic.height /= zoom;
+      MdrCameraProjection::Orthographic(orthographic)
+    }
+  }
+}
+
+/// Returns the rotation which turns the top of the screen for an unrotated camera, negative
+/// y, to face `up`.
+fn up_alignment(up: &Vector3<f32>) -> UnitQuaternion<f32> {
+  // Only fails when `up` is positive y, directly opposite
+  UnitQuaternion::rotation_between(&-Vector3::y(), up)
+    .unwrap_or_else(|| UnitQuaternion::from_axis_angle(&Vector3::z_axis(), PI))
+}
+
+/// Returns the world position and orientation of `camera`. The camera's transform is applied
+/// to the world, so its translation is the negated position and its rotation is the inverse of
+/// its orientation.
+fn camera_pose(camera: &MdrCamera) -> (Vector3<f32>, MdrRotation) {
+  let translation = &camera.transform.translation;
+  let position = -Vector3::new(translation.x, translation.y, translation.z);
+
+  (position, camera.transform.rotation.inverse())
+}
+
+/// Places `camera` at the world `position` with the world `orientation`.
+fn set_camera_pose(camera: &mut MdrCamera, position: &Vector3<f32>, orientation: &MdrRotation) {
+  camera
+    .transform
+    .translation
+    .set(-position.x, -position.y, -position.z);
+  camera.transform.rotation = orientation.inverse();
+}
@@ -0,0 +1,545 @@
+use fxhash::FxHashMap;
+use log::error;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{
+  graphics::MdrResourceManager,
+  resources::{manifest::MdrResourceManifest, MdrMaterial, MdrMesh, MdrRgb},
+};
+
+use super::{
+  transform::{MdrTransform, MdrTranslation},
+  MdrAmbientOcclusion, MdrAntialiasing, MdrBloom, MdrCamera, MdrCameraProjection, MdrColorGrading,
+  MdrCustomEffect, MdrLight, MdrLightKind, MdrObjectHandle, MdrPerspective, MdrPostProcessing,
+  MdrRenderObject, MdrScene, MdrSceneError, MdrTonemapper, MdrVignette, DEFAULT_CLEAR_COLOR,
+};
+
+/// A serializable description of a scene and the resources it uses. Objects refer to their
+/// meshes and materials by name, and to other objects by their index in `objects`. Fields
+/// added since a scene was saved take their defaults, so older scene files still load.
+#[derive(Serialize, Deserialize)]
+pub struct MdrSceneDescription {
+  pub resources: MdrResourceManifest,
+  pub camera: MdrCameraDescription,
+  pub lights: Vec<MdrLightDescription>,
+  pub objects: Vec<MdrObjectDescription>,
+  /// Name of the scene's environment.
+  #[serde(default)]
+  pub environment: Option<String>,
+  /// Name of the environment drawn as the scene's skybox.
+  #[serde(default)]
+  pub skybox: Option<String>,
+  #[serde(default = "default_clear_color")]
+  pub clear_color: MdrRgb,
+  #[serde(default)]
+  pub post_processing: MdrPostProcessingDescription,
+}
+
+/// The saved parts of an `MdrCamera`. The aspect ratio is not saved as it follows the size of
+/// the render target.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "MdrCameraFields")]
+pub struct MdrCameraDescription {
+  pub transform: MdrTransform,
+  pub projection: MdrCameraProjection,
+  pub exposure: f32,
+  pub tonemapper: MdrTonemapper,
+}
+
+/// The fields a camera description is deserialized from, including those of the first saved
+/// format, which stored a perspective's parameters directly on the camera.
+#[derive(Deserialize)]
+#[serde(default)]
+struct MdrCameraFields {
+  transform: MdrTransform,
+  #[serde(deserialize_with = "present")]
+  projection: Option<MdrCameraProjection>,
+  exposure: f32,
+  tonemapper: MdrTonemapper,
+  #[serde(deserialize_with = "present")]
+  field_of_view: Option<f32>,
+  #[serde(deserialize_with = "present")]
+  near_plane: Option<f32>,
+  #[serde(deserialize_with = "present")]
+  far_plane: Option<f32>,
+}
+
+/// The saved parts of an `MdrPostProcessing`, referring to the resources it uses by name.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct MdrPostProcessingDescription {
+  pub ambient_occlusion: MdrAmbientOcclusion,
+  pub bloom: MdrBloom,
+  /// Name of the color lookup table the scene is graded with.
+  pub color_lut: Option<String>,
+  pub color_grading_intensity: f32,
+  pub vignette: MdrVignette,
+  pub antialiasing: MdrAntialiasing,
+  pub custom_effects: Vec<MdrCustomEffectDescription>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MdrCustomEffectDescription {
+  /// Name of the post effect.
+  pub effect: String,
+  pub enabled: bool,
+  pub parameters: [f32; 4],
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MdrLightDescription {
+  pub color: MdrRgb,
+  pub brightness: f32,
+  #[serde(default = "default_light_kind")]
+  pub kind: MdrLightKind,
+  /// Distance at which the light fades out, `MdrLight::default_range()` of the brightness if
+  /// `None`.
+  #[serde(default)]
+  pub range: Option<f32>,
+  #[serde(default)]
+  pub casts_shadows: bool,
+  pub translation: MdrTranslation,
+  /// Index of the object the light is attached to.
+  pub attached_to: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MdrObjectDescription {
+  pub name: Option<String>,
+  pub tags: Vec<String>,
+  pub mesh: String,
+  pub material: String,
+  pub transform: MdrTransform,
+  #[serde(default = "default_true")]
+  pub casts_shadows: bool,
+  #[serde(default = "default_true")]
+  pub receives_shadows: bool,
+  /// Index of the object's parent.
+  pub parent: Option<usize>,
+}
+
+impl MdrSceneDescription {
+  /// Describes `scene`, whose resources were created as recorded in `resources`.
+  pub fn from_scene(scene: &MdrScene, resources: &MdrResourceManifest) -> Self {
+    // Handles are replaced by each object's position in the description
+    let object_indices: FxHashMap<MdrObjectHandle, usize> = scene
+      .objects()
+      .enumerate()
+      .map(|(index, (handle, _))| (handle, index))
+      .collect();
+    let index_of = |handle: Option<MdrObjectHandle>| {
+      handle.and_then(|handle| object_indices.get(&handle).copied())
+    };
+
+    let objects = scene
+      .objects()
+      .map(|(_, object)| MdrObjectDescription {
+        name: object.name.clone(),
+        tags: object.tags.clone(),
+        mesh: object.mesh.name.clone(),
+        material: object.material.name.clone(),
+        transform: object.transform,
+        casts_shadows: object.casts_shadows,
+        receives_shadows: object.receives_shadows,
+        parent: index_of(object.parent),
+      })
+      .collect();
+
+    let lights = scene
+      .lights
+      .iter()
+      .map(|light| MdrLightDescription {
+        color: light.color,
+        brightness: light.brightness,
+        kind: light.kind,
+        range: Some(light.range),
+        casts_shadows: light.casts_shadows,
+        translation: light.translation,
+        attached_to: index_of(light.attached_to),
+      })
+      .collect();
+
+    let post_processing = &scene.post_processing;
+    let custom_effects = post_processing
+      .custom_effects
+      .iter()
+      .map(|custom_effect| MdrCustomEffectDescription {
+        effect: custom_effect.effect.name.clone(),
+        enabled: custom_effect.enabled,
+        parameters: custom_effect.parameters,
+      })
+      .collect();
+
+    Self {
+      resources: resources.clone(),
+      camera: MdrCameraDescription {
+        transform: scene.camera.transform,
+        projection: scene.camera.projection,
+        exposure: scene.camera.exposure,
+        tonemapper: scene.camera.tonemapper,
+      },
+      lights,
+      objects,
+      environment: scene
+        .environment
+        .as_ref()
+        .map(|environment| environment.name.clone()),
+      skybox: scene.skybox.as_ref().map(|skybox| skybox.name.clone()),
+      clear_color: scene.clear_color,
+      post_processing: MdrPostProcessingDescription {
+        ambient_occlusion: post_processing.ambient_occlusion,
+        bloom: post_processing.bloom,
+        color_lut: post_processing
+          .color_grading
+          .lut
+          .as_ref()
+          .map(|lut| lut.name.clone()),
+        color_grading_intensity: post_processing.color_grading.intensity,
+        vignette: post_processing.vignette,
+        antialiasing: post_processing.antialiasing,
+        custom_effects,
+      },
+    }
+  }
+
+  /// Loads the described resources into `resource_manager`, skipping any which are already
+  /// loaded, and builds the described scene from them.
+  pub fn build(
+    &self,
+    resource_manager: &mut MdrResourceManager,
+  ) -> Result<MdrScene, MdrSceneError> {
+    if let Err(e) = resource_manager.load_manifest(&self.resources) {
+      error!("Failed to load scene resources: {:?}", e);
+      return Err(MdrSceneError::ResourceLoadError);
+    }
+
+    let mut scene = MdrScene::new();
+    scene.camera = MdrCamera {
+      transform: self.camera.transform,
+      projection: self.camera.projection,
+      exposure: self.camera.exposure,
+      tonemapper: self.camera.tonemapper,
+      ..MdrCamera::default()
+    };
+
+    // Add every object before parenting, as parents may come after their children
+    let mut handles = Vec::<MdrObjectHandle>::with_capacity(self.objects.len());
+    for description in self.objects.iter() {
+      let mesh = resource_manager.retrieve_mesh(&description.mesh);
+      let material = resource_manager.retrieve_material(&description.material);
+      let (mesh, material): (MdrMesh, MdrMaterial) = match (mesh, material) {
+        (Ok(mesh), Ok(material)) => (mesh, material),
+        _ => {
+          error!(
+            "Scene object refers to missing mesh {} or material {}",
+            description.mesh, description.material
+          );
+          return Err(MdrSceneError::ResourceLoadError);
+        }
+      };
+
+      let mut object = MdrRenderObject::new(mesh, material);
+      object.transform = description.transform;
+      object.name = description.name.clone();
+      object.tags = description.tags.clone();
+      object.casts_shadows = description.casts_shadows;
+      object.receives_shadows = description.receives_shadows;
+      handles.push(scene.add_object(object));
+    }
+
+    let handle_at = |index: Option<usize>| match index {
+      Some(index) => match handles.get(index) {
+        Some(handle) => Ok(Some(*handle)),
+        None => Err(MdrSceneError::ObjectNotFound),
+      },
+      None => Ok(None),
+    };
+
+    for (description, handle) in self.objects.iter().zip(handles.iter()) {
+      scene.set_parent(*handle, handle_at(description.parent)?)?;
+    }
+
+    for description in self.lights.iter() {
+      let mut light = MdrLight::new(
+        description.color.r,
+        description.color.g,
+        description.color.b,
+        description.brightness,
+      );
+      light.kind = description.kind;
+      if let Some(range) = description.range {
+        light.range = range;
+      }
+      light.casts_shadows = description.casts_shadows;
+      light.translation = description.translation;
+      light.attached_to = handle_at(description.attached_to)?;
+      scene.lights.add_light(light);
+    }
+
+    if let Some(name) = &self.environment {
+      match resource_manager.retrieve_environment(name) {
+        Ok(environment) => scene.environment = Some(environment),
+        Err(_) => {
+          error!("Scene refers to missing environment {}", name);
+          return Err(MdrSceneError::ResourceLoadError);
+        }
+      }
+    }
+
+    if let Some(name) = &self.skybox {
+      match resource_manager.retrieve_environment(name) {
+        Ok(skybox) => scene.skybox = Some(skybox),
+        Err(_) => {
+          error!("Scene refers to missing skybox environment {}", name);
+          return Err(MdrSceneError::ResourceLoadError);
+        }
+      }
+    }
+    scene.clear_color = self.clear_color;
+    scene.post_processing = self.post_processing.build(resource_manager)?;
+
+    Ok(scene)
+  }
+}
+
+impl Default for MdrCameraDescription {
+  fn default() -> Self {
+    let camera = MdrCamera::default();
+    Self {
+      transform: camera.transform,
+      projection: camera.projection,
+      exposure: camera.exposure,
+      tonemapper: camera.tonemapper,
+    }
+  }
+}
+
+impl Default for MdrCameraFields {
+  fn default() -> Self {
+    let camera = MdrCameraDescription::default();
+    Self {
+      transform: camera.transform,
+      projection: None,
+      exposure: camera.exposure,
+      tonemapper: camera.tonemapper,
+      field_of_view: None,
+      near_plane: None,
+      far_plane: None,
+    }
+  }
+}
+
+impl From<MdrCameraFields> for MdrCameraDescription {
+  fn from(fields: MdrCameraFields) -> Self {
+    // Cameras saved before projections were added are perspectives with their own parameters
+    let is_legacy =
+      fields.field_of_view.is_some() || fields.near_plane.is_some() || fields.far_plane.is_some();
+    let projection = match fields.projection {
+      Some(projection) => projection,
+      None if is_legacy => {
+        let default = MdrPerspective::default();
+        MdrCameraProjection::Perspective(MdrPerspective {
+          field_of_view: fields.field_of_view.unwrap_or(default.field_of_view),
+          near_plane: fields.near_plane.unwrap_or(default.near_plane),
+          far_plane: fields.far_plane.unwrap_or(default.far_plane),
+        })
+      }
+      None => MdrCameraDescription::default().projection,
+    };
+
+    Self {
+      transform: fields.transform,
+      projection,
+      exposure: fields.exposure,
+      tonemapper: fields.tonemapper,
+    }
+  }
+}
+
+impl Default for MdrPostProcessingDescription {
+  fn default() -> Self {
+    let color_grading = MdrColorGrading::default();
+    Self {
+      ambient_occlusion: MdrAmbientOcclusion::default(),
+      bloom: MdrBloom::default(),
+      color_lut: None,
+      color_grading_intensity: color_grading.intensity,
+      vignette: MdrVignette::default(),
+      antialiasing: MdrAntialiasing::default(),
+      custom_effects: Vec::new(),
+    }
+  }
+}
+
+impl MdrPostProcessingDescription {
+  /// Builds the described post-processing from the resources in `resource_manager`.
+  fn build(
+    &self,
+    resource_manager: &MdrResourceManager,
+  ) -> Result<MdrPostProcessing, MdrSceneError> {
+    let lut = match &self.color_lut {
+      Some(name) => match resource_manager.retrieve_color_lut(name) {
+        Ok(lut) => Some(lut),
+        Err(_) => {
+          error!("Scene refers to missing color lookup table {}", name);
+          return Err(MdrSceneError::ResourceLoadError);
+        }
+      },
+      None => None,
+    };
+
+    let mut custom_effects = Vec::with_capacity(self.custom_effects.len());
+    for description in self.custom_effects.iter() {
+      let effect = match resource_manager.retrieve_post_effect(&description.effect) {
+        Ok(effect) => effect,
+        Err(_) => {
+          error!("Scene refers to missing post effect {}", description.effect);
+          return Err(MdrSceneError::ResourceLoadError);
+        }
+      };
+      custom_effects.push(MdrCustomEffect {
+        effect,
+        enabled: description.enabled,
+        parameters: description.parameters,
+      });
+    }
+
+    Ok(MdrPostProcessing {
+      ambient_occlusion: self.ambient_occlusion,
+      bloom: self.bloom,
+      color_grading: MdrColorGrading {
+        lut,
+        intensity: self.color_grading_intensity,
+      },
+      vignette: self.vignette,
+      antialiasing: self.antialiasing,
+      custom_effects,
+    })
+  }
+}
+
+// Defaults of fields missing from older scene files
+// /////////////////////////////////////////////////
+
+fn default_clear_color() -> MdrRgb {
+  DEFAULT_CLEAR_COLOR
+}
+
+fn default_light_kind() -> MdrLightKind {
+  MdrLightKind::Point
+}
+
+fn default_true() -> bool {
+  true
+}
+
+/// Deserializes a field which is `None` when missing from the file, rather than written as an
+/// option.
+fn present<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+  deserializer: D,
+) -> Result<Option<T>, D::Error> {
+  T::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resources::{
+    manifest::{MdrMaterialSource, MdrMeshSource, MdrTextureImage},
+    texture::{MdrFilter, MdrMipmapMode},
+  };
+  use crate::scene::test_object;
+
+  fn to_ron(description: &MdrSceneDescription) -> String {
+    ron::ser::to_string_pretty(description, ron::ser::PrettyConfig::default()).unwrap()
+  }
+
+  #[test]
+  fn round_trip_through_ron() {
+    let mut scene = MdrScene::new();
+    let parent = scene.add_object(test_object());
+    let mut child = test_object();
+    child.name = Some(String::from("child"));
+    child.casts_shadows = false;
+    let child = scene.add_object(child);
+    scene.set_parent(child, Some(parent)).unwrap();
+    let mut light = MdrLight::white(2.0);
+    light.attached_to = Some(parent);
+    scene.lights.add_light(light);
+
+    let mut resources = MdrResourceManifest::default();
+    resources.meshes.push(MdrMeshSource {
+      name: String::from("mesh"),
+      path: String::from("mesh.obj"),
+    });
+    let description = MdrSceneDescription::from_scene(&scene, &resources);
+
+    let serialized = to_ron(&description);
+    let deserialized: MdrSceneDescription = ron::de::from_str(&serialized).unwrap();
+
+    assert_eq!(to_ron(&deserialized), serialized);
+    assert_eq!(deserialized.objects.len(), 2);
+    assert_eq!(deserialized.objects[1].parent, Some(0));
+    assert!(!deserialized.objects[1].casts_shadows);
+    assert_eq!(deserialized.lights[0].attached_to, Some(0));
+    assert_eq!(deserialized.resources.meshes[0].path, "mesh.obj");
+  }
+
+  #[test]
+  fn load_scene_saved_before_later_fields() {
+    // A scene saved in the first format, before shadows, projections, quaternion rotations,
+    // environments, and post-processing were added
+    let old_scene = include_str!("testdata/first_format_scene.ron");
+    let description: MdrSceneDescription = ron::de::from_str(old_scene).unwrap();
+
+    assert!(description.resources.environments.is_empty());
+    match &description.resources.textures[0].image {
+      MdrTextureImage::File {
+        filter,
+        mipmap_mode,
+        anisotropy,
+        ..
+      } => {
+        assert!(*filter == MdrFilter::Linear);
+        assert!(*mipmap_mode == MdrMipmapMode::None);
+        assert_eq!(*anisotropy, None);
+      }
+      MdrTextureImage::Solid(_) => panic!("Expected a texture loaded from a file"),
+    }
+    let material: &MdrMaterialSource = &description.resources.materials[0];
+    assert_eq!(material.metallic, None);
+    assert_eq!(material.roughness_factor, 1.0);
+
+    let camera = &description.camera;
+    assert_eq!(camera.transform.translation.z, -5.0);
+    let camera_rotation = camera.transform.rotation.quaternion();
+    assert!((camera_rotation.angle() - 0.5).abs() < 1e-5);
+    match camera.projection {
+      MdrCameraProjection::Perspective(perspective) => {
+        assert_eq!(perspective.field_of_view, 1.0);
+        assert_eq!(perspective.near_plane, 0.1);
+        assert_eq!(perspective.far_plane, 100.0);
+      }
+      MdrCameraProjection::Orthographic(_) => panic!("Expected a perspective projection"),
+    }
+    assert_eq!(camera.exposure, 0.0);
+
+    let light = &description.lights[0];
+    assert!(matches!(light.kind, MdrLightKind::Point));
+    assert_eq!(light.range, None);
+    assert!(!light.casts_shadows);
+
+    let object = &description.objects[0];
+    assert_eq!(object.transform.translation.x, 1.0);
+    // The first format rotated by its `y` angle around the z axis
+    let object_rotation = object.transform.rotation.quaternion();
+    let rotated = object_rotation * nalgebra::Vector3::x();
+    assert!((rotated - nalgebra::Vector3::y()).norm() < 1e-5);
+    assert!(object.casts_shadows);
+    assert!(object.receives_shadows);
+
+    assert_eq!(description.environment, None);
+    assert_eq!(description.clear_color.r, DEFAULT_CLEAR_COLOR.r);
+    assert!(description.post_processing.custom_effects.is_empty());
+  }
+}
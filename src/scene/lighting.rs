@@ -1,27 +1,87 @@
-use crate::{config::MAX_POINT_LIGHTS, resources::MdrRgb, scene::transform::MdrTranslation};
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  config::LIGHT_CUTOFF_BRIGHTNESS,
+  resources::MdrRgb,
+  scene::{transform::MdrTranslation, MdrObjectHandle},
+};
 
 #[derive(Clone, Copy)]
 pub struct MdrLight {
   pub color: MdrRgb,
   pub brightness: f32,
-
+  pub kind: MdrLightKind,
+  /// The distance at which point and spot lights fade out completely, must be greater than
+  /// zero. Defaults to `default_range()` of the brightness the light was created with. Lights
+  /// with an infinite range never fade and are never culled.
+  pub range: f32,
+  /// Whether objects block the light, casting shadows. Each shadow-casting point light uses six
+  /// of the `MAX_SHADOW_MAPS` shadow maps and other lights use one, lights which don't fit cast
+  /// no shadows.
+  pub casts_shadows: bool,
+
+  /// The light's position, relative to the object it is attached to if there is one.
   pub translation: MdrTranslation,
+  /// The scene object the light moves with, if any. The directions of directional and spot
+  /// lights also rotate with the object.
+  pub attached_to: Option<MdrObjectHandle>,
 }
 
 impl MdrLight {
+  /// Creates a point light.
   pub fn new(r: f32, g: f32, b: f32, brightness: f32) -> Self {
     Self {
       color: MdrRgb { r, g, b },
       brightness,
+      kind: MdrLightKind::Point,
+      range: Self::default_range(brightness),
+      casts_shadows: false,
 
       translation: MdrTranslation::identity(),
+      attached_to: None,
     }
   }
 
+  /// Creates a white point light.
   pub fn white(brightness: f32) -> Self {
     Self::new(1.0, 1.0, 1.0, brightness)
   }
 
+  /// Creates a directional light shining along `direction`.
+  pub fn directional(color: MdrRgb, brightness: f32, direction: Vector3<f32>) -> Self {
+    Self {
+      kind: MdrLightKind::Directional { direction },
+      ..Self::new(color.r, color.g, color.b, brightness)
+    }
+  }
+
+  /// Creates a spot light shining along `direction`, with cone angles in radians.
+  pub fn spot(
+    color: MdrRgb,
+    brightness: f32,
+    direction: Vector3<f32>,
+    inner_cone_angle: f32,
+    outer_cone_angle: f32,
+  ) -> Self {
+    Self {
+      kind: MdrLightKind::Spot {
+        direction,
+        inner_cone_angle,
+        outer_cone_angle,
+      },
+      ..Self::new(color.r, color.g, color.b, brightness)
+    }
+  }
+
+  /// Returns the distance at which a light of `brightness` would fall below
+  /// `LIGHT_CUTOFF_BRIGHTNESS` if it fell off with the square of the distance.
+  pub fn default_range(brightness: f32) -> f32 {
+    (brightness.max(0.0) / LIGHT_CUTOFF_BRIGHTNESS)
+      .sqrt()
+      .max(f32::MIN_POSITIVE)
+  }
+
   pub const fn unused() -> Self {
     Self {
       color: MdrRgb {
@@ -30,16 +90,53 @@ impl MdrLight {
         b: 0.0,
       },
       brightness: 0.0,
+      kind: MdrLightKind::Point,
+      range: f32::INFINITY,
+      casts_shadows: false,
       translation: MdrTranslation::identity(),
+      attached_to: None,
     }
   }
 }
 
-/// A set of lights in a scene. Currently only supports point lights.
-/// Up to `MAX_POINT_LIGHTS` can be added to a scene.
+/// The ways a light can shine into the scene. Directions are relative to the object the light
+/// is attached to, if there is one, and don't need to be normalized.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum MdrLightKind {
+  /// Shines equally in every direction from the light's position, like a light bulb.
+  Point,
+
+  /// Shines along `direction` everywhere in the scene, like the sun. The light's position is
+  /// ignored.
+  Directional { direction: Vector3<f32> },
+
+  /// Shines from the light's position in a cone around `direction`, like a flashlight. Surfaces
+  /// within `inner_cone_angle` radians of the direction are fully lit, fading to unlit at
+  /// `outer_cone_angle` radians.
+  Spot {
+    direction: Vector3<f32>,
+    inner_cone_angle: f32,
+    outer_cone_angle: f32,
+  },
+}
+
+impl MdrLightKind {
+  /// Returns the direction the light shines in, `None` for point lights.
+  pub fn direction(&self) -> Option<Vector3<f32>> {
+    match self {
+      Self::Point => None,
+      Self::Directional { direction } | Self::Spot { direction, .. } => Some(*direction),
+    }
+  }
+}
+
+/// A set of point, directional, and spot lights in a scene. Any number of lights can be added,
+/// lights are sorted into clusters of the view frustum on the GPU so that each fragment only
+/// shades the lights which can reach it. Point and spot lights with an infinite `range` reach
+/// every cluster, and each cluster holds at most `MAX_LIGHTS_PER_CLUSTER` lights.
+#[derive(Default)]
 pub struct MdrLightSet {
   lights: Vec<MdrLight>,
-  light_count: usize,
 }
 
 impl MdrLightSet {
@@ -48,25 +145,14 @@ impl MdrLightSet {
     Self::default()
   }
 
-  /// Add a light to the scene's light set. Will panic if `MAX_POINT_LIGHTS` are already present.
+  /// Add a light to the scene's light set.
   pub fn add_light(&mut self, light: MdrLight) {
-    if self.light_count == MAX_POINT_LIGHTS {
-      panic!(
-        "You added more than {} lights and now everything broke, be careful.",
-        MAX_POINT_LIGHTS
-      )
-    }
-
     self.lights.push(light);
-    self.light_count += 1;
   }
 
-  /// Remove a light from the light set.
+  /// Remove a light from the light set. Will panic if no light exists at that index.
   pub fn remove_light(&mut self, light_index: usize) {
-    assert!(light_index < MAX_POINT_LIGHTS);
-
     self.lights.remove(light_index);
-    self.light_count -= 1;
   }
 
   /// Get a reference to a particular light by index. Returns `None` if no light exists at that index.
@@ -79,27 +165,18 @@ impl MdrLightSet {
     self.lights.get_mut(light_index)
   }
 
-  /// Returns an array containing a copy of the light set's data.
-  pub fn get_light_array(&self) -> [MdrLight; MAX_POINT_LIGHTS] {
-    let mut light_array = [MdrLight::unused(); MAX_POINT_LIGHTS];
-    for i in 0..self.light_count {
-      light_array[i] = self.lights[i];
-    }
+  /// Iterate over the lights in the set.
+  pub fn iter(&self) -> impl Iterator<Item = &MdrLight> {
+    self.lights.iter()
+  }
 
-    light_array
+  /// Iterate mutably over the lights in the set.
+  pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut MdrLight> {
+    self.lights.iter_mut()
   }
 
   /// Get the number of lights in the set.
   pub fn get_count(&self) -> u32 {
-    self.light_count as u32
-  }
-}
-
-impl Default for MdrLightSet {
-  fn default() -> Self {
-    Self {
-      lights: Vec::<MdrLight>::with_capacity(MAX_POINT_LIGHTS),
-      light_count: 0,
-    }
+    self.lights.len() as u32
   }
 }
@@ -1,18 +1,70 @@
 mod camera;
+mod controller;
+mod description;
 mod lighting;
 mod object;
+mod post_processing;
+mod projection;
 pub mod transform;
 
-pub use camera::MdrCamera;
-pub use lighting::MdrLight;
-pub use object::MdrRenderObject;
+use nalgebra::{Matrix4, Vector3, Vector4};
 
-use self::lighting::MdrLightSet;
+use crate::resources::{MdrEnvironment, MdrImportedObject, MdrRgb};
+
+pub use camera::{MdrCamera, MdrTonemapper};
+pub use controller::{MdrFlyController, MdrOrbitController};
+pub use description::{
+  MdrCameraDescription, MdrCustomEffectDescription, MdrLightDescription, MdrObjectDescription,
+  MdrPostProcessingDescription, MdrSceneDescription,
+};
+pub use lighting::{MdrLight, MdrLightKind};
+pub use object::{MdrObjectHandle, MdrRenderObject};
+pub use post_processing::{
+  MdrAmbientOcclusion, MdrAntialiasing, MdrBloom, MdrColorGrading, MdrCustomEffect,
+  MdrPostProcessing, MdrVignette,
+};
+pub(crate) use projection::vulkan_depth_range;
+pub use projection::{MdrCameraProjection, MdrOrthographic, MdrPerspective, MdrProjection};
+
+use self::{lighting::MdrLightSet, object::MdrObjectSet, transform::MdrTransform};
+
+/// The color scenes are cleared to unless they are given another.
+const DEFAULT_CLEAR_COLOR: MdrRgb = MdrRgb {
+  r: 0.1,
+  g: 0.1,
+  b: 0.1,
+};
 
 pub struct MdrScene {
   pub camera: MdrCamera,
   pub lights: MdrLightSet,
-  pub scene_objects: Vec<MdrRenderObject>,
+  /// The environment lighting the scene from every direction. Scenes without one are lit by a
+  /// dim, uniform environment.
+  pub environment: Option<MdrEnvironment>,
+  /// The environment drawn behind every object in the scene. It doesn't have to be the
+  /// environment lighting the scene.
+  pub skybox: Option<MdrEnvironment>,
+  /// The linear color drawn behind every object when the scene has no skybox. It is exposed and
+  /// tone mapped along with the rest of the scene.
+  pub clear_color: MdrRgb,
+  /// The full-screen effects applied after the scene is drawn.
+  pub post_processing: MdrPostProcessing,
+  /// What the renderer draws in place of the shaded scene, for debugging.
+  pub debug_view: MdrDebugView,
+
+  objects: MdrObjectSet,
+}
+
+/// Alternative ways of drawing a scene for debugging the renderer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MdrDebugView {
+  /// The shaded scene.
+  #[default]
+  None,
+  /// The shaded scene tinted by the directional light shadow cascade covering each surface. The
+  /// cascades are red, green, blue, and yellow from nearest to furthest, and surfaces beyond
+  /// them are grey.
+  ShadowCascades,
 }
 
 impl MdrScene {
@@ -20,11 +72,418 @@ impl MdrScene {
     Self {
       camera: MdrCamera::default(),
       lights: MdrLightSet::new(),
-      scene_objects: Vec::<MdrRenderObject>::new(),
+      environment: None,
+      skybox: None,
+      clear_color: DEFAULT_CLEAR_COLOR,
+      post_processing: MdrPostProcessing::default(),
+      debug_view: MdrDebugView::default(),
+
+      objects: MdrObjectSet::new(),
+    }
+  }
+
+  /// Add an object to the scene, returning a handle which can be used to access it later. The
+  /// object becomes a root object if its parent is no longer in the scene.
+  pub fn add_object(&mut self, mut object: MdrRenderObject) -> MdrObjectHandle {
+    if let Some(parent) = object.parent {
+      if self.objects.get(parent).is_none() {
+        object.parent = None;
+      }
+    }
+
+    self.objects.insert(object)
+  }
+
+  /// Add objects imported from a file to the scene, parenting each to the object given by its
+  /// `parent` index. Returns the objects' handles, in the same order as `objects`.
+  pub fn add_imported_objects(&mut self, objects: Vec<MdrImportedObject>) -> Vec<MdrObjectHandle> {
+    let mut handles = Vec::<MdrObjectHandle>::with_capacity(objects.len());
+    for MdrImportedObject { mut object, parent } in objects {
+      object.parent = parent.map(|parent| handles[parent]);
+      handles.push(self.objects.insert(object));
+    }
+
+    handles
+  }
+
+  /// Remove the object referred to by `handle` from the scene and return it as a root object
+  /// with its world transform. Its children become root objects and lights attached to it are
+  /// detached, both keeping their world positions. Returns `None` if the handle is no longer
+  /// valid.
+  pub fn remove_object(&mut self, handle: MdrObjectHandle) -> Option<MdrRenderObject> {
+    let world_matrix = self.world_matrix(handle)?;
+
+    // Detach children, moving their parent's transform into their own
+    let world_matrices = self.world_matrices();
+    let children: Vec<(MdrObjectHandle, Matrix4<f32>)> = self
+      .objects
+      .iter()
+      .zip(world_matrices)
+      .filter(|((_, object), _)| object.parent == Some(handle))
+      .map(|((child, _), child_world_matrix)| (child, child_world_matrix))
+      .collect();
+    for (child, child_world_matrix) in children {
+      let child = self.objects.get_mut(child).unwrap();
+      child.parent = None;
+      child.transform = MdrTransform::from_matrix(&child_world_matrix);
+    }
+
+    // Detach lights, moving them to their world position and direction
+    for light in self.lights.iter_mut() {
+      if light.attached_to == Some(handle) {
+        let translation = &light.translation;
+        let world_position =
+          world_matrix * Vector4::new(translation.x, translation.y, translation.z, 1.0);
+
+        light.attached_to = None;
+        light
+          .translation
+          .set(world_position.x, world_position.y, world_position.z);
+        if let MdrLightKind::Directional { direction } | MdrLightKind::Spot { direction, .. } =
+          &mut light.kind
+        {
+          *direction = world_matrix.fixed_slice::<3, 3>(0, 0) * *direction;
+        }
+      }
+    }
+
+    let mut object = self.objects.remove(handle)?;
+    if object.parent.take().is_some() {
+      object.transform = MdrTransform::from_matrix(&world_matrix);
+    }
+    Some(object)
+  }
+
+  /// Get a reference to the object referred to by `handle`. Returns `None` if the handle is no
+  /// longer valid.
+  pub fn get(&self, handle: MdrObjectHandle) -> Option<&MdrRenderObject> {
+    self.objects.get(handle)
+  }
+
+  /// Get a mutable reference to the object referred to by `handle`. Returns `None` if the
+  /// handle is no longer valid.
+  pub fn get_mut(&mut self, handle: MdrObjectHandle) -> Option<&mut MdrRenderObject> {
+    self.objects.get_mut(handle)
+  }
+
+  /// Iterate over the objects in the scene along with their handles.
+  pub fn objects(&self) -> impl Iterator<Item = (MdrObjectHandle, &MdrRenderObject)> {
+    self.objects.iter()
+  }
+
+  /// Iterate mutably over the objects in the scene along with their handles.
+  pub fn objects_mut(&mut self) -> impl Iterator<Item = (MdrObjectHandle, &mut MdrRenderObject)> {
+    self.objects.iter_mut()
+  }
+
+  /// Get the number of objects in the scene.
+  pub fn object_count(&self) -> usize {
+    self.objects.len()
+  }
+
+  /// Returns the handle of the first object named `name`, if any. This searches every object
+  /// in the scene, so store the handle rather than looking it up each frame.
+  pub fn find_by_name(&self, name: &str) -> Option<MdrObjectHandle> {
+    self
+      .objects
+      .iter()
+      .find(|(_, object)| object.name.as_deref() == Some(name))
+      .map(|(handle, _)| handle)
+  }
+
+  /// Returns the handles of every object tagged with `tag`.
+  pub fn find_by_tag(&self, tag: &str) -> Vec<MdrObjectHandle> {
+    self
+      .objects
+      .iter()
+      .filter(|(_, object)| object.has_tag(tag))
+      .map(|(handle, _)| handle)
+      .collect()
+  }
+
+  /// Makes the object `child` a child of the object `parent`, or a root object if `parent` is
+  /// `None`. The child's transform is left unchanged, so it is now interpreted relative to its
+  /// new parent.
+  pub fn set_parent(
+    &mut self,
+    child: MdrObjectHandle,
+    parent: Option<MdrObjectHandle>,
+  ) -> Result<(), MdrSceneError> {
+    self.validate_parent(child, parent)?;
+    self.objects.get_mut(child).unwrap().parent = parent;
+
+    Ok(())
+  }
+
+  /// Makes the object `child` a child of the object `parent`, or a root object if `parent` is
+  /// `None`. The child's transform is adjusted so that it keeps its current world transform,
+  /// which can't be done if the parent is scaled to zero along an axis.
+  pub fn set_parent_keep_world_transform(
+    &mut self,
+    child: MdrObjectHandle,
+    parent: Option<MdrObjectHandle>,
+  ) -> Result<(), MdrSceneError> {
+    self.validate_parent(child, parent)?;
+
+    let world_matrix = self
+      .world_matrix(child)
+      .ok_or(MdrSceneError::ObjectNotFound)?;
+    let parent_inverse = match parent {
+      Some(parent) => self
+        .world_matrix(parent)
+        .ok_or(MdrSceneError::ObjectNotFound)?
+        .try_inverse()
+        .ok_or(MdrSceneError::SingularParentTransform)?,
+      None => Matrix4::identity(),
+    };
+
+    let object = self.objects.get_mut(child).unwrap();
+    object.parent = parent;
+    object.transform = MdrTransform::from_matrix(&(parent_inverse * world_matrix));
+
+    Ok(())
+  }
+
+  /// Returns the world transformation matrix of the object `handle`, composed of the transforms
+  /// of the object and all of its ancestors. Returns `None` if the handle is no longer valid.
+  pub fn world_matrix(&self, handle: MdrObjectHandle) -> Option<Matrix4<f32>> {
+    let object = self.objects.get(handle)?;
+    let local_matrix = object.transform.matrix();
+
+    match object.parent.and_then(|parent| self.world_matrix(parent)) {
+      Some(parent_matrix) => Some(parent_matrix * local_matrix),
+      None => Some(local_matrix),
     }
   }
 
-  pub fn add_object(&mut self, object: MdrRenderObject) {
-    self.scene_objects.push(object);
+  /// Returns the position of the object `handle` in world space. Returns `None` if the handle
+  /// is no longer valid.
+  pub fn world_position(&self, handle: MdrObjectHandle) -> Option<Vector3<f32>> {
+    self
+      .world_matrix(handle)
+      .map(|matrix| (matrix * Vector4::new(0.0, 0.0, 0.0, 1.0)).xyz())
+  }
+
+  /// Returns the position of `light` in world space, taking into account the object it is
+  /// attached to, if any.
+  pub fn light_world_position(&self, light: &MdrLight) -> Vector3<f32> {
+    let translation = &light.translation;
+    let local_position = Vector4::new(translation.x, translation.y, translation.z, 1.0);
+
+    match light
+      .attached_to
+      .and_then(|handle| self.world_matrix(handle))
+    {
+      Some(matrix) => (matrix * local_position).xyz(),
+      None => local_position.xyz(),
+    }
+  }
+
+  /// Returns the normalized direction `light` shines in, in world space, taking into account
+  /// the rotation of the object it is attached to, if any. Returns `None` for point lights.
+  pub fn light_world_direction(&self, light: &MdrLight) -> Option<Vector3<f32>> {
+    let direction = light.kind.direction()?;
+    let world_direction = match light
+      .attached_to
+      .and_then(|handle| self.world_matrix(handle))
+    {
+      Some(matrix) => matrix.fixed_slice::<3, 3>(0, 0) * direction,
+      None => direction,
+    };
+
+    Some(
+      world_direction
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(Vector3::z()),
+    )
+  }
+
+  /// Computes the world transformation matrix of every object in the scene, in the same order
+  /// as `objects()`. Each ancestor's matrix is only computed once.
+  pub(crate) fn world_matrices(&self) -> Vec<Matrix4<f32>> {
+    let mut matrices = vec![None; self.objects.slot_count()];
+
+    self
+      .objects
+      .iter()
+      .map(|(handle, _)| self.resolve_world_matrix(handle, &mut matrices))
+      .collect()
+  }
+
+  /// Computes the world matrix of the object `handle`, storing it and those of its ancestors
+  /// in `matrices`, which is indexed by handle index.
+  fn resolve_world_matrix(
+    &self,
+    handle: MdrObjectHandle,
+    matrices: &mut [Option<Matrix4<f32>>],
+  ) -> Matrix4<f32> {
+    if let Some(matrix) = matrices[handle.index()] {
+      return matrix;
+    }
+
+    // Objects whose parent is no longer in the scene are treated as roots, as in
+    // `world_matrix()`
+    let object = self.objects.get(handle).unwrap();
+    let local_matrix = object.transform.matrix();
+    let parent = object
+      .parent
+      .filter(|parent| self.objects.get(*parent).is_some());
+    let world_matrix = match parent {
+      Some(parent) => self.resolve_world_matrix(parent, matrices) * local_matrix,
+      None => local_matrix,
+    };
+
+    matrices[handle.index()] = Some(world_matrix);
+    world_matrix
+  }
+
+  /// Checks that `parent` can be made the parent of `child` without creating a cycle.
+  fn validate_parent(
+    &self,
+    child: MdrObjectHandle,
+    parent: Option<MdrObjectHandle>,
+  ) -> Result<(), MdrSceneError> {
+    if self.objects.get(child).is_none() {
+      return Err(MdrSceneError::ObjectNotFound);
+    }
+
+    // Walk up from the new parent, if we reach the child it would become its own ancestor
+    let mut ancestor = parent;
+    while let Some(handle) = ancestor {
+      if handle == child {
+        return Err(MdrSceneError::ParentCycle);
+      }
+
+      ancestor = match self.objects.get(handle) {
+        Some(object) => object.parent,
+        None => return Err(MdrSceneError::ObjectNotFound),
+      };
+    }
+
+    Ok(())
+  }
+}
+
+#[derive(Debug)]
+/// Error emitted by `MdrScene`.
+pub enum MdrSceneError {
+  /// Emitted when a handle refers to an object which is not in the scene.
+  ObjectNotFound,
+  /// Emitted when re-parenting an object would make it its own ancestor.
+  ParentCycle,
+  /// Emitted when an object can't keep its world transform under a new parent whose transform
+  /// can't be inverted, because it is scaled to zero along an axis.
+  SingularParentTransform,
+  /// Emitted when a scene file can't be read from disk.
+  SceneReadError,
+  /// Emitted when a scene file can't be written to disk.
+  SceneWriteError,
+  /// Emitted when a scene file's contents are not a valid scene description.
+  SceneParseError,
+  /// Emitted when the resources used by a scene description fail to load.
+  ResourceLoadError,
+}
+
+/// Creates an object using the mesh `"mesh"` and material `"material"`, for tests which don't
+/// load the resources.
+#[cfg(test)]
+pub(crate) fn test_object() -> MdrRenderObject {
+  use crate::resources::{MdrMaterial, MdrMesh};
+
+  MdrRenderObject::new(
+    MdrMesh {
+      name: String::from("mesh"),
+    },
+    MdrMaterial {
+      name: String::from("material"),
+    },
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn keep_world_transform_under_flattened_parent_fails() {
+    let mut scene = MdrScene::new();
+    let mut parent = test_object();
+    parent.transform.scale.set(1.0, 0.0, 1.0);
+    let parent = scene.add_object(parent);
+    let child = scene.add_object(test_object());
+
+    let result = scene.set_parent_keep_world_transform(child, Some(parent));
+
+    assert!(matches!(
+      result,
+      Err(MdrSceneError::SingularParentTransform)
+    ));
+    // The child is left where it was
+    assert_eq!(scene.get(child).unwrap().parent(), None);
+  }
+
+  #[test]
+  fn keep_world_transform_under_removed_parent_fails() {
+    let mut scene = MdrScene::new();
+    let parent = scene.add_object(test_object());
+    let child = scene.add_object(test_object());
+    scene.remove_object(parent);
+
+    let result = scene.set_parent_keep_world_transform(child, Some(parent));
+
+    assert!(matches!(result, Err(MdrSceneError::ObjectNotFound)));
+  }
+
+  #[test]
+  fn keep_world_transform_preserves_world_position() {
+    let mut scene = MdrScene::new();
+    let mut parent = test_object();
+    parent.transform.translation.set(1.0, 2.0, 3.0);
+    parent.transform.scale.set(2.0, 2.0, 2.0);
+    let parent = scene.add_object(parent);
+    let mut child = test_object();
+    child.transform.translation.set(-1.0, 0.0, 5.0);
+    let child = scene.add_object(child);
+
+    scene
+      .set_parent_keep_world_transform(child, Some(parent))
+      .unwrap();
+
+    let position = scene.world_position(child).unwrap();
+    assert!((position - Vector3::new(-1.0, 0.0, 5.0)).norm() < 1e-5);
+    assert_eq!(scene.get(child).unwrap().parent(), Some(parent));
+  }
+
+  #[test]
+  fn removed_child_is_readded_as_root() {
+    let mut scene = MdrScene::new();
+    let mut parent = test_object();
+    parent.transform.translation.set(1.0, 2.0, 3.0);
+    let parent = scene.add_object(parent);
+    let child = scene.add_object(test_object());
+    scene.set_parent(child, Some(parent)).unwrap();
+
+    let child = scene.remove_object(child).unwrap();
+    scene.remove_object(parent);
+    let child = scene.add_object(child);
+
+    assert_eq!(scene.get(child).unwrap().parent(), None);
+    let position = scene.world_position(child).unwrap();
+    assert!((position - Vector3::new(1.0, 2.0, 3.0)).norm() < 1e-5);
+    assert_eq!(scene.world_matrices().len(), 1);
+  }
+
+  #[test]
+  fn object_with_missing_parent_is_a_root() {
+    let mut scene = MdrScene::new();
+    let parent = scene.add_object(test_object());
+    let child = scene.add_object(test_object());
+    scene.set_parent(child, Some(parent)).unwrap();
+    scene.objects.remove(parent);
+
+    let world_matrices = scene.world_matrices();
+
+    assert_eq!(world_matrices, vec![scene.world_matrix(child).unwrap()]);
+    assert_eq!(world_matrices[0], Matrix4::identity());
   }
 }
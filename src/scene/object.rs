@@ -4,8 +4,21 @@ use super::transform::MdrTransform;
 
 pub struct MdrRenderObject {
   pub mesh: MdrMesh,
+  /// The object's transform relative to its parent, or to the world if it has no parent.
   pub transform: MdrTransform,
   pub material: MdrMaterial,
+
+  /// An optional name the object can be looked up by.
+  pub name: Option<String>,
+  /// Tags the object can be looked up by.
+  pub tags: Vec<String>,
+
+  /// Whether the object blocks shadow-casting lights.
+  pub casts_shadows: bool,
+  /// Whether shadows are drawn onto the object.
+  pub receives_shadows: bool,
+
+  pub(crate) parent: Option<MdrObjectHandle>,
 }
 
 impl MdrRenderObject {
@@ -14,6 +27,228 @@ impl MdrRenderObject {
       mesh,
       transform: MdrTransform::identity(),
       material,
+
+      name: None,
+      tags: Vec::new(),
+
+      casts_shadows: true,
+      receives_shadows: true,
+
+      parent: None,
+    }
+  }
+
+  /// Returns the handle of the object's parent in the scene, if it has one.
+  pub fn parent(&self) -> Option<MdrObjectHandle> {
+    self.parent
+  }
+
+  /// Returns whether the object has been given `tag`.
+  pub fn has_tag(&self, tag: &str) -> bool {
+    self.tags.iter().any(|object_tag| object_tag == tag)
+  }
+}
+
+/// A handle to an object in an `MdrScene`. Handles stay valid until their object is removed,
+/// and never refer to a different object afterwards, even if its storage is reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MdrObjectHandle {
+  index: u32,
+  generation: u32,
+}
+
+impl MdrObjectHandle {
+  /// The position of the handle's object in its set's storage.
+  pub(crate) fn index(&self) -> usize {
+    self.index as usize
+  }
+}
+
+/// Storage for one object in an `MdrObjectSet`. The generation is incremented each time
+/// the slot's object is removed.
+struct MdrObjectSlot {
+  generation: u32,
+  object: Option<MdrRenderObject>,
+}
+
+/// The objects in a scene, addressed by generational handles. Slots left by removed objects
+/// are reused for new objects, so removal never moves other objects.
+pub(crate) struct MdrObjectSet {
+  slots: Vec<MdrObjectSlot>,
+  free_slots: Vec<u32>,
+}
+
+impl MdrObjectSet {
+  /// Create an empty object set.
+  pub fn new() -> Self {
+    Self {
+      slots: Vec::new(),
+      free_slots: Vec::new(),
+    }
+  }
+
+  /// Add an object to the set, returning its handle.
+  pub fn insert(&mut self, object: MdrRenderObject) -> MdrObjectHandle {
+    match self.free_slots.pop() {
+      Some(index) => {
+        let slot = &mut self.slots[index as usize];
+        slot.object = Some(object);
+
+        MdrObjectHandle {
+          index,
+          generation: slot.generation,
+        }
+      }
+      None => {
+        self.slots.push(MdrObjectSlot {
+          generation: 0,
+          object: Some(object),
+        });
+
+        MdrObjectHandle {
+          index: (self.slots.len() - 1) as u32,
+          generation: 0,
+        }
+      }
+    }
+  }
+
+  /// Remove the object referred to by `handle` from the set and return it. Returns `None` if
+  /// the handle is no longer valid.
+  pub fn remove(&mut self, handle: MdrObjectHandle) -> Option<MdrRenderObject> {
+    let slot = self.slots.get_mut(handle.index())?;
+    if slot.generation != handle.generation {
+      return None;
+    }
+
+    let object = slot.object.take()?;
+    slot.generation = slot.generation.wrapping_add(1);
+    self.free_slots.push(handle.index);
+
+    Some(object)
+  }
+
+  /// Get a reference to the object referred to by `handle`. Returns `None` if the handle is no
+  /// longer valid.
+  pub fn get(&self, handle: MdrObjectHandle) -> Option<&MdrRenderObject> {
+    let slot = self.slots.get(handle.index())?;
+    if slot.generation != handle.generation {
+      return None;
     }
+
+    slot.object.as_ref()
+  }
+
+  /// Get a mutable reference to the object referred to by `handle`. Returns `None` if the
+  /// handle is no longer valid.
+  pub fn get_mut(&mut self, handle: MdrObjectHandle) -> Option<&mut MdrRenderObject> {
+    let slot = self.slots.get_mut(handle.index())?;
+    if slot.generation != handle.generation {
+      return None;
+    }
+
+    slot.object.as_mut()
+  }
+
+  /// Iterate over the objects in the set along with their handles.
+  pub fn iter(&self) -> impl Iterator<Item = (MdrObjectHandle, &MdrRenderObject)> {
+    self.slots.iter().enumerate().filter_map(|(index, slot)| {
+      slot.object.as_ref().map(|object| {
+        let handle = MdrObjectHandle {
+          index: index as u32,
+          generation: slot.generation,
+        };
+        (handle, object)
+      })
+    })
+  }
+
+  /// Iterate mutably over the objects in the set along with their handles.
+  pub fn iter_mut(&mut self) -> impl Iterator<Item = (MdrObjectHandle, &mut MdrRenderObject)> {
+    self
+      .slots
+      .iter_mut()
+      .enumerate()
+      .filter_map(|(index, slot)| {
+        let generation = slot.generation;
+        slot.object.as_mut().map(|object| {
+          let handle = MdrObjectHandle {
+            index: index as u32,
+            generation,
+          };
+          (handle, object)
+        })
+      })
+  }
+
+  /// The number of storage slots in the set, including empty ones. Handle indices are always
+  /// less than this.
+  pub fn slot_count(&self) -> usize {
+    self.slots.len()
+  }
+
+  /// Get the number of objects in the set.
+  pub fn len(&self) -> usize {
+    self.slots.len() - self.free_slots.len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::scene::test_object;
+
+  fn named_object(name: &str) -> MdrRenderObject {
+    let mut object = test_object();
+    object.name = Some(String::from(name));
+    object
+  }
+
+  fn name_of(set: &MdrObjectSet, handle: MdrObjectHandle) -> Option<&str> {
+    set.get(handle).and_then(|object| object.name.as_deref())
+  }
+
+  #[test]
+  fn removed_handle_is_stale() {
+    let mut set = MdrObjectSet::new();
+    let handle = set.insert(named_object("a"));
+
+    assert_eq!(set.remove(handle).unwrap().name.as_deref(), Some("a"));
+    assert!(set.get(handle).is_none());
+    assert!(set.get_mut(handle).is_none());
+    assert!(set.remove(handle).is_none());
+    assert_eq!(set.len(), 0);
+  }
+
+  #[test]
+  fn reused_slot_does_not_revive_stale_handle() {
+    let mut set = MdrObjectSet::new();
+    let old_handle = set.insert(named_object("old"));
+    set.remove(old_handle);
+    let new_handle = set.insert(named_object("new"));
+
+    // The slot is reused, but the old handle doesn't refer to its new object
+    assert_eq!(new_handle.index(), old_handle.index());
+    assert_ne!(new_handle, old_handle);
+    assert!(set.get(old_handle).is_none());
+    assert!(set.remove(old_handle).is_none());
+    assert_eq!(name_of(&set, new_handle), Some("new"));
+    assert_eq!(set.slot_count(), 1);
+  }
+
+  #[test]
+  fn removal_keeps_other_handles_valid() {
+    let mut set = MdrObjectSet::new();
+    let first = set.insert(named_object("first"));
+    let second = set.insert(named_object("second"));
+    let third = set.insert(named_object("third"));
+    set.remove(second);
+
+    assert_eq!(name_of(&set, first), Some("first"));
+    assert_eq!(name_of(&set, third), Some("third"));
+    assert_eq!(set.len(), 2);
+
+    let handles: Vec<MdrObjectHandle> = set.iter().map(|(handle, _)| handle).collect();
+    assert_eq!(handles, vec![first, third]);
   }
 }
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+
+use crate::resources::{MdrColorLut, MdrPostEffect};
+
+/// The full-screen effects applied to a scene after it is drawn. Every effect can be turned on,
+/// off, or adjusted between frames.
+///
+/// Ambient occlusion and then custom effects are applied first, to the scene's linear HDR
+/// colors. Bloom is then gathered from the result, and the scene is exposed, tone mapped, color
+/// graded, and vignetted in one pass. Anti-aliasing is applied last, to the tone mapped colors.
+#[derive(Default)]
+pub struct MdrPostProcessing {
+  pub ambient_occlusion: MdrAmbientOcclusion,
+  pub bloom: MdrBloom,
+  pub color_grading: MdrColorGrading,
+  pub vignette: MdrVignette,
+  pub antialiasing: MdrAntialiasing,
+  /// Effects loaded with `MdrResourceManager::load_post_effect()`, applied in order.
+  pub custom_effects: Vec<MdrCustomEffect>,
+}
+
+/// Screen-space ambient occlusion, a darkening of creases and of the places where objects
+/// meet, estimated from the depth buffer and from the G-buffer's normals when rendering deferred.
+/// With MSAA only the first depth sample of each pixel is read, so the occlusion isn't
+/// anti-aliased and pixels along the edges of objects may take it from either side of the edge.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct MdrAmbientOcclusion {
+  pub enabled: bool,
+  /// World space distance around each surface searched for occluding geometry.
+  pub radius: f32,
+  /// Depth difference below which geometry doesn't count as occluding, which stops flat
+  /// surfaces occluding themselves.
+  pub bias: f32,
+  /// How much the occlusion darkens the scene, from 0 for not at all to 1 for fully.
+  pub intensity: f32,
+}
+
+impl Default for MdrAmbientOcclusion {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      radius: 0.5,
+      bias: 0.025,
+      intensity: 1.0,
+    }
+  }
+}
+
+/// A glow spreading from the parts of the scene brighter than `threshold`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct MdrBloom {
+  pub enabled: bool,
+  /// Brightness above which colors begin to glow, before exposure is applied.
+  pub threshold: f32,
+  /// Width of the range below `threshold` over which the glow fades in, as a fraction of the
+  /// threshold. 0 gives a hard cut off.
+  pub knee: f32,
+  /// Factor the glow is multiplied by before it is added to the scene.
+  pub intensity: f32,
+  /// Distance in texels the glow is spread by at each of the `config::BLOOM_MIP_LEVELS`
+  /// levels it is blurred at.
+  pub radius: f32,
+}
+
+impl Default for MdrBloom {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      threshold: 1.0,
+      knee: 0.5,
+      intensity: 0.8,
+      radius: 1.0,
+    }
+  }
+}
+
+/// Remapping of the tone mapped colors through a 3D lookup table.
+#[derive(Debug)]
+pub struct MdrColorGrading {
+  /// The lookup table loaded with `MdrResourceManager::load_color_lut()`, or `None` to leave
+  /// colors as they are.
+  pub lut: Option<MdrColorLut>,
+  /// How much of the graded color is used, from 0 for none to 1 for all of it.
+  pub intensity: f32,
+}
+
+impl Default for MdrColorGrading {
+  fn default() -> Self {
+    Self {
+      lut: None,
+      intensity: 1.0,
+    }
+  }
+}
+
+/// A darkening of the edges of the screen.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct MdrVignette {
+  pub enabled: bool,
+  /// How dark the corners of the screen become, from 0 to 1.
+  pub intensity: f32,
+  /// Distance from the center of the screen at which darkening begins, where 1 is the middle of
+  /// each edge.
+  pub radius: f32,
+  /// Distance over which the darkening fades in beyond `radius`.
+  pub smoothness: f32,
+}
+
+impl Default for MdrVignette {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      intensity: 0.4,
+      radius: 0.75,
+      smoothness: 0.45,
+    }
+  }
+}
+
+/// Methods of smoothing jagged edges in the finished frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MdrAntialiasing {
+  #[default]
+  None,
+  /// Fast approximate anti-aliasing, which blurs along the high-contrast edges it finds.
+  Fxaa,
+}
+
+/// A custom post-processing effect and the values passed to its shader.
+#[derive(Debug)]
+pub struct MdrCustomEffect {
+  pub effect: MdrPostEffect,
+  pub enabled: bool,
+  /// Passed to the effect's shader as its `vec4` push constant.
+  pub parameters: [f32; 4],
+}
+
+impl MdrCustomEffect {
+  /// Creates an enabled instance of `effect` with every parameter set to 0.
+  pub fn new(effect: MdrPostEffect) -> Self {
+    Self {
+      effect,
+      enabled: true,
+      parameters: [0.0; 4],
+    }
+  }
+}
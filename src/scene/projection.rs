@@ -0,0 +1,203 @@
+use nalgebra::{Matrix4, Orthographic3, Perspective3};
+use serde::{Deserialize, Serialize};
+
+/// A projection from view space into clip space.
+pub trait MdrProjection {
+  /// Returns the projection matrix for a render target with the given `aspect_ratio`
+  /// (width / height). Depths between the clipping planes map to 0 to 1, as Vulkan expects.
+  fn projection_matrix(&self, aspect_ratio: f32) -> Matrix4<f32>;
+
+  /// Returns the distance from the viewer to the near clipping plane.
+  fn near_plane(&self) -> f32;
+
+  /// Returns the distance from the viewer to the far clipping plane.
+  fn far_plane(&self) -> f32;
+}
+
+/// A perspective projection, where objects appear smaller the further away they are.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct MdrPerspective {
+  /// The vertical field of view in radians.
+  pub field_of_view: f32,
+  pub near_plane: f32,
+  pub far_plane: f32,
+}
+
+impl MdrProjection for MdrPerspective {
+  fn projection_matrix(&self, aspect_ratio: f32) -> Matrix4<f32> {
+    let projection = Perspective3::new(
+      aspect_ratio,
+      self.field_of_view,
+      self.near_plane,
+      self.far_plane,
+    );
+
+    vulkan_depth_range(&projection.to_homogeneous())
+  }
+
+  fn near_plane(&self) -> f32 {
+    self.near_plane
+  }
+
+  fn far_plane(&self) -> f32 {
+    self.far_plane
+  }
+}
+
+impl Default for MdrPerspective {
+  fn default() -> Self {
+    Self {
+      field_of_view: std::f32::consts::FRAC_PI_2,
+      near_plane: 0.01,
+      far_plane: 1000.0,
+    }
+  }
+}
+
+/// An orthographic projection, where objects keep their size regardless of distance.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct MdrOrthographic {
+  /// The height of the visible area in world units, its width follows from the aspect ratio.
+  pub height: f32,
+  pub near_plane: f32,
+  pub far_plane: f32,
+}
+
+impl MdrProjection for MdrOrthographic {
+  fn projection_matrix(&self, aspect_ratio: f32) -> Matrix4<f32> {
+    let half_height = self.height / 2.0;
+    let half_width = half_height * aspect_ratio;
+
+    let projection = Orthographic3::new(
+      -half_width,
+      half_width,
+      -half_height,
+      half_height,
+      self.near_plane,
+      self.far_plane,
+    );
+
+    vulkan_depth_range(&projection.to_homogeneous())
+  }
+
+  fn near_plane(&self) -> f32 {
+    self.near_plane
+  }
+
+  fn far_plane(&self) -> f32 {
+    self.far_plane
+  }
+}
+
+impl Default for MdrOrthographic {
+  fn default() -> Self {
+    Self {
+      height: 10.0,
+      near_plane: 0.01,
+      far_plane: 1000.0,
+    }
+  }
+}
+
+/// Converts a projection which maps depths to the range -1 to 1, as nalgebra's do, to map them
+/// to the range 0 to 1 used by Vulkan, so that nothing between the clipping planes is clipped.
+pub(crate) fn vulkan_depth_range(projection: &Matrix4<f32>) -> Matrix4<f32> {
+  #[rustfmt::skip]
+  let correction = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.5,
+    0.0, 0.0, 0.0, 1.0,
+  );
+
+  correction * projection
+}
+
+/// The projections a camera can use, which can be switched between at runtime.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum MdrCameraProjection {
+  Perspective(MdrPerspective),
+  Orthographic(MdrOrthographic),
+}
+
+impl MdrCameraProjection {
+  /// Returns the projection as a trait object.
+  fn as_projection(&self) -> &dyn MdrProjection {
+    match self {
+      Self::Perspective(perspective) => perspective,
+      Self::Orthographic(orthographic) => orthographic,
+    }
+  }
+}
+
+impl MdrProjection for MdrCameraProjection {
+  fn projection_matrix(&self, aspect_ratio: f32) -> Matrix4<f32> {
+    self.as_projection().projection_matrix(aspect_ratio)
+  }
+
+  fn near_plane(&self) -> f32 {
+    self.as_projection().near_plane()
+  }
+
+  fn far_plane(&self) -> f32 {
+    self.as_projection().far_plane()
+  }
+}
+
+impl Default for MdrCameraProjection {
+  fn default() -> Self {
+    Self::Perspective(MdrPerspective::default())
+  }
+}
+
+impl From<MdrPerspective> for MdrCameraProjection {
+  fn from(perspective: MdrPerspective) -> Self {
+    Self::Perspective(perspective)
+  }
+}
+
+impl From<MdrOrthographic> for MdrCameraProjection {
+  fn from(orthographic: MdrOrthographic) -> Self {
+    Self::Orthographic(orthographic)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use nalgebra::Point3;
+
+  use super::*;
+
+  /// Asserts that points on the near and far planes of `projection` end up at depths 0 and 1.
+  fn assert_vulkan_depth_range(projection: &dyn MdrProjection) {
+    let matrix = projection.projection_matrix(16.0 / 9.0);
+    let near = matrix.transform_point(&Point3::new(0.0, 0.0, -projection.near_plane()));
+    let far = matrix.transform_point(&Point3::new(0.0, 0.0, -projection.far_plane()));
+
+    assert!(near.z.abs() < 1e-5, "near plane at depth {}", near.z);
+    assert!((far.z - 1.0).abs() < 1e-5, "far plane at depth {}", far.z);
+  }
+
+  #[test]
+  fn perspective_maps_clipping_planes_to_vulkan_depths() {
+    assert_vulkan_depth_range(&MdrPerspective {
+      field_of_view: 1.0,
+      near_plane: 0.1,
+      far_plane: 100.0,
+    });
+  }
+
+  #[test]
+  fn orthographic_maps_clipping_planes_to_vulkan_depths() {
+    assert_vulkan_depth_range(&MdrOrthographic {
+      height: 4.0,
+      near_plane: 0.5,
+      far_plane: 50.0,
+    });
+  }
+
+  #[test]
+  fn camera_projection_maps_clipping_planes_to_vulkan_depths() {
+    assert_vulkan_depth_range(&MdrCameraProjection::default());
+  }
+}
@@ -1,6 +1,12 @@
-use nalgebra::{Matrix4, Rotation3, Scale3, Translation3};
+use std::{fmt, ops::Mul};
 
-#[derive(Clone, Copy)]
+use nalgebra::{
+  Matrix3, Matrix4, Quaternion, Rotation3, Scale3, Translation3, Unit, UnitQuaternion, Vector3,
+  Vector4,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct MdrTransform {
   pub translation: MdrTranslation,
   pub rotation: MdrRotation,
@@ -31,10 +37,58 @@ impl MdrTransform {
       scale: MdrScale::identity(),
     }
   }
+
+  /// Decomposes an affine transformation matrix into a transform. The matrix is assumed to
+  /// have no shear, which holds for any matrix built by composing `MdrTransform`s with uniform
+  /// scales. A reflection becomes a negative scale along x, and a matrix scaled to zero along
+  /// an axis has no recoverable rotation, so the identity rotation is used.
+  pub(crate) fn from_matrix(matrix: &Matrix4<f32>) -> Self {
+    let translation = MdrTranslation {
+      x: matrix[(0, 3)],
+      y: matrix[(1, 3)],
+      z: matrix[(2, 3)],
+    };
+
+    // The length of each basis vector is the scale along that axis
+    let linear: Matrix3<f32> = matrix.fixed_slice::<3, 3>(0, 0).into_owned();
+    let mut scale = MdrScale {
+      x: linear.column(0).norm(),
+      y: linear.column(1).norm(),
+      z: linear.column(2).norm(),
+    };
+    if scale.x == 0.0 || scale.y == 0.0 || scale.z == 0.0 {
+      return Self {
+        translation,
+        rotation: MdrRotation::identity(),
+        scale,
+      };
+    }
+
+    // A rotation can't mirror, so a reflection is moved into the scale
+    if linear.determinant() < 0.0 {
+      scale.x = -scale.x;
+    }
+
+    // Removing the scale leaves the rotation
+    let rotation_matrix = Matrix3::from_columns(&[
+      linear.column(0) / scale.x,
+      linear.column(1) / scale.y,
+      linear.column(2) / scale.z,
+    ]);
+    let rotation = MdrRotation::from_quaternion(UnitQuaternion::from_rotation_matrix(
+      &Rotation3::from_matrix_unchecked(rotation_matrix),
+    ));
+
+    Self {
+      translation,
+      rotation,
+      scale,
+    }
+  }
 }
 
 /// Represents a translation along the x, y, and z axes.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct MdrTranslation {
   pub x: f32,
   pub y: f32,
@@ -79,44 +133,225 @@ impl From<MdrTranslation> for [f32; 3] {
   }
 }
 
-/// Represents a rotation in **degrees** around the x, y, and z axes.
-#[derive(Clone, Copy)]
-
+/// Represents a rotation as a unit quaternion, which can be composed and interpolated without
+/// suffering from gimbal lock. Rotations are serialized as quaternion components `[i, j, k, w]`,
+/// and the `(x, y, z)` angles written before rotations were quaternions are still accepted.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "MdrRotationComponents", into = "[f32; 4]")]
 pub struct MdrRotation {
-  pub x: f32,
-  pub y: f32,
-  pub z: f32,
+  quaternion: UnitQuaternion<f32>,
 }
 
 impl MdrRotation {
-  pub fn set(&mut self, x: f32, y: f32, z: f32) {
-    self.x = x;
-    self.y = y;
-    self.z = z;
-  }
-
   pub const fn identity() -> Self {
     Self {
-      x: 0.0,
-      y: 0.0,
-      z: 0.0,
+      quaternion: Unit::new_unchecked(Quaternion {
+        coords: Vector4::new(0.0, 0.0, 0.0, 1.0),
+      }),
+    }
+  }
+
+  /// Creates a rotation of `angle` radians around `axis`, which does not need to be normalized.
+  /// Returns the identity rotation if `axis` has zero length.
+  pub fn from_axis_angle(axis: &Vector3<f32>, angle: f32) -> Self {
+    match Unit::try_new(*axis, f32::EPSILON) {
+      Some(axis) => Self::from_quaternion(UnitQuaternion::from_axis_angle(&axis, angle)),
+      None => Self::identity(),
     }
   }
 
+  /// Creates a rotation from rotations of `angles` around the x, y, and z axes, measured in
+  /// `unit`. The rotations are applied one after another about the fixed parent axes, in the
+  /// axis order given by `order`.
+  pub fn from_euler(angles: [f32; 3], order: MdrEulerOrder, unit: MdrAngleUnit) -> Self {
+    let [x, y, z] = angles.map(|angle| unit.to_radians(angle));
+    let x = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), x);
+    let y = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), y);
+    let z = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), z);
+
+    // Rotations applied first are furthest to the right
+    let quaternion = match order {
+      MdrEulerOrder::XYZ => z * y * x,
+      MdrEulerOrder::XZY => y * z * x,
+      MdrEulerOrder::YXZ => z * x * y,
+      MdrEulerOrder::YZX => x * z * y,
+      MdrEulerOrder::ZXY => y * x * z,
+      MdrEulerOrder::ZYX => x * y * z,
+    };
+
+    Self::from_quaternion(quaternion)
+  }
+
+  /// Creates a rotation which turns the negative z axis, the direction cameras face, towards
+  /// `direction`, while keeping the y axis as close to `up` as possible. Returns the identity
+  /// rotation if `direction` has zero length or is parallel to `up`.
+  pub fn look_at(direction: &Vector3<f32>, up: &Vector3<f32>) -> Self {
+    if direction.cross(up).norm_squared() <= f32::EPSILON {
+      return Self::identity();
+    }
+
+    Self::from_quaternion(UnitQuaternion::face_towards(&-direction, up))
+  }
+
+  /// Creates a rotation from a unit quaternion.
+  pub fn from_quaternion(quaternion: UnitQuaternion<f32>) -> Self {
+    Self { quaternion }
+  }
+
+  /// Returns the rotation as a unit quaternion.
+  pub fn quaternion(&self) -> UnitQuaternion<f32> {
+    self.quaternion
+  }
+
+  /// Returns the rotation which undoes this one.
+  pub fn inverse(&self) -> Self {
+    Self::from_quaternion(self.quaternion.inverse())
+  }
+
+  /// Returns the rotation which applies this rotation followed by `next`.
+  pub fn then(&self, next: &MdrRotation) -> Self {
+    *next * *self
+  }
+
+  /// Rotates by `angle` radians around `axis` after the current rotation, i.e. around the
+  /// parent's axes.
+  pub fn rotate(&mut self, axis: &Vector3<f32>, angle: f32) {
+    *self = Self::from_axis_angle(axis, angle) * *self;
+  }
+
+  /// Rotates by `angle` radians around `axis` before the current rotation, i.e. around the
+  /// already rotated object's own axes.
+  pub fn rotate_local(&mut self, axis: &Vector3<f32>, angle: f32) {
+    *self = *self * Self::from_axis_angle(axis, angle);
+  }
+
+  /// Spherically interpolates between this rotation at `t = 0.0` and `other` at `t = 1.0`,
+  /// taking the shortest path.
+  pub fn slerp(&self, other: &MdrRotation, t: f32) -> Self {
+    // Quaternions 180 degrees apart have no unique shortest path, pick one rather than failing
+    match self
+      .quaternion
+      .try_slerp(&other.quaternion, t, f32::EPSILON)
+    {
+      Some(quaternion) => Self::from_quaternion(quaternion),
+      None => Self::from_quaternion(self.quaternion.nlerp(&other.quaternion, t)),
+    }
+  }
+
+  /// Applies the rotation to `vector`.
+  pub fn rotate_vector(&self, vector: &Vector3<f32>) -> Vector3<f32> {
+    self.quaternion * vector
+  }
+
   pub(crate) fn matrix(&self) -> Matrix4<f32> {
-    Rotation3::from_euler_angles(self.x, self.z, self.y).to_homogeneous()
+    self.quaternion.to_homogeneous()
   }
 
   pub(crate) fn inverse_matrix(&self) -> Matrix4<f32> {
-    Rotation3::from_euler_angles(self.x, self.z, self.y)
-      .inverse()
-      .to_homogeneous()
+    self.quaternion.inverse().to_homogeneous()
   }
 }
 
-/// Represents a scale along the x, y, and z axes.
-#[derive(Clone, Copy)]
+impl Mul for MdrRotation {
+  type Output = Self;
+
+  /// Composes two rotations, the result applies `rhs` first and then `self`.
+  fn mul(self, rhs: Self) -> Self {
+    Self::from_quaternion(self.quaternion * rhs.quaternion)
+  }
+}
+
+impl From<MdrRotation> for [f32; 4] {
+  fn from(rotation: MdrRotation) -> Self {
+    rotation.quaternion.coords.into()
+  }
+}
 
+impl TryFrom<[f32; 4]> for MdrRotation {
+  type Error = MdrRotationError;
+
+  /// Creates a rotation from quaternion components `[i, j, k, w]`, normalizing them. Fails if
+  /// the components are all zero, or so close to it that they can't be normalized.
+  fn try_from(coords: [f32; 4]) -> Result<Self, Self::Error> {
+    match UnitQuaternion::try_new(Quaternion::from(Vector4::from(coords)), f32::EPSILON) {
+      Some(quaternion) => Ok(Self::from_quaternion(quaternion)),
+      None => Err(MdrRotationError::DegenerateQuaternion),
+    }
+  }
+}
+
+/// The forms a rotation can be deserialized from.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MdrRotationComponents {
+  /// Quaternion components `[i, j, k, w]`.
+  Quaternion([f32; 4]),
+  /// Angles in radians, as saved before rotations were quaternions. They are applied as that
+  /// rotation's matrix was: `x` around the x axis, then `z` around the y axis, then `y` around
+  /// the z axis.
+  Euler { x: f32, y: f32, z: f32 },
+}
+
+impl TryFrom<MdrRotationComponents> for MdrRotation {
+  type Error = MdrRotationError;
+
+  fn try_from(components: MdrRotationComponents) -> Result<Self, Self::Error> {
+    match components {
+      MdrRotationComponents::Quaternion(coords) => Self::try_from(coords),
+      MdrRotationComponents::Euler { x, y, z } => Ok(Self::from_quaternion(
+        UnitQuaternion::from_euler_angles(x, z, y),
+      )),
+    }
+  }
+}
+
+#[derive(Debug)]
+/// Error emitted when creating an `MdrRotation`.
+pub enum MdrRotationError {
+  /// Emitted when quaternion components have zero length and so don't describe a rotation.
+  DegenerateQuaternion,
+}
+
+// Deserializing a rotation reports the error in its message
+impl fmt::Display for MdrRotationError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::DegenerateQuaternion => write!(f, "quaternion components have zero length"),
+    }
+  }
+}
+
+/// The order in which rotations about each axis are applied when building a rotation from
+/// Euler angles. `XYZ` rotates about x first, then y, then z.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MdrEulerOrder {
+  XYZ,
+  XZY,
+  YXZ,
+  YZX,
+  ZXY,
+  ZYX,
+}
+
+/// The unit an angle is measured in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MdrAngleUnit {
+  Radians,
+  Degrees,
+}
+
+impl MdrAngleUnit {
+  /// Converts `angle`, measured in this unit, into radians.
+  pub fn to_radians(self, angle: f32) -> f32 {
+    match self {
+      Self::Radians => angle,
+      Self::Degrees => angle.to_radians(),
+    }
+  }
+}
+
+/// Represents a scale along the x, y, and z axes.
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct MdrScale {
   pub x: f32,
   pub y: f32,
@@ -149,3 +384,128 @@ impl MdrScale {
       .to_homogeneous()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::f32::consts::FRAC_PI_2;
+
+  use super::*;
+
+  fn assert_near(actual: Vector3<f32>, expected: Vector3<f32>) {
+    assert!(
+      (actual - expected).norm() < 1e-5,
+      "expected {:?}, got {:?}",
+      expected,
+      actual
+    );
+  }
+
+  #[test]
+  fn euler_order_applies_first_axis_first() {
+    let angles = [90.0, 90.0, 0.0];
+    let xyz = MdrRotation::from_euler(angles, MdrEulerOrder::XYZ, MdrAngleUnit::Degrees);
+    let yxz = MdrRotation::from_euler(angles, MdrEulerOrder::YXZ, MdrAngleUnit::Degrees);
+
+    // Around x takes y to z, then around y takes z to x
+    assert_near(xyz.rotate_vector(&Vector3::y()), Vector3::x());
+    // Around y leaves y alone, then around x takes it to z
+    assert_near(yxz.rotate_vector(&Vector3::y()), Vector3::z());
+  }
+
+  #[test]
+  fn from_matrix_recovers_transform() {
+    let mut transform = MdrTransform::identity();
+    transform.translation.set(1.0, 2.0, 3.0);
+    transform.rotation = MdrRotation::from_axis_angle(&Vector3::new(1.0, 1.0, 0.0), 0.8);
+    transform.scale.set(2.0, 2.0, 2.0);
+
+    let recovered = MdrTransform::from_matrix(&transform.matrix());
+
+    assert!((recovered.matrix() - transform.matrix()).norm() < 1e-5);
+  }
+
+  #[test]
+  fn from_matrix_keeps_reflection_in_scale() {
+    let mut transform = MdrTransform::identity();
+    transform.rotation = MdrRotation::from_axis_angle(&Vector3::y(), 0.5);
+    transform.scale.set(1.0, -1.0, 1.0);
+
+    let recovered = MdrTransform::from_matrix(&transform.matrix());
+
+    assert!(recovered.scale.x < 0.0);
+    assert!((recovered.matrix() - transform.matrix()).norm() < 1e-5);
+  }
+
+  #[test]
+  fn from_matrix_with_zero_scale_has_identity_rotation() {
+    let mut transform = MdrTransform::identity();
+    transform.rotation = MdrRotation::from_axis_angle(&Vector3::z(), 0.5);
+    transform.scale.set(1.0, 0.0, 1.0);
+
+    let recovered = MdrTransform::from_matrix(&transform.matrix());
+
+    assert_eq!(<[f32; 4]>::from(recovered.rotation), [0.0, 0.0, 0.0, 1.0]);
+    assert_eq!(recovered.scale.y, 0.0);
+  }
+
+  #[test]
+  fn look_at_turns_forward_towards_direction() {
+    let rotation = MdrRotation::look_at(&Vector3::new(2.0, 0.0, 0.0), &Vector3::y());
+
+    assert_near(rotation.rotate_vector(&-Vector3::z()), Vector3::x());
+    assert_near(rotation.rotate_vector(&Vector3::y()), Vector3::y());
+  }
+
+  #[test]
+  fn look_at_along_up_is_identity() {
+    let rotation = MdrRotation::look_at(&Vector3::y(), &Vector3::y());
+
+    assert_near(rotation.rotate_vector(&Vector3::x()), Vector3::x());
+  }
+
+  #[test]
+  fn slerp_interpolates_angle() {
+    let start = MdrRotation::identity();
+    let end = MdrRotation::from_axis_angle(&Vector3::y(), FRAC_PI_2);
+
+    let start_vector = start.slerp(&end, 0.0).rotate_vector(&Vector3::x());
+    let middle = start.slerp(&end, 0.5);
+    let end_vector = start.slerp(&end, 1.0).rotate_vector(&Vector3::x());
+
+    assert_near(start_vector, Vector3::x());
+    assert!((middle.quaternion().angle() - FRAC_PI_2 / 2.0).abs() < 1e-5);
+    assert_near(end_vector, -Vector3::z());
+  }
+
+  #[test]
+  fn rotation_from_components_is_normalized() {
+    let rotation = MdrRotation::try_from([0.0, 0.0, 0.0, 2.0]).unwrap();
+
+    assert_eq!(<[f32; 4]>::from(rotation), [0.0, 0.0, 0.0, 1.0]);
+  }
+
+  #[test]
+  fn rotation_from_zero_components_fails() {
+    assert!(matches!(
+      MdrRotation::try_from([0.0; 4]),
+      Err(MdrRotationError::DegenerateQuaternion)
+    ));
+    assert!(ron::de::from_str::<MdrRotation>("(0.0, 0.0, 0.0, 0.0)").is_err());
+  }
+
+  #[test]
+  fn rotation_from_components_through_ron() {
+    let rotation: MdrRotation = ron::de::from_str("(0.0, 0.0, 0.0, 1.0)").unwrap();
+
+    assert_near(rotation.rotate_vector(&Vector3::x()), Vector3::x());
+  }
+
+  #[test]
+  fn rotation_from_legacy_angles_matches_legacy_matrix() {
+    let rotation: MdrRotation = ron::de::from_str("(x: 0.3, y: 1.2, z: -0.7)").unwrap();
+    let legacy_matrix = Rotation3::from_euler_angles(0.3, -0.7, 1.2);
+
+    let vector = Vector3::new(1.0, 2.0, 3.0);
+    assert_near(rotation.rotate_vector(&vector), legacy_matrix * vector);
+  }
+}
@@ -2,8 +2,11 @@ use std::time::Instant;
 
 use crate::{input::MdrInputState, scene::MdrScene};
 
+/// A user function which updates the scene each frame, see `set_update_function()`.
+pub(crate) type MdrUpdateFunction = Box<dyn FnMut(&mut MdrScene, &MdrInputState, f32)>;
+
 pub struct MdrUpdateContext {
-  update_function: Box<dyn FnMut(&mut MdrScene, &MdrInputState, f32)>,
+  update_function: MdrUpdateFunction,
 
   last_instant: Instant,
 }
@@ -21,7 +24,7 @@ impl MdrUpdateContext {
   ///   * `&mut MdrScene` - A mutable reference to the scene being updated.
   ///   * `&MdrInputState` - A reference to the input state this frame.
   ///   * `f32` - the time delta since last frame in seconds.
-  pub fn set_update_function(&mut self, f: Box<dyn FnMut(&mut MdrScene, &MdrInputState, f32)>) {
+  pub fn set_update_function(&mut self, f: MdrUpdateFunction) {
     self.update_function = f;
   }
 
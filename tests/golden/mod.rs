@@ -0,0 +1,278 @@
+//! Golden-image test harness. Scenes are rendered headlessly and compared pixel by pixel
+//! against reference PNGs stored in `tests/golden/reference/`.
+//!
+//! When the `MDR_BLESS` environment variable is set, the rendered frame is written as the new
+//! reference instead of being compared. A missing reference fails the test, so that new tests
+//! must be blessed and their references committed. On failure, the rendered frame and a diff
+//! image highlighting mismatched pixels are written to `<target tmpdir>/golden/`.
+
+use std::{
+  env, fs,
+  path::{Path, PathBuf},
+};
+
+use image::{Rgba, RgbaImage};
+use mdr_engine::{
+  resources::{
+    texture::{MdrFilter, MdrMipmapMode, MdrSamplerMode, MdrTextureCreateInfo},
+    MdrColorType, MdrMaterial, MdrMaterialCreateInfo, MdrRgb,
+  },
+  scene::{MdrLight, MdrObjectHandle, MdrRenderObject},
+  MdrEngine, MdrEngineOptions, MdrRenderPath,
+};
+
+/// Settings for a single golden-image comparison.
+pub struct GoldenTest<'a> {
+  /// Name of the test, used for the reference and output file names.
+  pub name: &'a str,
+  /// The `[width, height]` to render at.
+  pub dimensions: [u32; 2],
+  /// The largest per-channel difference at which two pixels are still considered equal.
+  pub tolerance: u8,
+  /// The number of pixels which may exceed `tolerance` before the test fails.
+  pub max_mismatched_pixels: usize,
+  /// The number of samples per pixel to render with.
+  pub msaa_samples: u32,
+  /// How the scene's objects are drawn and shaded.
+  pub render_path: MdrRenderPath,
+}
+
+impl<'a> GoldenTest<'a> {
+  pub fn new(name: &'a str) -> Self {
+    Self {
+      name,
+      dimensions: [256, 256],
+      tolerance: 2,
+      max_mismatched_pixels: 0,
+      msaa_samples: 1,
+      render_path: MdrRenderPath::Forward,
+    }
+  }
+
+  /// Creates a headless engine, builds the scene described by `build_scene` into it, renders
+  /// a frame, and compares the frame against the stored reference image.
+  pub fn run(&self, build_scene: impl FnOnce(&mut MdrEngine)) {
+    let options = MdrEngineOptions {
+      debug: false,
+      msaa_samples: self.msaa_samples,
+      render_path: self.render_path,
+    };
+    let mut engine = MdrEngine::new_headless(options, self.dimensions);
+    build_scene(&mut engine);
+
+    let frame = engine
+      .capture_frame()
+      .expect("Headless engine failed to capture a frame");
+
+    self.check(&frame);
+  }
+
+  /// Compares `frame` against the reference image, panicking with a description of the
+  /// mismatch if they differ.
+  fn check(&self, frame: &RgbaImage) {
+    let reference_path = reference_dir().join(format!("{}.png", self.name));
+
+    if env::var_os("MDR_BLESS").is_some() {
+      fs::create_dir_all(reference_dir()).unwrap();
+      frame.save(&reference_path).unwrap();
+      eprintln!("Wrote reference image {}", reference_path.display());
+      return;
+    }
+
+    if !reference_path.exists() {
+      let actual_path = self.write_output("actual", frame);
+      panic!(
+        "{}: no reference image at {}, frame written to {}. Run with MDR_BLESS=1 to accept it.",
+        self.name,
+        reference_path.display(),
+        actual_path.display()
+      );
+    }
+
+    let reference = image::open(&reference_path).unwrap().to_rgba8();
+    if reference.dimensions() != frame.dimensions() {
+      let actual_path = self.write_output("actual", frame);
+      panic!(
+        "{}: rendered frame is {:?} but the reference is {:?}, frame written to {}",
+        self.name,
+        frame.dimensions(),
+        reference.dimensions(),
+        actual_path.display()
+      );
+    }
+
+    let (diff, mismatched_pixels) = diff_images(&reference, frame, self.tolerance);
+    if mismatched_pixels > self.max_mismatched_pixels {
+      let actual_path = self.write_output("actual", frame);
+      let diff_path = self.write_output("diff", &diff);
+      panic!(
+        "{}: {} pixels differ from the reference by more than {} (allowed: {}).\n\
+        Rendered frame: {}\nDiff image: {}",
+        self.name,
+        mismatched_pixels,
+        self.tolerance,
+        self.max_mismatched_pixels,
+        actual_path.display(),
+        diff_path.display()
+      );
+    }
+  }
+
+  /// Writes `image` to the output directory with `suffix`, returning the path written to.
+  fn write_output(&self, suffix: &str, image: &RgbaImage) -> PathBuf {
+    let output_dir = output_dir();
+    fs::create_dir_all(&output_dir).unwrap();
+
+    let path = output_dir.join(format!("{}.{}.png", self.name, suffix));
+    image.save(&path).unwrap();
+    path
+  }
+}
+
+/// Compares two images of the same size, returning a diff image and the number of pixels with
+/// a channel differing by more than `tolerance`. Mismatched pixels are drawn in red on the diff
+/// image, and matching pixels are drawn as a faded copy of the reference.
+fn diff_images(reference: &RgbaImage, actual: &RgbaImage, tolerance: u8) -> (RgbaImage, usize) {
+  let mut mismatched_pixels = 0;
+  let diff = RgbaImage::from_fn(reference.width(), reference.height(), |x, y| {
+    let expected = reference.get_pixel(x, y);
+    let found = actual.get_pixel(x, y);
+
+    let is_mismatch = expected
+      .0
+      .iter()
+      .zip(found.0.iter())
+      .any(|(a, b)| a.abs_diff(*b) > tolerance);
+
+    if is_mismatch {
+      mismatched_pixels += 1;
+      Rgba([255, 0, 0, 255])
+    } else {
+      let luma = (expected[0] as u32 + expected[1] as u32 + expected[2] as u32) / 3;
+      let faded = (luma / 4) as u8;
+      Rgba([faded, faded, faded, 255])
+    }
+  });
+
+  (diff, mismatched_pixels)
+}
+
+fn reference_dir() -> PathBuf {
+  PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/reference")
+}
+
+fn output_dir() -> PathBuf {
+  PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+/// Returns the path to an asset shipped with the basic example.
+pub fn asset(asset_path: &str) -> String {
+  PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    .join("examples/basic/assets")
+    .join(asset_path)
+    .to_str()
+    .unwrap()
+    .to_string()
+}
+
+/// Loads the base color, roughness, normal, and occlusion maps of one of the example's texture
+/// sets, and its metalness map if it has one, and creates a material from them named after the
+/// set.
+pub fn load_texture_set_material(engine: &mut MdrEngine, set: &str) -> MdrMaterial {
+  let resources = engine.manage_resources();
+
+  let mut load = |map: &str, color_type: MdrColorType| {
+    resources
+      .load_texture(
+        MdrTextureCreateInfo {
+          source: asset(&format!("textures/{}/{}.png", set, map)).as_str(),
+          color_type,
+          sampler_mode: MdrSamplerMode::Repeat,
+          filter: MdrFilter::Linear,
+          mipmap_mode: MdrMipmapMode::Linear,
+          anisotropy: Some(16),
+        },
+        &format!("{}_{}", set, map),
+      )
+      .unwrap()
+  };
+  let diffuse = load("base_color", MdrColorType::SRGBA);
+  let roughness = load("roughness", MdrColorType::NonColorData);
+  let normal = load("normal", MdrColorType::NonColorData);
+  let occlusion = load("occlusion", MdrColorType::NonColorData);
+  let metallic = if Path::new(&asset(&format!("textures/{}/metalness.png", set))).exists() {
+    Some(load("metalness", MdrColorType::NonColorData))
+  } else {
+    None
+  };
+  let metallic_factor = if metallic.is_some() { 1.0 } else { 0.0 };
+
+  resources
+    .create_material(
+      MdrMaterialCreateInfo {
+        diffuse,
+        roughness,
+        normal,
+        metallic,
+        occlusion: Some(occlusion),
+        emissive: None,
+
+        metallic_factor,
+        roughness_factor: 1.0,
+        emissive_factor: MdrRgb::black(),
+      },
+      set,
+    )
+    .unwrap()
+}
+
+/// Adds the tiled sphere floating above a metal ground plane which most feature tests are drawn
+/// with, returning the sphere's handle.
+pub fn add_sphere_on_plane(engine: &mut MdrEngine) -> MdrObjectHandle {
+  let sphere_mesh = engine
+    .manage_resources()
+    .load_mesh(&asset("meshes/sphere.obj"), "sphere")
+    .unwrap();
+  let plane_mesh = engine
+    .manage_resources()
+    .load_mesh(&asset("meshes/plane.obj"), "plane")
+    .unwrap();
+  let sphere_material = load_texture_set_material(engine, "blue_tiles");
+  let plane_material = load_texture_set_material(engine, "metal_plates");
+
+  let mut sphere = MdrRenderObject::new(sphere_mesh, sphere_material);
+  sphere.transform.translation.set(0.0, -1.0, -3.0);
+  let sphere = engine.scene.add_object(sphere);
+  let mut ground_plane = MdrRenderObject::new(plane_mesh, plane_material);
+  ground_plane.transform.translation.set(0.0, 1.0, 0.0);
+  engine.scene.add_object(ground_plane);
+
+  sphere
+}
+
+/// Returns the white light above and to the side of `add_sphere_on_plane()`'s sphere, which
+/// lights its scenes.
+pub fn key_light() -> MdrLight {
+  let mut light = MdrLight::white(0.75);
+  light.translation.set(1.0, -3.0, 0.0);
+  light
+}
+
+/// Loads the example's sky as the scene's environment, and also draws it behind the scene if
+/// `as_skybox` is set.
+pub fn add_sky(engine: &mut MdrEngine, as_skybox: bool) {
+  let sky = engine
+    .manage_resources()
+    .load_environment(&asset("environments/sky.hdr"), "sky")
+    .unwrap();
+  engine.scene.environment = Some(sky);
+
+  if as_skybox {
+    engine.scene.skybox = Some(
+      engine
+        .manage_resources()
+        .retrieve_environment("sky")
+        .unwrap(),
+    );
+  }
+}
@@ -0,0 +1,328 @@
+//! Golden-image regression tests for the mesh pipeline. These require a Vulkan driver with a
+//! graphics queue, a CPU implementation such as lavapipe is sufficient, so they are ignored by
+//! default and run with `cargo test -- --ignored`.
+
+mod golden;
+
+use golden::{
+  add_sky, add_sphere_on_plane, asset, key_light, load_texture_set_material, GoldenTest,
+};
+use mdr_engine::{
+  resources::{
+    texture::{MdrFilter, MdrMipmapMode, MdrSamplerMode, MdrTextureCreateInfo},
+    MdrColorType, MdrMaterialCreateInfo, MdrRgb,
+  },
+  scene::{MdrAntialiasing, MdrLight, MdrRenderObject, MdrTonemapper},
+  MdrRenderPath,
+};
+use nalgebra::Vector3;
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn suzanne_point_light() {
+  GoldenTest::new("suzanne_point_light").run(|engine| {
+    let mesh = engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/suzanne.obj"), "monkey")
+      .unwrap();
+    let material = load_texture_set_material(engine, "white_bricks");
+
+    let mut monkey = MdrRenderObject::new(mesh, material);
+    monkey.transform.translation.set(0.0, 0.0, -2.0);
+    engine.scene.add_object(monkey);
+
+    let mut light = MdrLight::white(0.75);
+    light.translation.set(1.0, 3.0, 3.0);
+    engine.scene.lights.add_light(light);
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn textured_primitives() {
+  GoldenTest::new("textured_primitives").run(|engine| {
+    let sphere_mesh = engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/sphere.obj"), "sphere")
+      .unwrap();
+    let cube_mesh = engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/cube.obj"), "cube")
+      .unwrap();
+    let plane_mesh = engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/plane.obj"), "plane")
+      .unwrap();
+    let sphere_material = load_texture_set_material(engine, "blue_tiles");
+    let cube_material = load_texture_set_material(engine, "wood_planks");
+    let plane_material = load_texture_set_material(engine, "metal_plates");
+
+    let mut sphere = MdrRenderObject::new(sphere_mesh, sphere_material);
+    sphere.transform.translation.set(2.0, -2.0, -3.0);
+    engine.scene.add_object(sphere);
+    let mut cube = MdrRenderObject::new(cube_mesh, cube_material);
+    cube.transform.translation.set(-2.0, -2.0, -3.0);
+    engine.scene.add_object(cube);
+    let mut ground_plane = MdrRenderObject::new(plane_mesh, plane_material);
+    ground_plane.transform.translation.set(0.0, 1.0, 0.0);
+    engine.scene.add_object(ground_plane);
+
+    let mut light = MdrLight::white(0.75);
+    light.translation.set(1.0, 3.0, 3.0);
+    engine.scene.lights.add_light(light);
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn shadowed_primitives() {
+  GoldenTest::new("shadowed_primitives").run(|engine| {
+    add_sphere_on_plane(engine);
+
+    let mut light = key_light();
+    light.casts_shadows = true;
+    engine.scene.lights.add_light(light);
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn environment_lit_primitives() {
+  GoldenTest::new("environment_lit_primitives").run(|engine| {
+    add_sphere_on_plane(engine);
+
+    // No lights, only the environment
+    add_sky(engine, false);
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn skybox_background() {
+  GoldenTest::new("skybox_background").run(|engine| {
+    let sphere_mesh = engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/sphere.obj"), "sphere")
+      .unwrap();
+    let sphere_material = load_texture_set_material(engine, "blue_tiles");
+
+    let mut sphere = MdrRenderObject::new(sphere_mesh, sphere_material);
+    sphere.transform.translation.set(0.0, 0.0, -3.0);
+    engine.scene.add_object(sphere);
+
+    // The sky both lights the sphere and is drawn behind it
+    add_sky(engine, true);
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn compressed_texture() {
+  GoldenTest::new("compressed_texture").run(|engine| {
+    let cube_mesh = engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/cube.obj"), "cube")
+      .unwrap();
+
+    // A BC1 checkerboard with pre-built mip levels
+    let diffuse = engine
+      .manage_resources()
+      .load_texture(
+        MdrTextureCreateInfo {
+          source: asset("textures/checker.dds").as_str(),
+          color_type: MdrColorType::SRGBA,
+          sampler_mode: MdrSamplerMode::Repeat,
+          filter: MdrFilter::Linear,
+          mipmap_mode: MdrMipmapMode::Linear,
+          anisotropy: Some(16),
+        },
+        "checker",
+      )
+      .unwrap();
+    let roughness = engine
+      .manage_resources()
+      .create_solid_texture([0.6, 0.6, 0.6].into(), "checker_roughness")
+      .unwrap();
+    let normal = engine
+      .manage_resources()
+      .create_solid_texture([0.5, 0.5, 1.0].into(), "checker_normal")
+      .unwrap();
+    let material = engine
+      .manage_resources()
+      .create_material(
+        MdrMaterialCreateInfo {
+          diffuse,
+          roughness,
+          normal,
+          metallic: None,
+          occlusion: None,
+          emissive: None,
+
+          metallic_factor: 0.0,
+          roughness_factor: 1.0,
+          emissive_factor: MdrRgb::black(),
+        },
+        "checker",
+      )
+      .unwrap();
+
+    let mut cube = MdrRenderObject::new(cube_mesh, material);
+    cube.transform.translation.set(0.0, 0.0, -3.0);
+    engine.scene.add_object(cube);
+
+    let mut light = MdrLight::white(0.75);
+    light.translation.set(1.0, -3.0, 3.0);
+    engine.scene.lights.add_light(light);
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn tonemapped_bright_light() {
+  GoldenTest::new("tonemapped_bright_light").run(|engine| {
+    let mesh = engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/suzanne.obj"), "monkey")
+      .unwrap();
+    let material = load_texture_set_material(engine, "white_bricks");
+
+    let mut monkey = MdrRenderObject::new(mesh, material);
+    monkey.transform.translation.set(0.0, 0.0, -2.0);
+    engine.scene.add_object(monkey);
+
+    // A light far brighter than white, which would clip without tone mapping
+    let mut light = MdrLight::white(12.0);
+    light.translation.set(1.0, 3.0, 3.0);
+    engine.scene.lights.add_light(light);
+
+    engine.scene.camera.exposure = -1.0;
+    engine.scene.camera.tonemapper = MdrTonemapper::AgX;
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn post_processing_effects() {
+  GoldenTest::new("post_processing_effects").run(|engine| {
+    let mesh = engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/suzanne.obj"), "monkey")
+      .unwrap();
+    let material = load_texture_set_material(engine, "white_bricks");
+
+    let mut monkey = MdrRenderObject::new(mesh, material);
+    monkey.transform.translation.set(0.0, 0.0, -2.0);
+    engine.scene.add_object(monkey);
+
+    // Highlights bright enough to bloom
+    let mut light = MdrLight::white(12.0);
+    light.translation.set(1.0, 3.0, 3.0);
+    engine.scene.lights.add_light(light);
+
+    let lut = engine
+      .manage_resources()
+      .load_color_lut(&asset("luts/warm.cube"), "warm")
+      .unwrap();
+    let post_processing = &mut engine.scene.post_processing;
+    post_processing.bloom.enabled = true;
+    post_processing.color_grading.lut = Some(lut);
+    post_processing.vignette.enabled = true;
+    post_processing.antialiasing = MdrAntialiasing::Fxaa;
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn multisampled_thin_geometry() {
+  let test = GoldenTest {
+    msaa_samples: 4,
+    ..GoldenTest::new("multisampled_thin_geometry")
+  };
+  test.run(|engine| {
+    engine
+      .manage_resources()
+      .load_mesh(&asset("meshes/cube.obj"), "cube")
+      .unwrap();
+    load_texture_set_material(engine, "white_bricks");
+
+    // Thin bars at shallow angles, whose edges alias badly with one sample per pixel
+    for (index, angle) in [0.05_f32, 0.3, 0.8].into_iter().enumerate() {
+      let resources = engine.manage_resources();
+      let mut bar = MdrRenderObject::new(
+        resources.retrieve_mesh("cube").unwrap(),
+        resources.retrieve_material("white_bricks").unwrap(),
+      );
+      bar
+        .transform
+        .translation
+        .set(0.0, index as f32 * 0.4 - 0.4, -3.0);
+      bar.transform.scale.set(1.5, 0.02, 0.02);
+      bar
+        .transform
+        .rotation
+        .rotate(&Vector3::new(0.0, 0.0, 1.0), angle);
+      engine.scene.add_object(bar);
+    }
+
+    let mut light = MdrLight::white(2.0);
+    light.translation.set(0.0, 0.0, 2.0);
+    engine.scene.lights.add_light(light);
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn deferred_shading() {
+  let test = GoldenTest {
+    render_path: MdrRenderPath::Deferred,
+    ..GoldenTest::new("deferred_shading")
+  };
+  test.run(|engine| {
+    add_sphere_on_plane(engine);
+
+    // Lights, shadows, and the environment are all applied in the lighting pass, while the
+    // skybox is drawn behind the G-buffer
+    let mut light = key_light();
+    light.casts_shadows = true;
+    engine.scene.lights.add_light(light);
+    let mut fill_light = MdrLight::white(0.3);
+    fill_light.translation.set(-2.0, -1.0, -1.0);
+    engine.scene.lights.add_light(fill_light);
+
+    add_sky(engine, true);
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn ambient_occlusion() {
+  let test = GoldenTest::new("ambient_occlusion");
+  test.run(|engine| {
+    // The sphere rests on the plane, so the occlusion gathers where they touch
+    let sphere = add_sphere_on_plane(engine);
+    let sphere = engine.scene.get_mut(sphere).unwrap();
+    sphere.transform.translation.set(0.0, 0.0, -3.0);
+    engine.scene.lights.add_light(key_light());
+
+    engine.scene.post_processing.ambient_occlusion.enabled = true;
+  });
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and blessed reference images"]
+fn multisampled_ambient_occlusion() {
+  let test = GoldenTest {
+    msaa_samples: 4,
+    ..GoldenTest::new("multisampled_ambient_occlusion")
+  };
+  test.run(|engine| {
+    // Occlusion is found from the first depth sample of each pixel, while edges are resolved
+    // from all of them
+    let sphere = add_sphere_on_plane(engine);
+    let sphere = engine.scene.get_mut(sphere).unwrap();
+    sphere.transform.translation.set(0.0, 0.0, -3.0);
+    engine.scene.lights.add_light(key_light());
+
+    engine.scene.post_processing.ambient_occlusion.enabled = true;
+  });
+}